@@ -1,5 +1,5 @@
 use crate::constant::CAMERA_SPEED;
-use crate::entity::actor::Actor;
+use crate::entity::actor::{Actor, ActorStats};
 use crate::{controller::player::Player, states::GameState};
 use bevy::core::FrameCount;
 use bevy::prelude::*;
@@ -12,6 +12,9 @@ pub struct GameCamera {
     pub y: f32,
     pub scale_factor: f32,
     pub vibration: f32,
+
+    /// ボスの登場演出などで、プレイヤーの代わりに注目する位置
+    pub focus: Option<Vec2>,
 }
 
 static BLIGHTNESS_IN_GAME: f32 = 0.01;
@@ -34,6 +37,7 @@ fn setup_camera(mut commands: Commands) {
             y: 0.0,
             scale_factor: initial_scale_factor,
             vibration: 0.0,
+            focus: None,
         },
         // カメラにAmbiendLight2dを追加すると、画面全体が暗くなり、
         // 光が当たっていない部分の明るさを設定できます
@@ -45,22 +49,27 @@ fn setup_camera(mut commands: Commands) {
 }
 
 fn update_camera_position(
-    player_query: Query<(&Transform, &Actor), With<Player>>,
+    player_query: Query<(&Transform, &Actor, &ActorStats), With<Player>>,
     mut camera_query: Query<
         (&mut Transform, &mut OrthographicProjection, &mut GameCamera),
         (With<Camera2d>, Without<Player>),
     >,
     frame_count: Res<FrameCount>,
 ) {
-    if let Ok((player, actor)) = player_query.get_single() {
+    if let Ok((player, actor, stats)) = player_query.get_single() {
         if let Ok((mut camera, mut ortho, mut scale_factor)) = camera_query.get_single_mut() {
             // ポインターのある方向にカメラをずらして遠方を見やすくする係数
             // カメラがブレるように感じて酔いやすい？
             let point_by_mouse_factor = 0.0; // 0.2;
 
-            let vrp = player.translation.truncate()
-                + actor.pointer.normalize_or_zero()
-                    * (actor.pointer.length() * point_by_mouse_factor).min(50.0);
+            let vrp = match scale_factor.focus {
+                Some(focus) => focus,
+                None => {
+                    player.translation.truncate()
+                        + actor.pointer.normalize_or_zero()
+                            * (actor.pointer.length() * point_by_mouse_factor).min(50.0)
+                }
+            };
 
             scale_factor.x += (vrp.x - scale_factor.x) * CAMERA_SPEED;
             scale_factor.y += (vrp.y - scale_factor.y) * CAMERA_SPEED;
@@ -70,7 +79,7 @@ fn update_camera_position(
                 scale_factor.y + (frame_count.0 as f32 * 5.0).sin() * scale_factor.vibration;
 
             scale_factor.vibration = (scale_factor.vibration - 0.5).max(0.0);
-            scale_factor.scale_factor = actor.get_total_scale_factor();
+            scale_factor.scale_factor = stats.scale_factor;
 
             let s = ortho.scale.log2();
             ortho.scale = (2.0_f32).powf(s + (scale_factor.scale_factor - s) * 0.2);
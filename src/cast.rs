@@ -3,9 +3,12 @@ use crate::{
     constant::{
         ENEMY_BULLET_GROUP, ENEMY_GROUP, ENTITY_GROUP, WALL_GROUP, WITCH_BULLET_GROUP, WITCH_GROUP,
     },
-    controller::remote::{send_remote_message, RemoteMessage},
+    controller::{
+        network_simulator::OutgoingMessage,
+        remote::{send_remote_message, RemoteMessage},
+    },
     entity::{
-        actor::{Actor, ActorGroup},
+        actor::{Actor, ActorGroup, ActorStats, Dashing},
         bullet::{spawn_bullet, SpawnBullet, BULLET_SPAWNING_MARGIN},
         life::Life,
         slime_seed::SpawnSlimeSeed,
@@ -17,20 +20,96 @@ use crate::{
 };
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::ExternalImpulse;
-use bevy_simple_websocket::ClientMessage;
 use rand::random;
 use uuid::Uuid;
 
-/// 現在のインデックスをもとに呪文を唱えます
-/// マナが不足している場合は不発になる場合もあります
+/// ダッシュしてから Dashing が取り除かれるまでのフレーム数
+const DASH_FRAMES: u32 = 20;
+
+/// 杖を一回振ります
+/// 常時詠唱の呪文があれば、スロットの呪文より先に唱えます
+/// 返り値は杖の詠唱遅延を加えた詠唱遅延で、スロットの呪文を一巡したときは杖のリロード時間も加わります
+pub fn cast_wand(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    writer: &mut EventWriter<OutgoingMessage>,
+    se_writer: &mut EventWriter<SEEvent>,
+    actor_entity: Entity,
+    actor: &mut Actor,
+    stats: &ActorStats,
+    actor_life: &mut Life,
+    actor_transform: &Transform,
+    actor_impulse: &mut ExternalImpulse,
+    online: bool,
+    slime_writer: &mut EventWriter<SpawnSlimeSeed>,
+    wand_index: usize,
+) -> i32 {
+    let (item, start) = match actor.wands[wand_index] {
+        Some(ref wand) => (wand.item, wand.index),
+        None => return 0,
+    };
+
+    let mut delay = 0;
+    if let Some(spell_type) = item.always_cast {
+        delay = cast_spell_type(
+            commands,
+            assets,
+            writer,
+            se_writer,
+            actor_entity,
+            actor,
+            stats,
+            actor_life,
+            actor_transform,
+            actor_impulse,
+            online,
+            slime_writer,
+            spell_type,
+            item.spread,
+            wand_index,
+        );
+    }
+    delay = delay.max(cast_spell(
+        commands,
+        assets,
+        writer,
+        se_writer,
+        actor_entity,
+        actor,
+        stats,
+        actor_life,
+        actor_transform,
+        actor_impulse,
+        online,
+        slime_writer,
+        wand_index,
+    ));
+
+    if 0 < delay {
+        delay = (delay + item.cast_delay).max(1);
+    }
+
+    let end = actor.wands[wand_index]
+        .as_ref()
+        .map(|wand| wand.index)
+        .unwrap_or(0);
+    if end <= start {
+        delay += item.reload as i32;
+    }
+
+    delay
+}
+
+/// 現在のインデックスをもとに呪文を唱え、インデックスを次の呪文に進めます
 /// 返り値として詠唱で生じた詠唱遅延を返すので、呼び出し元はその値をアクターの詠唱遅延に加算する必要があります。
 pub fn cast_spell(
     commands: &mut Commands,
     assets: &Res<GameAssets>,
-    writer: &mut EventWriter<ClientMessage>,
+    writer: &mut EventWriter<OutgoingMessage>,
     se_writer: &mut EventWriter<SEEvent>,
     actor_entity: Entity,
     actor: &mut Actor,
+    stats: &ActorStats,
     actor_life: &mut Life,
     actor_transform: &Transform,
     actor_impulse: &mut ExternalImpulse,
@@ -38,156 +117,191 @@ pub fn cast_spell(
     slime_writer: &mut EventWriter<SpawnSlimeSeed>,
     wand_index: usize,
 ) -> i32 {
-    if let Some(ref mut wand) = &mut actor.wands[wand_index] {
-        if let Some(spell) = wand.slots[wand.index] {
-            let props = spell.spell_type.to_props();
-
-            match props.cast {
-                SpellCast::Bullet {
-                    slice,
-                    collier_radius,
-                    speed,
-                    lifetime,
-                    damage,
-                    impulse,
-                    scattering,
-                    light_intensity,
-                    light_radius,
-                    light_color_hlsa,
-                } => {
-                    let normalized = actor.pointer.normalize();
-                    let angle = actor.pointer.to_angle();
-                    let angle_with_random = angle + (random::<f32>() - 0.5) * scattering;
-                    let direction = Vec2::from_angle(angle_with_random);
-                    let range = WITCH_COLLIDER_RADIUS + BULLET_SPAWNING_MARGIN;
-                    let bullet_position =
-                        actor_transform.translation.truncate() + range * normalized;
-
-                    let spawn = SpawnBullet {
-                        uuid: Uuid::new_v4(),
-                        position: bullet_position,
-                        velocity: direction
-                            * speed
-                            * (1.0 + actor.effects.bullet_speed_buff_factor),
-                        bullet_lifetime: lifetime,
-                        sender: Some(actor.uuid),
-                        damage: damage + actor.effects.bullet_damage_buff_amount,
-                        impulse,
-                        slice: slice.to_string(),
-                        collier_radius,
-                        light_intensity,
-                        light_radius,
-                        light_color_hlsa,
-                        homing: actor.effects.homing,
-                        group: match actor.actor_group {
-                            ActorGroup::Player => WITCH_BULLET_GROUP,
-                            ActorGroup::Enemy => ENEMY_BULLET_GROUP,
-                        },
-                        filter: match actor.actor_group {
-                            ActorGroup::Player => ENEMY_GROUP,
-                            ActorGroup::Enemy => WITCH_GROUP,
-                        } | ENTITY_GROUP
-                            | WALL_GROUP,
-                    };
-
-                    spawn_bullet(commands, assets.atlas.clone(), se_writer, &spawn);
-                    actor.effects = default();
-                    wand.shift();
-
-                    send_remote_message(writer, online, &RemoteMessage::Fire(spawn));
-
-                    return props.cast_delay as i32;
-                }
-                SpellCast::BulletSpeedUpDown { delta } => {
-                    wand.shift();
-                    actor.effects.bullet_speed_buff_factor =
-                        (actor.effects.bullet_speed_buff_factor + delta)
-                            .max(-0.9)
-                            .min(3.0);
-
-                    return props.cast_delay as i32;
-                }
-                SpellCast::Heal => {
-                    wand.shift();
-
-                    if spell.spell_type == SpellType::Heal && actor_life.life == actor_life.max_life
-                    {
-                        return 0;
-                    }
-
-                    actor_life.life = (actor_life.life + 2).min(actor_life.max_life);
-                    se_writer.send(SEEvent::pos(
-                        SE::Heal,
-                        actor_transform.translation.truncate(),
-                    ));
-
-                    return props.cast_delay as i32;
-                }
-                SpellCast::MultipleCast { amount } => {
-                    wand.shift();
-                    let mut delay = 0;
-                    for _ in 0..amount {
-                        delay = delay.max(cast_spell(
-                            commands,
-                            assets,
-                            writer,
-                            se_writer,
-                            actor_entity,
-                            actor,
-                            actor_life,
-                            actor_transform,
-                            actor_impulse,
-                            online,
-                            slime_writer,
-                            wand_index,
-                        ));
-                    }
-                    return delay;
-                }
-                SpellCast::Homing => {
-                    wand.shift();
-                    actor.effects.homing = (actor.effects.homing + 0.01).max(-0.1).min(0.1);
-                    return props.cast_delay as i32;
-                }
-                SpellCast::HeavyShot => {
-                    wand.shift();
-                    actor.effects.bullet_damage_buff_amount += 5;
-                    return props.cast_delay as i32;
-                }
-                SpellCast::SummonSlime { friend } => {
-                    wand.shift();
-                    slime_writer.send(SpawnSlimeSeed {
-                        from: actor_transform.translation.truncate(),
-                        to: actor_transform.translation.truncate() + actor.pointer,
-                        owner: actor_entity,
-                        actor_group: match (actor.actor_group, friend) {
-                            (ActorGroup::Player, true) => ActorGroup::Player,
-                            (ActorGroup::Player, false) => ActorGroup::Enemy,
-                            (ActorGroup::Enemy, true) => ActorGroup::Enemy,
-                            (ActorGroup::Enemy, false) => ActorGroup::Player,
-                        },
-                    });
-                    return props.cast_delay as i32;
-                }
-                SpellCast::Dash => {
-                    wand.shift();
-                    actor_impulse.impulse += if 0.0 < actor.move_direction.length() {
-                        actor.move_direction
-                    } else {
-                        actor.pointer.normalize()
-                    } * 50000.0;
-                    se_writer.send(SEEvent::pos(
-                        SE::Shuriken,
-                        actor_transform.translation.truncate(),
-                    ));
-                    return props.cast_delay as i32;
-                }
-            }
-        } else {
+    let (spell, spread) = match actor.wands[wand_index] {
+        Some(ref mut wand) => {
+            let spell = wand.slots[wand.index];
             wand.shift();
-            return 0;
+            (spell, wand.item.spread)
+        }
+        None => return 0,
+    };
+
+    match spell {
+        Some(spell) => cast_spell_type(
+            commands,
+            assets,
+            writer,
+            se_writer,
+            actor_entity,
+            actor,
+            stats,
+            actor_life,
+            actor_transform,
+            actor_impulse,
+            online,
+            slime_writer,
+            spell.spell_type,
+            spread,
+            wand_index,
+        ),
+        None => 0,
+    }
+}
+
+/// 指定した呪文を唱えます
+/// spread は杖による弾丸のばらつきの増加分(度)です
+fn cast_spell_type(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    writer: &mut EventWriter<OutgoingMessage>,
+    se_writer: &mut EventWriter<SEEvent>,
+    actor_entity: Entity,
+    actor: &mut Actor,
+    stats: &ActorStats,
+    actor_life: &mut Life,
+    actor_transform: &Transform,
+    actor_impulse: &mut ExternalImpulse,
+    online: bool,
+    slime_writer: &mut EventWriter<SpawnSlimeSeed>,
+    spell_type: SpellType,
+    spread: u32,
+    wand_index: usize,
+) -> i32 {
+    let props = spell_type.to_props();
+
+    match props.cast {
+        SpellCast::Bullet {
+            slice,
+            collier_radius,
+            speed,
+            lifetime,
+            damage,
+            impulse,
+            scattering,
+            light_intensity,
+            light_radius,
+            light_color_hlsa,
+        } => {
+            let normalized = actor.pointer.normalize();
+            let angle = actor.pointer.to_angle();
+            let scattering = scattering + (spread as f32).to_radians();
+            let angle_with_random = angle + (random::<f32>() - 0.5) * scattering;
+            let direction = Vec2::from_angle(angle_with_random);
+            let range = WITCH_COLLIDER_RADIUS + BULLET_SPAWNING_MARGIN;
+            let bullet_position = actor_transform.translation.truncate() + range * normalized;
+
+            let spawn = SpawnBullet {
+                uuid: Uuid::new_v4(),
+                position: bullet_position,
+                velocity: direction
+                    * speed
+                    * (1.0 + actor.effects.bullet_speed_buff_factor + stats.bullet_speed),
+                bullet_lifetime: lifetime,
+                sender: Some(actor.uuid),
+                damage: damage + actor.effects.bullet_damage_buff_amount + stats.damage,
+                impulse,
+                slice: slice.to_string(),
+                collier_radius,
+                light_intensity,
+                light_radius,
+                light_color_hlsa,
+                homing: actor.effects.homing,
+                group: match actor.actor_group {
+                    ActorGroup::Player => WITCH_BULLET_GROUP,
+                    ActorGroup::Enemy => ENEMY_BULLET_GROUP,
+                },
+                filter: match actor.actor_group {
+                    ActorGroup::Player => ENEMY_GROUP,
+                    ActorGroup::Enemy => WITCH_GROUP,
+                } | ENTITY_GROUP
+                    | WALL_GROUP,
+            };
+
+            spawn_bullet(commands, assets.atlas.clone(), se_writer, &spawn);
+            actor.effects = default();
+
+            send_remote_message(writer, online, &RemoteMessage::Fire(spawn));
+
+            return props.cast_delay as i32;
+        }
+        SpellCast::BulletSpeedUpDown { delta } => {
+            actor.effects.bullet_speed_buff_factor = (actor.effects.bullet_speed_buff_factor
+                + delta)
+                .max(-0.9)
+                .min(3.0);
+
+            return props.cast_delay as i32;
+        }
+        SpellCast::Heal => {
+            if spell_type == SpellType::Heal && actor_life.life == actor_life.max_life {
+                return 0;
+            }
+
+            actor_life.life = (actor_life.life + 2).min(actor_life.max_life);
+            se_writer.send(SEEvent::pos(
+                SE::Heal,
+                actor_transform.translation.truncate(),
+            ));
+
+            return props.cast_delay as i32;
+        }
+        SpellCast::MultipleCast { amount } => {
+            let mut delay = 0;
+            for _ in 0..amount {
+                delay = delay.max(cast_spell(
+                    commands,
+                    assets,
+                    writer,
+                    se_writer,
+                    actor_entity,
+                    actor,
+                    stats,
+                    actor_life,
+                    actor_transform,
+                    actor_impulse,
+                    online,
+                    slime_writer,
+                    wand_index,
+                ));
+            }
+            return delay;
+        }
+        SpellCast::Homing => {
+            actor.effects.homing = (actor.effects.homing + 0.01).max(-0.1).min(0.1);
+            return props.cast_delay as i32;
+        }
+        SpellCast::HeavyShot => {
+            actor.effects.bullet_damage_buff_amount += 5;
+            return props.cast_delay as i32;
+        }
+        SpellCast::SummonSlime { friend } => {
+            slime_writer.send(SpawnSlimeSeed {
+                from: actor_transform.translation.truncate(),
+                to: actor_transform.translation.truncate() + actor.pointer,
+                owner: actor_entity,
+                actor_group: match (actor.actor_group, friend) {
+                    (ActorGroup::Player, true) => ActorGroup::Player,
+                    (ActorGroup::Player, false) => ActorGroup::Enemy,
+                    (ActorGroup::Enemy, true) => ActorGroup::Enemy,
+                    (ActorGroup::Enemy, false) => ActorGroup::Player,
+                },
+            });
+            return props.cast_delay as i32;
+        }
+        SpellCast::Dash => {
+            commands
+                .entity(actor_entity)
+                .try_insert(Dashing(DASH_FRAMES));
+            actor_impulse.impulse += if 0.0 < actor.move_direction.length() {
+                actor.move_direction
+            } else {
+                actor.pointer.normalize()
+            } * 50000.0;
+            se_writer.send(
+                SEEvent::pos(SE::Shuriken, actor_transform.translation.truncate())
+                    .with_source(actor.actor_group),
+            );
+            return props.cast_delay as i32;
         }
-    } else {
-        return 0;
     }
 }
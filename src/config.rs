@@ -10,6 +10,15 @@ pub struct GameConfig {
     pub player_name: String,
     pub language: Languages,
     pub fullscreen: bool,
+
+    /// 協力プレイがオンの場合は、通常のレベルでもサーバーに接続して他のプレイヤーと一緒に冒険します
+    #[serde(default)]
+    pub coop: bool,
+
+    /// 協力プレイの合言葉です
+    /// 同じ合言葉を入力したプレイヤーどうしだけが一緒に冒険します
+    #[serde(default)]
+    pub room: String,
 }
 
 impl Default for GameConfig {
@@ -20,6 +29,8 @@ impl Default for GameConfig {
             player_name: "".to_string(),
             language: Languages::Ja,
             fullscreen: false,
+            coop: false,
+            room: "".to_string(),
         }
     }
 }
@@ -12,6 +12,10 @@ pub const CRATE_NAME: &str = "magiaforge";
 
 pub const WEBSOCKET_URL: &str = "wss://magia-server-38847751193.asia-northeast1.run.app";
 
+/// プレイヤーの状態を送信する間隔(フレーム数)
+/// FixedUpdate は64Hzなので、4フレームごとで毎秒16回になります
+pub const DEFAULT_SYNC_SEND_INTERVAL: u32 = 4;
+
 pub const DEFAULT_BGM_VOLUME: f32 = 0.4;
 
 pub const DEFAULT_SE_VOLUME: f32 = 0.8;
@@ -0,0 +1,220 @@
+use crate::asset::GameAssets;
+use crate::controller::player::Player;
+use crate::entity::actor::Actor;
+use crate::entity::bomb::spawn_bomb;
+use crate::entity::life::Life;
+use crate::hud::overlay::OverlayEvent;
+use crate::inventory_item::InventoryItemType;
+use crate::language::Dict;
+use crate::level::{CurrentLevel, GameLevel};
+use crate::player_state::PlayerState;
+use crate::se::{SEEvent, SE};
+use crate::states::{GameMenuState, GameState};
+use bevy::prelude::*;
+use bevy_rapier2d::plugin::PhysicsSet;
+use serde::{Deserialize, Serialize};
+
+/// 回復薬で回復するライフ
+const POTION_HEAL: i32 = 20;
+
+/// 爆弾を投げる速さ
+const THROW_SPEED: f32 = 200.0;
+
+/// 使うとなくなるアイテムです
+/// インベントリでは同じ種類のものを重ねて持つことができます
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
+pub enum ConsumableType {
+    HealingPotion,
+    TeleportScroll,
+    Bomb,
+}
+
+pub const CONSUMABLES: [ConsumableType; 3] = [
+    ConsumableType::HealingPotion,
+    ConsumableType::TeleportScroll,
+    ConsumableType::Bomb,
+];
+
+pub struct ConsumableProps {
+    pub name: Dict,
+    pub description: Dict,
+    pub icon: &'static str,
+
+    /// ショップでの基本価格
+    pub price: u32,
+
+    /// インベントリのひとつのスロットに重ねられる数
+    pub max_stack: u32,
+}
+
+impl ConsumableType {
+    pub fn to_props(&self) -> ConsumableProps {
+        // 専用のアイコンができるまで、既存のスライスを流用しています
+        match self {
+            ConsumableType::HealingPotion => ConsumableProps {
+                name: Dict {
+                    ja: "回復薬",
+                    en: "Healing Potion",
+                },
+                description: Dict {
+                    ja: "飲むとライフが回復する。",
+                    en: "Restores life when drunk.",
+                },
+                icon: "spell_heal",
+                price: 30,
+                max_stack: 5,
+            },
+            ConsumableType::TeleportScroll => ConsumableProps {
+                name: Dict {
+                    ja: "帰還の巻物",
+                    en: "Teleport Scroll",
+                },
+                description: Dict {
+                    ja: "読むと拠点に戻る。",
+                    en: "Returns you to home when read.",
+                },
+                icon: "magic_star0",
+                price: 50,
+                max_stack: 3,
+            },
+            ConsumableType::Bomb => ConsumableProps {
+                name: Dict {
+                    ja: "爆弾",
+                    en: "Bomb",
+                },
+                description: Dict {
+                    ja: "投げるとしばらくして爆発し、周囲のものにダメージを与える。",
+                    en: "Explodes shortly after being thrown, damaging everything nearby.",
+                },
+                icon: "slime_seed",
+                price: 20,
+                max_stack: 10,
+            },
+        }
+    }
+}
+
+/// プレイヤーのインベントリにある消費アイテムを使います
+#[derive(Event, Clone, Copy, Debug)]
+pub struct UseConsumableEvent {
+    pub index: usize,
+}
+
+/// インベントリの先頭にある消費アイテムを素早く使うためのキー
+pub const QUICK_USE_KEY: KeyCode = KeyCode::KeyF;
+
+/// インベントリで最初に見つかった、使用できる消費アイテムの位置を返します
+/// 未清算の商品は使えないので飛ばします
+pub fn find_quick_use_item(actor: &Actor) -> Option<usize> {
+    actor.inventory.0.iter().position(|item| {
+        matches!(
+            item,
+            Some(item) if item.price == 0
+                && matches!(item.item_type, InventoryItemType::Consumable(..))
+        )
+    })
+}
+
+fn quick_use(
+    keys: Res<ButtonInput<KeyCode>>,
+    menu: Res<State<GameMenuState>>,
+    player_query: Query<&Actor, With<Player>>,
+    mut writer: EventWriter<UseConsumableEvent>,
+) {
+    if *menu.get() == GameMenuState::Closed && keys.just_pressed(QUICK_USE_KEY) {
+        if let Ok(actor) = player_query.get_single() {
+            if let Some(index) = find_quick_use_item(actor) {
+                writer.send(UseConsumableEvent { index });
+            }
+        }
+    }
+}
+
+fn use_consumable(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut reader: EventReader<UseConsumableEvent>,
+    mut player_query: Query<(Entity, &Player, &mut Actor, &mut Life, &Transform)>,
+    mut current: ResMut<CurrentLevel>,
+    mut se: EventWriter<SEEvent>,
+    mut overlay_writer: EventWriter<OverlayEvent>,
+) {
+    for event in reader.read() {
+        if let Ok((player_entity, player, mut actor, mut life, transform)) =
+            player_query.get_single_mut()
+        {
+            let position = transform.translation.truncate();
+            match actor.inventory.get(event.index) {
+                // 未清算の商品は使えません
+                Some(item) if 0 < item.price => {
+                    se.send(SEEvent::new(SE::NoDamage));
+                }
+                Some(mut item) => {
+                    let used = match item.item_type {
+                        InventoryItemType::Consumable(ConsumableType::HealingPotion) => {
+                            if life.life < life.max_life {
+                                life.life = (life.life + POTION_HEAL).min(life.max_life);
+                                se.send(SEEvent::pos(SE::Heal, position));
+                                true
+                            } else {
+                                false
+                            }
+                        }
+                        // 未清算の商品を持ったまま店から逃げ出すことはできません
+                        InventoryItemType::Consumable(ConsumableType::TeleportScroll) => {
+                            current.level != Some(GameLevel::Level(0)) && actor.dept() == 0
+                        }
+                        InventoryItemType::Consumable(ConsumableType::Bomb) => {
+                            let velocity = actor.pointer.normalize_or_zero() * THROW_SPEED;
+                            spawn_bomb(&mut commands, &assets, position, velocity, actor.uuid);
+                            se.send(
+                                SEEvent::pos(SE::Shuriken, position).with_source(actor.actor_group),
+                            );
+                            true
+                        }
+                        _ => false,
+                    };
+
+                    if !used {
+                        se.send(SEEvent::new(SE::NoDamage));
+                        continue;
+                    }
+
+                    item.count -= 1;
+                    actor
+                        .inventory
+                        .set(event.index, if 0 < item.count { Some(item) } else { None });
+
+                    // 巻物を使った後のインベントリを次のレベルへ引き継ぎます
+                    // 魔法陣でのワープと同じく、ワープが始まったらプレイヤーを消して操作や被弾を受け付けません
+                    if item.item_type
+                        == InventoryItemType::Consumable(ConsumableType::TeleportScroll)
+                    {
+                        current.next_level = GameLevel::Level(0);
+                        current.next_state = PlayerState::new(player, &actor, &life);
+                        commands.entity(player_entity).despawn_recursive();
+                        se.send(SEEvent::pos(SE::Warp, position));
+                        overlay_writer.send(OverlayEvent::Close(GameState::Warp));
+                        break;
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+pub struct ConsumablePlugin;
+
+impl Plugin for ConsumablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UseConsumableEvent>();
+        app.add_systems(
+            FixedUpdate,
+            use_consumable
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+        app.add_systems(Update, quick_use.run_if(in_state(GameState::InGame)));
+    }
+}
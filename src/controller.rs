@@ -1,3 +1,8 @@
+pub mod arena_match;
+pub mod connection;
 pub mod despawn_with_gold;
+pub mod network_simulator;
 pub mod player;
 pub mod remote;
+pub mod replication;
+pub mod snapshot;
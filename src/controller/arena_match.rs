@@ -0,0 +1,380 @@
+use crate::controller::network_simulator::OutgoingMessage;
+use crate::controller::player::Player;
+use crate::controller::remote::{send_remote_message, RemoteMessage, RemotePlayer};
+use crate::controller::replication::RemoteStates;
+use crate::entity::actor::Actor;
+use crate::entity::life::DamageEvent;
+use crate::language::Dict;
+use crate::level::{CurrentLevel, GameLevel};
+use crate::states::GameState;
+use bevy::{core::FrameCount, prelude::*, utils::HashMap};
+use bevy_rapier2d::plugin::PhysicsSet;
+use bevy_simple_websocket::{ReadyState, WebSocketState};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// デスマッチで勝利に必要なキル数
+pub const DEATHMATCH_KILLS: u32 = 10;
+
+/// 時間制ラウンドの制限時間(秒)
+const TIMED_ROUND_SECONDS: f32 = 180.0;
+
+/// 試合終了後、次のラウンドが始まるまでの時間(秒)
+const RESULT_SECONDS: f32 = 10.0;
+
+/// サバイバルでは、ラウンド開始からこの時間が経つまで勝敗を判定しません
+/// 倒れていた魔女が復活するのを待つためです
+const SURVIVAL_GRACE_SECONDS: f32 = 3.0;
+
+/// 試合の状態を送信する間隔(フレーム数)
+const MATCH_SEND_INTERVAL: u32 = 30;
+
+/// この時間だけ進行役から試合の状態が届かない場合は、自分が進行役を引き継ぎます
+const MATCH_LEADER_TIMEOUT: u32 = 120;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MatchMode {
+    /// 先に規定のキル数に達した魔女の勝ち
+    #[default]
+    Deathmatch,
+    /// 復活なしで、最後まで生き残った魔女の勝ち
+    LastWitchStanding,
+    /// 制限時間内に最も多くキルした魔女の勝ち
+    TimedRound,
+}
+
+impl MatchMode {
+    pub fn name(&self) -> Dict {
+        match self {
+            MatchMode::Deathmatch => Dict {
+                ja: "デスマッチ",
+                en: "Deathmatch",
+            },
+            MatchMode::LastWitchStanding => Dict {
+                ja: "サバイバル",
+                en: "Last Witch Standing",
+            },
+            MatchMode::TimedRound => Dict {
+                ja: "タイムアタック",
+                en: "Timed Round",
+            },
+        }
+    }
+
+    fn next(&self) -> MatchMode {
+        match self {
+            MatchMode::Deathmatch => MatchMode::LastWitchStanding,
+            MatchMode::LastWitchStanding => MatchMode::TimedRound,
+            MatchMode::TimedRound => MatchMode::Deathmatch,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MatchPhase {
+    /// 対戦相手を待っている状態
+    #[default]
+    Waiting,
+    Playing,
+    /// 試合が終了し、結果を表示している状態
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchScore {
+    pub kills: u32,
+    pub deaths: u32,
+    pub damage: u32,
+}
+
+/// アリーナの試合の状態です
+/// 試合の進行は、試合の状態を送信している魔女のうち最もUUIDの大きい魔女が担当します
+/// キル数などの成績は、Hit と Die の通知から各クライアントがそれぞれ集計します
+#[derive(Resource, Default)]
+pub struct ArenaMatch {
+    pub round: u32,
+    pub mode: MatchMode,
+    pub phase: MatchPhase,
+
+    /// 残り時間(秒)
+    /// Playing では制限時間、Finished では次のラウンドまでの時間です
+    pub remaining: f32,
+
+    /// ラウンドが始まってから経過した時間(秒)
+    elapsed: f32,
+
+    pub scores: HashMap<Uuid, MatchScore>,
+
+    /// 試合に参加している魔女の名前
+    pub names: HashMap<Uuid, String>,
+
+    /// 自分の魔女のUUID
+    /// 倒れて観戦している間も保持します
+    pub own: Option<Uuid>,
+
+    /// 進行役のUUIDと、最後に試合の状態を受信したフレーム
+    leader: Option<(Uuid, FrameCount)>,
+}
+
+impl ArenaMatch {
+    /// 現在のルールで倒れた魔女が復活できるかどうかを返します
+    /// サバイバルではラウンドの開始直後を除いて復活できません
+    pub fn can_respawn(&self) -> bool {
+        !(self.mode == MatchMode::LastWitchStanding
+            && self.phase == MatchPhase::Playing
+            && SURVIVAL_GRACE_SECONDS <= self.elapsed)
+    }
+
+    pub fn record_hit(&mut self, attacker: Uuid, victim: Uuid, damage: i32) {
+        if self.phase == MatchPhase::Playing && attacker != victim {
+            self.scores.entry(attacker).or_default().damage += damage.max(0) as u32;
+        }
+    }
+
+    pub fn record_death(&mut self, victim: Uuid, killer: Option<Uuid>) {
+        if self.phase == MatchPhase::Playing {
+            self.scores.entry(victim).or_default().deaths += 1;
+            if let Some(killer) = killer {
+                if killer != victim {
+                    self.scores.entry(killer).or_default().kills += 1;
+                }
+            }
+        }
+    }
+
+    /// 参加している魔女の成績を、キル数とダメージの多い順に返します
+    /// サバイバルでは倒れていない魔女が優先されます
+    pub fn ranking(&self) -> Vec<(Uuid, MatchScore)> {
+        let survival = self.mode == MatchMode::LastWitchStanding;
+        let mut ranking: Vec<(Uuid, MatchScore)> = self
+            .names
+            .keys()
+            .map(|uuid| (*uuid, self.scores.get(uuid).cloned().unwrap_or_default()))
+            .collect();
+        ranking.sort_by(|a, b| {
+            let deaths = if survival {
+                a.1.deaths.cmp(&b.1.deaths)
+            } else {
+                std::cmp::Ordering::Equal
+            };
+            deaths
+                .then(b.1.kills.cmp(&a.1.kills))
+                .then(b.1.damage.cmp(&a.1.damage))
+                .then(a.0.cmp(&b.0))
+        });
+        ranking
+    }
+
+    pub fn receive_state(
+        &mut self,
+        sender: Uuid,
+        round: u32,
+        mode: MatchMode,
+        phase: MatchPhase,
+        remaining: u32,
+        frame_count: &FrameCount,
+    ) {
+        // 自分より小さいUUIDの魔女からの通知は無視します
+        if self.own.map_or(false, |own| sender < own) {
+            return;
+        }
+        if let Some((leader, last)) = self.leader {
+            let stale = MATCH_LEADER_TIMEOUT < frame_count.0.wrapping_sub(last.0);
+            if sender < leader && !stale {
+                return;
+            }
+        }
+        self.leader = Some((sender, *frame_count));
+        if self.round != round {
+            self.scores.clear();
+            self.elapsed = 0.0;
+        }
+        self.round = round;
+        self.mode = mode;
+        self.phase = phase;
+        self.remaining = remaining as f32;
+    }
+
+    fn is_leader(&self, frame_count: &FrameCount) -> bool {
+        match (self.own, self.leader) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(own), Some((leader, last))) => {
+                leader <= own || MATCH_LEADER_TIMEOUT < frame_count.0.wrapping_sub(last.0)
+            }
+        }
+    }
+
+    fn start_round(&mut self) {
+        self.round += 1;
+        self.mode = if self.round == 1 {
+            MatchMode::default()
+        } else {
+            self.mode.next()
+        };
+        self.phase = MatchPhase::Playing;
+        self.elapsed = 0.0;
+        self.remaining = match self.mode {
+            MatchMode::TimedRound => TIMED_ROUND_SECONDS,
+            _ => 0.0,
+        };
+        self.scores.clear();
+        info!("Round {} started: {:?}", self.round, self.mode);
+    }
+
+    fn finish(&mut self) {
+        self.phase = MatchPhase::Finished;
+        self.remaining = RESULT_SECONDS;
+        info!("Round {} finished", self.round);
+    }
+}
+
+fn setup_match(mut arena: ResMut<ArenaMatch>) {
+    *arena = ArenaMatch::default();
+}
+
+/// 試合に参加している魔女の名前を記録します
+fn track_match_players(
+    mut arena: ResMut<ArenaMatch>,
+    player_query: Query<(&Player, &Actor)>,
+    remotes: Query<(&RemotePlayer, &Actor)>,
+    current: Res<CurrentLevel>,
+) {
+    if current.level != Some(GameLevel::MultiPlayArena) {
+        return;
+    }
+    if let Ok((player, actor)) = player_query.get_single() {
+        arena.own = Some(actor.uuid);
+        arena.names.insert(actor.uuid, player.name.clone());
+    }
+    for (remote, actor) in remotes.iter() {
+        arena.names.insert(actor.uuid, remote.name.clone());
+    }
+}
+
+/// 自分の魔女が他の魔女から受けたダメージを通知します
+fn report_damage(
+    mut reader: EventReader<DamageEvent>,
+    mut writer: EventWriter<OutgoingMessage>,
+    mut arena: ResMut<ArenaMatch>,
+    player_query: Query<&Actor, With<Player>>,
+    state: Res<WebSocketState>,
+    current: Res<CurrentLevel>,
+) {
+    for event in reader.read() {
+        if current.level != Some(GameLevel::MultiPlayArena) {
+            continue;
+        }
+        if let (Ok(actor), Some(attacker)) = (player_query.get(event.target), event.attacker) {
+            arena.record_hit(attacker, actor.uuid, event.damage);
+            send_remote_message(
+                &mut writer,
+                state.ready_state == ReadyState::OPEN,
+                &RemoteMessage::Hit {
+                    sender: actor.uuid,
+                    attacker,
+                    damage: event.damage,
+                },
+            );
+        }
+    }
+}
+
+/// 試合を進行します
+/// 進行役の場合は勝敗を判定し、試合の状態を他の魔女に送信します
+fn update_match(
+    mut arena: ResMut<ArenaMatch>,
+    mut writer: EventWriter<OutgoingMessage>,
+    player_query: Query<&Player>,
+    remotes: Query<&RemotePlayer>,
+    states: Res<RemoteStates>,
+    state: Res<WebSocketState>,
+    current: Res<CurrentLevel>,
+    frame_count: Res<FrameCount>,
+    time: Res<Time>,
+) {
+    if current.level != Some(GameLevel::MultiPlayArena) {
+        return;
+    }
+
+    arena.remaining = (arena.remaining - time.delta_secs()).max(0.0);
+    arena.elapsed += time.delta_secs();
+
+    if !arena.is_leader(&frame_count) {
+        return;
+    }
+
+    let participants = 1 + states
+        .peers_in(GameLevel::MultiPlayArena, &frame_count)
+        .len();
+    let alive = player_query.iter().count() + remotes.iter().count();
+    let phase = arena.phase;
+    let round = arena.round;
+
+    match arena.phase {
+        MatchPhase::Waiting => {
+            if 2 <= participants {
+                arena.start_round();
+            }
+        }
+        MatchPhase::Playing => {
+            let finished = match arena.mode {
+                MatchMode::Deathmatch => arena
+                    .scores
+                    .values()
+                    .any(|score| DEATHMATCH_KILLS <= score.kills),
+                MatchMode::LastWitchStanding => {
+                    SURVIVAL_GRACE_SECONDS <= arena.elapsed && alive <= 1
+                }
+                MatchMode::TimedRound => arena.remaining <= 0.0,
+            };
+            if finished {
+                arena.finish();
+            } else if participants < 2 {
+                arena.phase = MatchPhase::Waiting;
+            }
+        }
+        MatchPhase::Finished => {
+            if arena.remaining <= 0.0 {
+                if 2 <= participants {
+                    arena.start_round();
+                } else {
+                    arena.phase = MatchPhase::Waiting;
+                }
+            }
+        }
+    }
+
+    let changed = phase != arena.phase || round != arena.round;
+    if changed || frame_count.0 % MATCH_SEND_INTERVAL == 0 {
+        if let Some(own) = arena.own {
+            send_remote_message(
+                &mut writer,
+                state.ready_state == ReadyState::OPEN,
+                &RemoteMessage::Match {
+                    sender: own,
+                    round: arena.round,
+                    mode: arena.mode,
+                    phase: arena.phase,
+                    remaining: arena.remaining.ceil() as u32,
+                },
+            );
+        }
+    }
+}
+
+pub struct ArenaMatchPlugin;
+
+impl Plugin for ArenaMatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ArenaMatch>();
+        app.add_systems(OnEnter(GameState::InGame), setup_match);
+        app.add_systems(
+            FixedUpdate,
+            (track_match_players, report_damage, update_match)
+                .chain()
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::config::GameConfig;
+use crate::constant::WEBSOCKET_URL;
+use crate::controller::player::Player;
+use crate::controller::remote::{is_online_level, PositionSync};
+use crate::entity::actor::Actor;
+use crate::entity::life::Life;
+use crate::hud::overlay::OverlayEvent;
+use crate::level::{CurrentLevel, GameLevel};
+use crate::player_state::PlayerState;
+use crate::states::GameState;
+use bevy::{core::FrameCount, prelude::*};
+use bevy_rapier2d::plugin::PhysicsSet;
+use bevy_simple_websocket::{ClientMessage, ReadyState, WebSocketState};
+
+/// 接続を試みてから、失敗とみなすまでのフレーム数
+const CONNECT_TIMEOUT: u32 = 300;
+
+/// 再接続までの待ち時間の初期値(フレーム数)
+/// 失敗するごとに2倍になります
+const RECONNECT_BASE_DELAY: u32 = 60;
+
+/// 再接続までの待ち時間の上限(フレーム数)
+const RECONNECT_MAX_DELAY: u32 = 960;
+
+/// この回数だけ連続して接続に失敗すると、再接続を諦めます
+/// アリーナにいる場合は拠点に戻ります
+pub const MAX_CONNECT_ATTEMPTS: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ConnectionStatus {
+    /// 接続していないか、接続する必要がない状態
+    #[default]
+    Offline,
+    Connecting,
+    Open,
+    /// 切断されたため、再接続を待っている状態
+    Reconnecting,
+    /// 再接続を諦めた状態
+    Failed,
+}
+
+/// WebSocket の接続状態です
+/// bevy_simple_websocket の ReadyState は OPEN 以外の状態を区別しにくいため、
+/// 接続の試行と失敗はこちらで管理します
+#[derive(Resource, Default)]
+pub struct Connection {
+    pub status: ConnectionStatus,
+
+    /// 連続して失敗した回数
+    pub attempts: u32,
+
+    /// Connecting ではタイムアウトするフレーム、Reconnecting では次に接続を試みるフレーム
+    deadline: u32,
+
+    /// 接続できずに拠点に戻ったフレーム
+    /// 拠点で説明を表示するために使います
+    pub fallback_frame: Option<FrameCount>,
+}
+
+impl Connection {
+    pub fn begin(&mut self, frame_count: &FrameCount) {
+        self.status = ConnectionStatus::Connecting;
+        self.attempts = 0;
+        self.deadline = frame_count.0 + CONNECT_TIMEOUT;
+        self.fallback_frame = None;
+    }
+
+    fn schedule_reconnect(&mut self, frame_count: &FrameCount) {
+        let delay = RECONNECT_BASE_DELAY
+            .saturating_mul(1 << self.attempts.saturating_sub(1).min(16))
+            .min(RECONNECT_MAX_DELAY);
+        self.status = ConnectionStatus::Reconnecting;
+        self.deadline = frame_count.0 + delay;
+    }
+}
+
+fn watch_connection(
+    mut commands: Commands,
+    mut writer: EventWriter<ClientMessage>,
+    mut overlay_writer: EventWriter<OverlayEvent>,
+    player_query: Query<(Entity, &Player, &Actor, &Life)>,
+    state: Res<WebSocketState>,
+    frame_count: Res<FrameCount>,
+    mut connection: ResMut<Connection>,
+    mut current: ResMut<CurrentLevel>,
+    mut sync: ResMut<PositionSync>,
+    config: Res<GameConfig>,
+) {
+    if !is_online_level(current.level, &config) {
+        return;
+    }
+
+    let open = state.ready_state == ReadyState::OPEN;
+
+    match connection.status {
+        ConnectionStatus::Offline | ConnectionStatus::Failed => {}
+        ConnectionStatus::Connecting => {
+            if open {
+                info!("Connected to {}", WEBSOCKET_URL);
+                connection.status = ConnectionStatus::Open;
+                connection.attempts = 0;
+
+                // 再接続した場合も含めて、キーフレームと名前を送り直します
+                *sync = PositionSync::default();
+            } else if connection.deadline <= frame_count.0 {
+                connection.attempts += 1;
+                warn!(
+                    "Failed to connect to {} ({}/{})",
+                    WEBSOCKET_URL, connection.attempts, MAX_CONNECT_ATTEMPTS
+                );
+                if MAX_CONNECT_ATTEMPTS <= connection.attempts {
+                    connection.status = ConnectionStatus::Failed;
+                    writer.send(ClientMessage::Close);
+
+                    // アリーナでは拠点に戻ります
+                    // 協力プレイではそのままオフラインで冒険を続けます
+                    if current.level != Some(GameLevel::MultiPlayArena) {
+                        return;
+                    }
+                    connection.fallback_frame = Some(*frame_count);
+                    if let Ok((entity, player, actor, life)) = player_query.get_single() {
+                        commands.entity(entity).despawn_recursive();
+                        current.next_level = GameLevel::Level(0);
+                        current.next_state = PlayerState::new(player, actor, life);
+                        overlay_writer.send(OverlayEvent::Close(GameState::Warp));
+                    }
+                } else {
+                    connection.schedule_reconnect(&frame_count);
+                }
+            }
+        }
+        ConnectionStatus::Open => {
+            if !open {
+                warn!("Disconnected from {}", WEBSOCKET_URL);
+                connection.attempts = 1;
+                connection.schedule_reconnect(&frame_count);
+            }
+        }
+        ConnectionStatus::Reconnecting => {
+            if connection.deadline <= frame_count.0 {
+                info!("Reconnecting to {}", WEBSOCKET_URL);
+                writer.send(ClientMessage::Close);
+                writer.send(ClientMessage::Open(WEBSOCKET_URL.to_string()));
+                connection.status = ConnectionStatus::Connecting;
+                connection.deadline = frame_count.0 + CONNECT_TIMEOUT;
+            }
+        }
+    }
+}
+
+pub struct ConnectionPlugin;
+
+impl Plugin for ConnectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Connection>();
+        app.add_systems(
+            FixedUpdate,
+            watch_connection
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
@@ -1,5 +1,8 @@
+use crate::controller::replication::RemoteControlled;
 use crate::entity::gold::spawn_gold;
 use crate::entity::life::Life;
+use crate::level::CurrentLevel;
+use crate::loot::{level_depth, spawn_loot, LootSource};
 use crate::se::{SEEvent, SE};
 use crate::{asset::GameAssets, set::GameSet, states::GameState};
 use bevy::prelude::*;
@@ -17,9 +20,11 @@ pub struct DespawnWithGold {
 fn dead_enemy(
     mut commands: Commands,
     assets: Res<GameAssets>,
-    mut query: Query<(Entity, &DespawnWithGold, &Life, &Transform)>,
+    current: Res<CurrentLevel>,
+    mut query: Query<(Entity, &DespawnWithGold, &Life, &Transform), Without<RemoteControlled>>,
     mut writer: EventWriter<SEEvent>,
 ) {
+    let depth = current.level.map(level_depth).unwrap_or(0);
     for (entity, enemy, enemy_life, transform) in query.iter_mut() {
         if enemy_life.life <= 0 {
             commands.entity(entity).despawn_recursive();
@@ -33,6 +38,14 @@ fn dead_enemy(
                     transform.translation.y,
                 );
             }
+
+            spawn_loot(
+                &mut commands,
+                &assets,
+                LootSource::Enemy,
+                depth,
+                transform.translation.truncate(),
+            );
         }
     }
 }
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use bevy_simple_websocket::{ClientMessage, ServerMessage};
+use rand::random;
+
+/// ゲームから送信するバイナリメッセージです
+/// WebSocket へは NetworkSimulatorPlugin を経由して送信されます
+#[derive(Event, Clone, Debug)]
+pub struct OutgoingMessage(pub Vec<u8>);
+
+/// WebSocket から受信したバイナリメッセージです
+/// NetworkSimulatorPlugin を経由して届きます
+#[derive(Event, Clone, Debug)]
+pub struct IncomingMessage(pub Vec<u8>);
+
+/// 手元でマルチプレイを試験するために再現する通信状態です
+/// 送信と受信のそれぞれに独立して適用されます
+/// すべてゼロのときはメッセージをそのまま通過させます
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkConditions {
+    /// 片道の遅延(ミリ秒)
+    pub latency_ms: u32,
+
+    /// 遅延に加えるゆらぎの最大値(ミリ秒)
+    pub jitter_ms: u32,
+
+    /// メッセージが失われる確率
+    pub loss: f32,
+
+    /// メッセージが重複して届く確率
+    pub duplication: f32,
+
+    /// メッセージが後続のメッセージより遅れて届く確率
+    pub reorder: f32,
+}
+
+impl NetworkConditions {
+    pub fn is_active(&self) -> bool {
+        *self != NetworkConditions::default()
+    }
+
+    fn delay(&self) -> f32 {
+        (self.latency_ms as f32 + self.jitter_ms as f32 * random::<f32>()) / 1000.0
+    }
+}
+
+/// 入れ替わりが起きたメッセージに追加される遅延(秒)
+const REORDER_DELAY: f32 = 0.1;
+
+/// 配送を待っているメッセージです
+#[derive(Default)]
+struct DelayQueue {
+    messages: Vec<(f32, Vec<u8>)>,
+
+    /// 最後に順番どおり配送されるメッセージの配送時刻
+    /// ゆらぎがあっても、入れ替わりが指定されない限りは順序を保ちます
+    last_deliver_at: f32,
+}
+
+impl DelayQueue {
+    fn push(&mut self, conditions: &NetworkConditions, now: f32, bin: Vec<u8>) {
+        if random::<f32>() < conditions.loss {
+            return;
+        }
+
+        let copies = if random::<f32>() < conditions.duplication {
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..copies {
+            let deliver_at = now + conditions.delay();
+            if random::<f32>() < conditions.reorder {
+                self.messages.push((
+                    deliver_at.max(self.last_deliver_at) + REORDER_DELAY,
+                    bin.clone(),
+                ));
+            } else {
+                self.last_deliver_at = deliver_at.max(self.last_deliver_at);
+                self.messages.push((self.last_deliver_at, bin.clone()));
+            }
+        }
+    }
+
+    /// 配送時刻に達したメッセージを配送時刻順に取り出します
+    fn pop_ready(&mut self, now: f32) -> Vec<Vec<u8>> {
+        let (mut ready, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.messages)
+            .into_iter()
+            .partition(|(deliver_at, _)| *deliver_at <= now);
+        self.messages = pending;
+        ready.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ready.into_iter().map(|(_, bin)| bin).collect()
+    }
+}
+
+fn simulate_outgoing(
+    mut reader: EventReader<OutgoingMessage>,
+    mut writer: EventWriter<ClientMessage>,
+    mut queue: Local<DelayQueue>,
+    conditions: Res<NetworkConditions>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs();
+    for message in reader.read() {
+        if conditions.is_active() {
+            queue.push(&conditions, now, message.0.clone());
+        } else {
+            writer.send(ClientMessage::Binary(message.0.clone()));
+        }
+    }
+    for bin in queue.pop_ready(now) {
+        writer.send(ClientMessage::Binary(bin));
+    }
+}
+
+fn simulate_incoming(
+    mut reader: EventReader<ServerMessage>,
+    mut writer: EventWriter<IncomingMessage>,
+    mut queue: Local<DelayQueue>,
+    conditions: Res<NetworkConditions>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs();
+    for message in reader.read() {
+        match message {
+            ServerMessage::String(text) => {
+                info!("Received text message: {}", text);
+            }
+            ServerMessage::Binary(bin) => {
+                if conditions.is_active() {
+                    queue.push(&conditions, now, bin.clone());
+                } else {
+                    writer.send(IncomingMessage(bin.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+    for bin in queue.pop_ready(now) {
+        writer.send(IncomingMessage(bin));
+    }
+}
+
+pub struct NetworkSimulatorPlugin;
+
+impl Plugin for NetworkSimulatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OutgoingMessage>();
+        app.add_event::<IncomingMessage>();
+        app.init_resource::<NetworkConditions>();
+        app.add_systems(PreUpdate, simulate_incoming);
+        app.add_systems(PostUpdate, simulate_outgoing);
+    }
+}
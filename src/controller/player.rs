@@ -1,25 +1,30 @@
 use crate::asset::GameAssets;
 use crate::constant::{ENTITY_LAYER_Z, MAX_WANDS};
+use crate::controller::arena_match::ArenaMatch;
+use crate::controller::network_simulator::OutgoingMessage;
 use crate::controller::remote::send_remote_message;
 use crate::controller::remote::RemoteMessage;
 use crate::entity::actor::{Actor, ActorFireState};
 use crate::entity::gold::Gold;
-use crate::entity::life::Life;
-use crate::equipment::EquipmentType;
+use crate::entity::life::{DamageEvent, Life};
+use crate::equipment::EquipmentItem;
+use crate::hud::spectator::Spectator;
 use crate::input::{get_direction, get_fire_trigger};
+use crate::level::{CurrentLevel, GameLevel};
+use crate::player_state::PlayerState;
 use crate::se::{SEEvent, SE};
 use crate::states::{GameMenuState, GameState};
-use bevy::core::FrameCount;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::AseSpriteAnimation;
 use bevy_light_2d::light::PointLight2d;
 use bevy_rapier2d::prelude::*;
-use bevy_simple_websocket::{ClientMessage, ReadyState, WebSocketState};
+use bevy_simple_websocket::{ReadyState, WebSocketState};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, Reflect)]
 pub struct Equipment {
-    pub equipment_type: EquipmentType,
+    pub item: EquipmentItem,
     pub price: u32,
 }
 
@@ -27,13 +32,10 @@ pub struct Equipment {
 #[derive(Component, Debug, Clone)]
 pub struct Player {
     pub name: String,
-    pub last_idle_frame_count: FrameCount,
-    pub last_ilde_x: f32,
-    pub last_ilde_y: f32,
-    pub last_idle_vx: f32,
-    pub last_idle_vy: f32,
-    pub last_idle_life: i32,
-    pub last_idle_max_life: i32,
+
+    /// 店から代金を払わずに持ち出した商品の合計金額
+    /// 店主に払うまで次のレベルにも引き継がれます
+    pub debt: u32,
 }
 
 /// プレイヤーの移動
@@ -56,19 +58,6 @@ fn move_player(
     }
 }
 
-fn apply_intensity_by_lantern(mut player_query: Query<&mut Actor, With<Player>>) {
-    if let Ok(mut actor) = player_query.get_single_mut() {
-        let equiped_lantern = actor.equipments.iter().any(|e| match e {
-            Some(Equipment {
-                equipment_type: EquipmentType::Lantern,
-                ..
-            }) => true,
-            _ => false,
-        });
-        actor.intensity = if equiped_lantern { 3.0 } else { 0.0 };
-    }
-}
-
 /// 魔法の発射
 fn trigger_bullet(
     mut player_query: Query<&mut Actor, (With<Player>, Without<Camera2d>)>,
@@ -150,15 +139,40 @@ fn pick_gold(
 fn die_player(
     mut commands: Commands,
     assets: Res<GameAssets>,
-    player_query: Query<(Entity, &Actor, &Life, &Transform), With<Player>>,
-    mut writer: EventWriter<ClientMessage>,
+    player_query: Query<(Entity, &Player, &Actor, &Life, &Transform)>,
+    mut writer: EventWriter<OutgoingMessage>,
     mut game: EventWriter<SEEvent>,
     websocket: Res<WebSocketState>,
+    current: Res<CurrentLevel>,
+    mut spectator: ResMut<Spectator>,
+    mut arena: ResMut<ArenaMatch>,
+    mut damage_reader: EventReader<DamageEvent>,
+    mut last_attacker: Local<Option<Uuid>>,
 ) {
-    if let Ok((entity, actor, player_life, transform)) = player_query.get_single() {
+    if let Ok((entity, player, actor, player_life, transform)) = player_query.get_single() {
+        // 倒された相手を通知するため、最後にダメージを与えたアクターを覚えておきます
+        for event in damage_reader.read() {
+            if event.target == entity && event.attacker != Some(actor.uuid) {
+                *last_attacker = event.attacker;
+            }
+        }
+
         if player_life.life <= 0 {
             commands.entity(entity).despawn_recursive();
 
+            let killer = last_attacker.take();
+
+            // アリーナではゲームオーバーにならず、他のプレイヤーを観戦します
+            if current.level == Some(GameLevel::MultiPlayArena) {
+                arena.record_death(actor.uuid, killer);
+                spectator.begin(
+                    actor.uuid,
+                    actor.golds,
+                    transform.translation.truncate(),
+                    PlayerState::new(player, actor, player_life),
+                );
+            }
+
             game.send(SEEvent::pos(SE::Cry, transform.translation.truncate()));
 
             // ダウンのアニメーションを残す
@@ -186,6 +200,7 @@ fn die_player(
                 &RemoteMessage::Die {
                     sender: actor.uuid,
                     uuid: actor.uuid,
+                    killer,
                 },
             );
         }
@@ -206,7 +221,6 @@ impl Plugin for PlayerPlugin {
                 trigger_bullet,
                 pick_gold,
                 die_player,
-                apply_intensity_by_lantern,
                 switch_wand,
             )
                 .run_if(in_state(GameState::InGame))
@@ -1,23 +1,35 @@
+use crate::config::GameConfig;
 use crate::constant::*;
+use crate::controller::arena_match::{ArenaMatch, MatchMode, MatchPhase};
+use crate::controller::connection::{Connection, ConnectionStatus};
+use crate::controller::network_simulator::{IncomingMessage, OutgoingMessage};
 use crate::controller::player::Player;
+use crate::controller::replication::RemoteStates;
+use crate::controller::snapshot::{EntitySnapshot, PlayerDelta, PlayerSnapshot};
 use crate::entity::actor::ActorGroup;
 use crate::entity::bullet::SpawnBullet;
 use crate::entity::life::Life;
+use crate::hud::chat::{ChatEvent, ChatMessage};
 use crate::inventory::Inventory;
 use crate::level::{setup_level, CurrentLevel, GameLevel};
 use crate::se::SE;
 use crate::{
     asset::GameAssets,
-    entity::{actor::Actor, bullet::spawn_bullet, gold::spawn_gold, witch::spawn_witch},
+    entity::{
+        actor::Actor,
+        bullet::spawn_bullet,
+        gold::spawn_gold,
+        witch::{spawn_witch, WitchNamePlate},
+    },
     hud::life_bar::LifeBarResource,
     se::SEEvent,
     states::GameState,
 };
 use bevy::{core::FrameCount, prelude::*, utils::HashMap};
 use bevy_rapier2d::{plugin::PhysicsSet, prelude::Velocity};
-use bevy_simple_websocket::{ClientMessage, ReadyState, ServerMessage, WebSocketState};
+use bevy_simple_websocket::{ClientMessage, ReadyState, WebSocketState};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use uuid::Uuid;
 
 /// ネットワークに接続したクライアントは、常に互いの位置を送信しあっているため、
@@ -44,130 +56,385 @@ pub struct RemotePlayer {
     pub name: String,
     pub golds: i32,
     pub last_update: FrameCount,
+
+    /// 受信して復元したスナップショットの履歴です
+    /// 送信側はここに含まれる任意のシーケンス番号を基準に差分を送ってくることがあります
+    pub snapshots: VecDeque<(u32, PlayerSnapshot)>,
+
+    /// 受信した最新のシーケンス番号
+    pub last_seq: u32,
+
+    /// 最後に Ack を送ったシーケンス番号
+    pub acked_seq: Option<u32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum RemoteMessage {
-    // エンティティの現在位置を通知します
-    // 前回の通知と比較して、位置が変更されたか60フレーム以上経過した場合、
-    // 他のプレイヤーから Join が送られたときは再通知します
-    Position {
+    // プレイヤーキャラクターの状態を通知します
+    // base が None の場合はキーフレームで、すべてのフィールドを含みます
+    // base が Some の場合は、受信者が Ack したシーケンス番号の状態からの差分です
+    Snapshot {
+        sender: Uuid,
+        seq: u32,
+        base: Option<u32>,
+        delta: PlayerDelta,
+    },
+    // target から受信したスナップショットのうち、復元できた最新のシーケンス番号を通知します
+    Ack {
+        sender: Uuid,
+        target: Uuid,
+        seq: u32,
+    },
+    // 名前や所持金、現在のレベルなど、頻繁には変化しない情報を通知します
+    Metadata {
         sender: Uuid,
-        uuid: Uuid,
         name: String,
         golds: i32,
-        x: f32,
-        y: f32,
-        vx: f32,
-        vy: f32,
-        life: i32,
-        max_life: i32,
-        angle: f32,
-        intensity: f32,
+        level: Option<GameLevel>,
+        room: String,
+    },
+    // レベルのホストが、そのレベルのモンスターなどの状態を通知します
+    Entities {
+        sender: Uuid,
+        level: GameLevel,
+        entities: Vec<EntitySnapshot>,
     },
     // 弾を発射したことを通知します
     Fire(SpawnBullet),
-    // ダメージを受けたことを通知します
+    // sender の魔女が attacker の弾丸でダメージを受けたことを通知します
+    // 体力はスナップショットで同期されるため、これは成績の集計にだけ使います
     Hit {
         sender: Uuid,
-        uuid: Uuid,
+        attacker: Uuid,
         damage: i32,
     },
+    // uuid のキャラクターが倒れたことを通知します
+    // killer は最後にダメージを与えたキャラクターです
     Die {
         sender: Uuid,
         uuid: Uuid,
+        killer: Option<Uuid>,
     },
+    // アリーナの試合の進行役が、試合の状態を通知します
+    Match {
+        sender: Uuid,
+        round: u32,
+        mode: MatchMode,
+        phase: MatchPhase,
+        remaining: u32,
+    },
+    // チャットやエモートを送信します
+    Chat {
+        sender: Uuid,
+        message: ChatMessage,
+    },
+}
+
+/// そのレベルでサーバーに接続するかどうかを返します
+/// マルチプレイアリーナでは常に接続し、通常のレベルでは協力プレイがオンで合言葉が入力されている場合だけ接続します
+pub fn is_online_level(level: Option<GameLevel>, config: &GameConfig) -> bool {
+    match level {
+        Some(GameLevel::MultiPlayArena) => true,
+        // 拠点はひとりで準備をする場所なので、協力プレイでも接続しません
+        Some(GameLevel::Level(0)) => false,
+        Some(GameLevel::Level(_)) => config.coop && !config.room.is_empty(),
+        None => false,
+    }
+}
+
+/// そのレベルで参加する部屋を返します
+/// 同じ部屋にいるプレイヤーのメッセージだけを受け取ります
+/// アリーナは全員が同じ部屋で、協力プレイでは合言葉が部屋になります
+pub fn current_room(level: Option<GameLevel>, config: &GameConfig) -> String {
+    match level {
+        Some(GameLevel::MultiPlayArena) => String::new(),
+        _ => config.room.clone(),
+    }
+}
+
+/// 位置の送信間隔の設定です
+#[derive(Resource, Debug, Clone)]
+pub struct SyncConfig {
+    /// スナップショットを送信する間隔(フレーム数)
+    pub send_interval: u32,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            send_interval: DEFAULT_SYNC_SEND_INTERVAL,
+        }
+    }
+}
+
+/// 変化がなくても状態を再送する間隔(フレーム数)
+/// despawn_no_contact_remotes のタイムアウトより短くする必要があります
+const HEARTBEAT_INTERVAL: u32 = 60;
+
+/// Ack の状態に関わらずキーフレームを送る間隔(フレーム数)
+const KEYFRAME_INTERVAL: u32 = 300;
+
+/// 名前や所持金が変化したときに再送するまでの最短の間隔(フレーム数)
+const METADATA_MIN_INTERVAL: u32 = 30;
+
+/// 変化がなくても名前や所持金を再送する間隔(フレーム数)
+const METADATA_INTERVAL: u32 = 600;
+
+/// 保持するスナップショットの履歴の数
+const SNAPSHOT_HISTORY: usize = 64;
+
+/// 自分のプレイヤーキャラクターの送信状態です
+#[derive(Resource, Default)]
+pub struct PositionSync {
+    seq: u32,
+
+    /// 送信したスナップショットの履歴
+    history: VecDeque<(u32, PlayerSnapshot)>,
+
+    /// 他のプレイヤーごとに、Ack された最新のシーケンス番号
+    /// まだ Ack を受け取っていないプレイヤーは None です
+    acks: HashMap<Uuid, Option<u32>>,
+
+    last_sent: Option<(FrameCount, PlayerSnapshot)>,
+
+    last_keyframe: FrameCount,
+
+    last_metadata: Option<(FrameCount, String, i32, Option<GameLevel>)>,
+
+    /// 新しいプレイヤーを見つけたときは名前と所持金を再送します
+    metadata_requested: bool,
+}
+
+impl PositionSync {
+    /// すべてのプレイヤーが Ack している状態のうち、もっとも古いものを差分の基準にします
+    /// Ack していないプレイヤーがいる場合はキーフレームを送る必要があります
+    fn baseline(&self) -> Option<(u32, PlayerSnapshot)> {
+        let mut min: Option<u32> = None;
+        for ack in self.acks.values() {
+            let seq = (*ack)?;
+            min = Some(min.map_or(seq, |m| m.min(seq)));
+        }
+        let seq = min?;
+        self.history.iter().find(|(s, _)| *s == seq).cloned()
+    }
+
+    fn register_peer(&mut self, uuid: Uuid) {
+        if !self.acks.contains_key(&uuid) {
+            self.acks.insert(uuid, None);
+            self.metadata_requested = true;
+        }
+    }
+}
+
+/// 帯域幅の計測値です
+#[derive(Resource, Default)]
+pub struct NetworkStats {
+    window_start: f32,
+    sent: usize,
+    received: HashMap<Uuid, usize>,
+
+    /// 直近1秒間に送信したバイト数
+    pub sent_per_second: usize,
+
+    /// 直近1秒間に各クライアントから受信したバイト数
+    pub received_per_second: HashMap<Uuid, usize>,
 }
 
 fn send_player_states(
-    mut writer: EventWriter<ClientMessage>,
-    mut query: Query<(&mut Player, &Actor, &Life, &GlobalTransform, &Velocity)>,
+    mut writer: EventWriter<OutgoingMessage>,
+    query: Query<(&Actor, &Life, &GlobalTransform, &Velocity), With<Player>>,
     state: Res<WebSocketState>,
     frame_count: Res<FrameCount>,
     current: Res<CurrentLevel>,
+    config: Res<SyncConfig>,
+    game_config: Res<GameConfig>,
+    mut sync: ResMut<PositionSync>,
 ) {
-    if current.level == Some(GameLevel::MultiPlayArena) && state.ready_state == ReadyState::OPEN {
-        if let Ok((mut player, actor, actor_life, transform, velocity)) = query.get_single_mut() {
+    if is_online_level(current.level, &game_config) && state.ready_state == ReadyState::OPEN {
+        if let Ok((actor, actor_life, transform, velocity)) = query.get_single() {
             if actor_life.life <= 0 {
                 return;
             }
 
-            let translate = transform.translation();
+            let snapshot = PlayerSnapshot::quantize(
+                transform.translation().truncate(),
+                velocity.linvel,
+                actor.pointer.to_angle(),
+                actor.intensity,
+                actor_life.life,
+                actor_life.max_life,
+            );
+
+            if let Some((last_frame, last_snapshot)) = sync.last_sent {
+                let elapsed = frame_count.0.wrapping_sub(last_frame.0);
+                if elapsed < config.send_interval {
+                    return;
+                }
+                if last_snapshot == snapshot && elapsed < HEARTBEAT_INTERVAL {
+                    return;
+                }
+            }
+
+            let keyframe_elapsed = frame_count.0.wrapping_sub(sync.last_keyframe.0);
+            let baseline = if KEYFRAME_INTERVAL <= keyframe_elapsed {
+                None
+            } else {
+                sync.baseline()
+            };
+
+            sync.seq += 1;
+            let seq = sync.seq;
+            let (base, delta) = match baseline {
+                Some((base_seq, base_snapshot)) => (Some(base_seq), snapshot.diff(&base_snapshot)),
+                None => {
+                    sync.last_keyframe = *frame_count;
+                    (None, PlayerDelta::full(&snapshot))
+                }
+            };
+
+            sync.history.push_back((seq, snapshot));
+            while SNAPSHOT_HISTORY < sync.history.len() {
+                sync.history.pop_front();
+            }
+            sync.last_sent = Some((*frame_count, snapshot));
 
-            if 60 < (frame_count.0 as i32 - player.last_idle_frame_count.0 as i32)
-                || translate.x != player.last_ilde_x
-                || translate.y != player.last_ilde_y
-                || actor_life.life != player.last_idle_life
-                || actor_life.max_life != player.last_idle_max_life
-            {
-                let command = RemoteMessage::Position {
+            send_remote_message(
+                &mut writer,
+                true,
+                &RemoteMessage::Snapshot {
                     sender: actor.uuid,
-                    uuid: actor.uuid,
-                    name: player.name.clone(),
-                    golds: actor.golds,
-                    x: translate.x,
-                    y: translate.y,
-                    vx: velocity.linvel.x,
-                    vy: velocity.linvel.y,
-                    life: actor_life.life,
-                    max_life: actor_life.max_life,
-                    angle: actor.pointer.to_angle(),
-                    intensity: actor.intensity,
-                };
-                let serialized = bincode::serialize(&command).unwrap();
-                writer.send(ClientMessage::Binary(serialized));
-                player.last_idle_frame_count = frame_count.clone();
-                player.last_ilde_x = translate.x;
-                player.last_ilde_y = translate.y;
-                player.last_idle_vx = velocity.linvel.x;
-                player.last_idle_vy = velocity.linvel.y;
+                    seq,
+                    base,
+                    delta,
+                },
+            );
+        }
+    }
+}
+
+/// 名前や所持金が変化したとき、または新しいプレイヤーを見つけたときに通知します
+fn send_player_metadata(
+    mut writer: EventWriter<OutgoingMessage>,
+    query: Query<(&Player, &Actor)>,
+    state: Res<WebSocketState>,
+    frame_count: Res<FrameCount>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    mut sync: ResMut<PositionSync>,
+) {
+    if is_online_level(current.level, &config) && state.ready_state == ReadyState::OPEN {
+        if let Ok((player, actor)) = query.get_single() {
+            let should_send = match &sync.last_metadata {
+                None => true,
+                Some((last_frame, name, golds, level)) => {
+                    let elapsed = frame_count.0.wrapping_sub(last_frame.0);
+                    METADATA_INTERVAL <= elapsed
+                        || (METADATA_MIN_INTERVAL <= elapsed
+                            && (sync.metadata_requested
+                                || *name != player.name
+                                || *golds != actor.golds
+                                || *level != current.level))
+                }
+            };
+            if should_send {
+                send_remote_message(
+                    &mut writer,
+                    true,
+                    &RemoteMessage::Metadata {
+                        sender: actor.uuid,
+                        name: player.name.clone(),
+                        golds: actor.golds,
+                        level: current.level,
+                        room: current_room(current.level, &config),
+                    },
+                );
+                sync.last_metadata = Some((
+                    *frame_count,
+                    player.name.clone(),
+                    actor.golds,
+                    current.level,
+                ));
+                sync.metadata_requested = false;
             }
         }
     }
 }
 
-fn on_enter(mut writer: EventWriter<ClientMessage>, current: Res<CurrentLevel>) {
-    if current.level != Some(GameLevel::MultiPlayArena)
-        && current.next_level == GameLevel::MultiPlayArena
+/// 受信したスナップショットのシーケンス番号を送信者に通知します
+/// 送信者はすべての受信者が Ack した状態を基準に差分を送ります
+fn send_acks(
+    mut writer: EventWriter<OutgoingMessage>,
+    player_query: Query<&Actor, (With<Player>, Without<RemotePlayer>)>,
+    mut remotes: Query<(&Actor, &mut RemotePlayer)>,
+    state: Res<WebSocketState>,
+    frame_count: Res<FrameCount>,
+    config: Res<SyncConfig>,
+) {
+    if state.ready_state != ReadyState::OPEN || frame_count.0 % config.send_interval.max(1) != 0 {
+        return;
+    }
+    if let Ok(player_actor) = player_query.get_single() {
+        for (actor, mut remote) in remotes.iter_mut() {
+            if remote.acked_seq != Some(remote.last_seq) {
+                send_remote_message(
+                    &mut writer,
+                    true,
+                    &RemoteMessage::Ack {
+                        sender: player_actor.uuid,
+                        target: actor.uuid,
+                        seq: remote.last_seq,
+                    },
+                );
+                remote.acked_seq = Some(remote.last_seq);
+            }
+        }
+    }
+}
+
+fn on_enter(
+    mut writer: EventWriter<ClientMessage>,
+    current: Res<CurrentLevel>,
+    mut sync: ResMut<PositionSync>,
+    mut connection: ResMut<Connection>,
+    frame_count: Res<FrameCount>,
+    config: Res<GameConfig>,
+) {
+    // レベルが変わると他のプレイヤーの状態を復元できなくなるので、
+    // 互いにキーフレームを送り直します
+    for ack in sync.acks.values_mut() {
+        *ack = None;
+    }
+    sync.metadata_requested = true;
+
+    if !is_online_level(current.level, &config)
+        && is_online_level(Some(current.next_level), &config)
     {
         info!("Connecting to {}", WEBSOCKET_URL);
+        *sync = PositionSync::default();
+        connection.begin(&frame_count);
         writer.send(ClientMessage::Open(WEBSOCKET_URL.to_string()));
     }
 }
 
-fn on_exit(mut writer: EventWriter<ClientMessage>, current: Res<CurrentLevel>) {
-    if current.level == Some(GameLevel::MultiPlayArena)
-        && current.next_level != GameLevel::MultiPlayArena
+fn on_exit(
+    mut writer: EventWriter<ClientMessage>,
+    current: Res<CurrentLevel>,
+    mut connection: ResMut<Connection>,
+    config: Res<GameConfig>,
+) {
+    if is_online_level(current.level, &config)
+        && !is_online_level(Some(current.next_level), &config)
     {
         info!("Closing {}", WEBSOCKET_URL);
         writer.send(ClientMessage::Close);
+        connection.status = ConnectionStatus::Offline;
     }
 }
 
-#[allow(dead_code)]
-enum RemoteEntityContent {
-    Witch,
-    Slime,
-    Chest,
-    BookShelf,
-}
-
-#[allow(dead_code)]
-struct RemoteEntity {
-    last_update: FrameCount,
-    content: RemoteEntityContent,
-    position: Vec2,
-    level: i32,
-}
-
-#[allow(dead_code)]
-struct RemoteStates {
-    entities: HashMap<Uuid, RemoteEntity>,
-}
-
 fn receive_events(
     mut commands: Commands,
-    mut reader: EventReader<ServerMessage>,
+    mut reader: EventReader<IncomingMessage>,
+    player_query: Query<&Actor, (With<Player>, Without<RemotePlayer>)>,
     mut remotes: Query<
         (
             Entity,
@@ -183,163 +450,343 @@ fn receive_events(
     frame_count: Res<FrameCount>,
     life_bar_res: Res<LifeBarResource>,
     mut writer: EventWriter<SEEvent>,
+    mut sync: ResMut<PositionSync>,
+    mut stats: ResMut<NetworkStats>,
+    mut states: ResMut<RemoteStates>,
+    mut arena: ResMut<ArenaMatch>,
+    mut chat_writer: EventWriter<ChatEvent>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
 ) {
     // キャラクターを生成されたときに実際に反映させるのは次のフレームからですが、
     // 1フレームに複数のメッセージが届くことがあるため、
     // 1フレームに複数のキャラクターが生成されないようにセットで管理します
     let mut spawned_players = HashSet::new();
 
+    let own_uuid = player_query.get_single().map(|actor| actor.uuid).ok();
+
+    let room = current_room(current.level, &config);
+
     for message in reader.read() {
-        match message {
-            ServerMessage::String(text) => {
-                info!("Received text message: {}", text);
+        match bincode::deserialize::<RemoteMessage>(&message.0) {
+            Err(err) => {
+                warn!("Failed to deserialize: {:?}", err);
             }
-            ServerMessage::Binary(bin) => match bincode::deserialize::<RemoteMessage>(bin) {
-                Err(err) => {
-                    warn!("Failed to deserialize: {:?}", err);
+            Ok(command) => {
+                if let Some(sender) = command.sender() {
+                    *stats.received.entry(sender).or_insert(0) += message.0.len();
+
+                    // 別の部屋のプレイヤーからのメッセージは無視します
+                    // 部屋はメタデータで通知されるので、メタデータを受け取るまでは無視します
+                    let in_room = states
+                        .peers
+                        .get(&sender)
+                        .map_or(false, |peer| peer.room == room);
+                    if !in_room && !matches!(command, RemoteMessage::Metadata { .. }) {
+                        continue;
+                    }
                 }
-                Ok(command) => {
-                    match command {
-                        RemoteMessage::Position {
-                            sender: _sender,
-                            uuid,
-                            name,
-                            golds,
-                            x,
-                            y,
-                            vx,
-                            vy,
-                            life,
-                            max_life,
-                            angle,
-                            intensity,
-                        } => {
-                            let target = remotes
-                                .iter_mut()
-                                .find(|(_, _, actor, _, _, _)| actor.uuid == uuid);
-                            if let Some((
-                                _,
-                                mut remote,
-                                mut actor,
-                                mut actor_life,
-                                mut transform,
-                                mut velocity,
-                            )) = target
-                            {
+
+                match command {
+                    RemoteMessage::Snapshot {
+                        sender,
+                        seq,
+                        base,
+                        delta,
+                    } => {
+                        sync.register_peer(sender);
+                        states.touch_peer(sender, &frame_count);
+
+                        // 別のレベルにいるプレイヤーはスポーンしません
+                        let peer_level = states.peers.get(&sender).and_then(|p| p.level);
+                        let same_level = peer_level.is_some() && peer_level == current.level;
+
+                        let target = remotes
+                            .iter_mut()
+                            .find(|(_, _, actor, _, _, _)| actor.uuid == sender);
+                        if let Some((
+                            entity,
+                            mut remote,
+                            mut actor,
+                            mut actor_life,
+                            mut transform,
+                            mut velocity,
+                        )) = target
+                        {
+                            if !same_level {
+                                commands.entity(entity).despawn_recursive();
+                                continue;
+                            }
+
+                            // 基準となる状態を持っていない場合は復元できないので無視します
+                            // 送信側は Ack が届かなければいずれキーフレームを送ってきます
+                            let base_snapshot = match base {
+                                None => Some(PlayerSnapshot::default()),
+                                Some(base_seq) => remote
+                                    .snapshots
+                                    .iter()
+                                    .find(|(s, _)| *s == base_seq)
+                                    .map(|(_, snapshot)| *snapshot),
+                            };
+                            if let Some(base_snapshot) = base_snapshot {
+                                let snapshot = base_snapshot.apply(&delta);
+
                                 remote.last_update = *frame_count;
-                                remote.golds = golds;
-                                transform.translation.x = x;
-                                transform.translation.y = y;
-                                velocity.linvel.x = vx;
-                                velocity.linvel.y = vy;
-                                actor_life.life = life;
-                                actor_life.max_life = max_life;
-                                actor.pointer = Vec2::from_angle(angle);
-                                actor.intensity = intensity;
-                            } else if !spawned_players.contains(&uuid) {
-                                spawned_players.insert(uuid);
-                                spawn_witch(
-                                    &mut commands,
-                                    &assets,
-                                    Vec2::new(x, y),
-                                    angle,
-                                    uuid,
-                                    Some(name.clone()),
-                                    life,
-                                    max_life,
-                                    &life_bar_res,
-                                    true,
-                                    3.0,
-                                    0,
-                                    [None, None, None, None],
-                                    Inventory::new(),
-                                    [None, None, None, None, None, None, None, None],
-                                    RemotePlayer {
-                                        name,
-                                        golds,
-                                        last_update: *frame_count,
-                                    },
-                                    ActorGroup::Enemy,
-                                );
-                                info!("Remote player spawned: {}", uuid);
+                                remote.snapshots.push_back((seq, snapshot));
+                                while SNAPSHOT_HISTORY < remote.snapshots.len() {
+                                    remote.snapshots.pop_front();
+                                }
+
+                                // 順序が入れ替わって届いた古い状態は履歴には残しますが、反映はしません
+                                if remote.last_seq < seq {
+                                    remote.last_seq = seq;
+                                    let position = snapshot.position();
+                                    transform.translation.x = position.x;
+                                    transform.translation.y = position.y;
+                                    velocity.linvel = snapshot.velocity();
+                                    actor_life.life = snapshot.life as i32;
+                                    actor_life.max_life = snapshot.max_life as i32;
+                                    actor.pointer = Vec2::from_angle(snapshot.angle());
+                                    actor.intensity = snapshot.intensity();
+                                }
                             }
-                        }
-                        RemoteMessage::Fire(spawning) => {
-                            spawn_bullet(
+                        } else if same_level && base.is_none() && !spawned_players.contains(&sender)
+                        {
+                            spawned_players.insert(sender);
+                            let snapshot = PlayerSnapshot::default().apply(&delta);
+                            let (name, golds) = states
+                                .peers
+                                .get(&sender)
+                                .map(|p| (p.name.clone(), p.golds))
+                                .unwrap_or((String::new(), 0));
+                            spawn_witch(
                                 &mut commands,
-                                assets.atlas.clone(),
-                                &mut writer,
-                                &spawning,
+                                &assets,
+                                snapshot.position(),
+                                snapshot.angle(),
+                                sender,
+                                Some(name.clone()),
+                                snapshot.life as i32,
+                                snapshot.max_life as i32,
+                                &life_bar_res,
+                                true,
+                                snapshot.intensity(),
+                                0,
+                                [None, None, None, None],
+                                Inventory::new(),
+                                [None, None, None, None, None, None, None, None],
+                                RemotePlayer {
+                                    name,
+                                    golds,
+                                    last_update: *frame_count,
+                                    snapshots: VecDeque::from([(seq, snapshot)]),
+                                    last_seq: seq,
+                                    acked_seq: None,
+                                },
+                                // アリーナでは対戦相手、通常のレベルでは協力プレイの仲間になります
+                                // 仲間の魔女は同じグループになるため、互いの弾は当たりません
+                                if current.level == Some(GameLevel::MultiPlayArena) {
+                                    ActorGroup::Enemy
+                                } else {
+                                    ActorGroup::Player
+                                },
                             );
+                            info!("Remote player spawned: {}", sender);
+                        }
+                    }
+                    RemoteMessage::Ack {
+                        sender,
+                        target,
+                        seq,
+                    } => {
+                        if Some(target) == own_uuid {
+                            let ack = sync.acks.entry(sender).or_insert(None);
+                            *ack = Some(ack.map_or(seq, |a| a.max(seq)));
                         }
-                        RemoteMessage::Hit {
-                            sender: _sender,
-                            uuid,
-                            damage,
-                        } => {
+                    }
+                    RemoteMessage::Metadata {
+                        sender,
+                        name,
+                        golds,
+                        level,
+                        room: peer_room,
+                    } => {
+                        // 別の部屋のプレイヤーは、以前に同じ部屋にいた場合も含めて忘れます
+                        if peer_room != room {
+                            states.peers.remove(&sender);
+                            sync.acks.remove(&sender);
                             let target = remotes
-                                .iter_mut()
-                                .find(|(_, _, actor, _, _, _)| actor.uuid == uuid);
+                                .iter()
+                                .find(|(_, _, actor, _, _, _)| actor.uuid == sender);
+                            if let Some((entity, _, _, _, _, _)) = target {
+                                commands.entity(entity).despawn_recursive();
+                            }
+                            continue;
+                        }
 
-                            if let Some((_, mut remote, _, mut actor_life, _, _)) = target {
-                                actor_life.life -= damage;
-                                remote.last_update = *frame_count;
+                        sync.register_peer(sender);
+                        states.touch_peer(sender, &frame_count);
+                        if let Some(peer) = states.peers.get_mut(&sender) {
+                            // 別のレベルに移動したプレイヤーにはキーフレームを送り直します
+                            if peer.level != level {
+                                sync.acks.insert(sender, None);
                             }
+                            peer.name = name.clone();
+                            peer.golds = golds;
+                            peer.level = level;
+                            peer.room = peer_room;
                         }
-                        RemoteMessage::Die {
-                            sender: _sender,
-                            uuid,
-                        } => {
-                            let target = remotes
-                                .iter_mut()
-                                .find(|(_, _, actor, _, _, _)| actor.uuid == uuid);
 
-                            if let Some((entity, _, _, _, transform, _)) = target {
-                                writer
-                                    .send(SEEvent::pos(SE::Cry, transform.translation.truncate()));
+                        let target = remotes
+                            .iter_mut()
+                            .find(|(_, _, actor, _, _, _)| actor.uuid == sender);
+                        if let Some((_, mut remote, _, _, _, _)) = target {
+                            remote.name = name;
+                            remote.golds = golds;
+                        }
+                    }
+                    RemoteMessage::Entities {
+                        sender,
+                        level,
+                        entities,
+                    } => {
+                        states.touch_peer(sender, &frame_count);
+                        states.receive_entities(sender, level, entities, &frame_count);
+                    }
+                    RemoteMessage::Fire(mut spawning) => {
+                        // アリーナでは他の魔女の弾丸が自分の魔女にも当たるようにします
+                        if current.level == Some(GameLevel::MultiPlayArena) {
+                            spawning.filter |= WITCH_GROUP;
+                        }
+                        spawn_bullet(&mut commands, assets.atlas.clone(), &mut writer, &spawning);
+                    }
+                    RemoteMessage::Hit {
+                        sender,
+                        attacker,
+                        damage,
+                    } => {
+                        arena.record_hit(attacker, sender, damage);
+                    }
+                    RemoteMessage::Die {
+                        sender: _sender,
+                        uuid,
+                        killer,
+                    } => {
+                        let target = remotes
+                            .iter_mut()
+                            .find(|(_, _, actor, _, _, _)| actor.uuid == uuid);
 
-                                commands.entity(entity).despawn_recursive();
+                        if let Some((entity, _, _, _, transform, _)) = target {
+                            writer.send(SEEvent::pos(SE::Cry, transform.translation.truncate()));
 
-                                for _ in 0..20 {
-                                    spawn_gold(
-                                        &mut commands,
-                                        &assets,
-                                        transform.translation.x,
-                                        transform.translation.y,
-                                    );
-                                }
+                            commands.entity(entity).despawn_recursive();
+                            sync.acks.remove(&uuid);
+                            arena.record_death(uuid, killer);
+
+                            for _ in 0..20 {
+                                spawn_gold(
+                                    &mut commands,
+                                    &assets,
+                                    transform.translation.x,
+                                    transform.translation.y,
+                                );
                             }
+                        } else {
+                            // プレイヤー以外のエンティティの消滅はホストから通知されます
+                            states.receive_die(uuid);
                         }
-                    };
-                }
-            },
-            _ => {}
+                    }
+                    RemoteMessage::Match {
+                        sender,
+                        round,
+                        mode,
+                        phase,
+                        remaining,
+                    } => {
+                        arena.receive_state(sender, round, mode, phase, remaining, &frame_count);
+                    }
+                    RemoteMessage::Chat { sender, message } => {
+                        chat_writer.send(ChatEvent { sender, message });
+                    }
+                };
+            }
         }
     }
 }
 
+/// リモートプレイヤーの名前が変わったときに名札を更新します
+fn update_remote_name_plate(
+    remotes: Query<&RemotePlayer, Changed<RemotePlayer>>,
+    mut plates: Query<(&Parent, &mut Text2d), With<WitchNamePlate>>,
+) {
+    for (parent, mut text) in plates.iter_mut() {
+        if let Ok(remote) = remotes.get(parent.get()) {
+            if text.0 != remote.name {
+                text.0 = remote.name.clone();
+            }
+        }
+    }
+}
+
+/// 送受信したバイト数を1秒ごとに集計します
+fn update_network_stats(
+    mut reader: EventReader<OutgoingMessage>,
+    mut stats: ResMut<NetworkStats>,
+    time: Res<Time>,
+) {
+    for message in reader.read() {
+        stats.sent += message.0.len();
+    }
+
+    let now = time.elapsed_secs();
+    if 1.0 <= now - stats.window_start {
+        stats.window_start = now;
+        stats.sent_per_second = stats.sent;
+        stats.sent = 0;
+        stats.received_per_second = std::mem::take(&mut stats.received);
+    }
+}
+
 /// 最終の Ping から120フレーム以上経過したリモートプレイヤーを削除します
 fn despawn_no_contact_remotes(
     mut commands: Commands,
     mut remotes: Query<(Entity, &Actor, &RemotePlayer)>,
     frame_count: Res<FrameCount>,
+    mut sync: ResMut<PositionSync>,
 ) {
     for (entity, actor, remote) in remotes.iter_mut() {
         if 120 < (frame_count.0 as i32 - remote.last_update.0 as i32) {
             info!("Remote player {} despawned", actor.uuid);
             commands.entity(entity).despawn_recursive();
+            sync.acks.remove(&actor.uuid);
+        }
+    }
+}
+
+impl RemoteMessage {
+    fn sender(&self) -> Option<Uuid> {
+        match self {
+            RemoteMessage::Snapshot { sender, .. } => Some(*sender),
+            RemoteMessage::Ack { sender, .. } => Some(*sender),
+            RemoteMessage::Metadata { sender, .. } => Some(*sender),
+            RemoteMessage::Entities { sender, .. } => Some(*sender),
+            RemoteMessage::Fire(spawning) => spawning.sender,
+            RemoteMessage::Hit { sender, .. } => Some(*sender),
+            RemoteMessage::Die { sender, .. } => Some(*sender),
+            RemoteMessage::Match { sender, .. } => Some(*sender),
+            RemoteMessage::Chat { sender, .. } => Some(*sender),
         }
     }
 }
 
 pub fn send_remote_message(
-    writer: &mut EventWriter<ClientMessage>,
+    writer: &mut EventWriter<OutgoingMessage>,
     online: bool,
     message: &RemoteMessage,
 ) {
     if online {
         let serialized = bincode::serialize::<RemoteMessage>(message).unwrap();
-        writer.send(ClientMessage::Binary(serialized));
+        writer.send(OutgoingMessage(serialized));
     }
 }
 
@@ -351,6 +798,10 @@ impl Plugin for RemotePlayerPlugin {
         // setup_level が完了すると current.level が更新されるため、
         // on_enter の条件分岐が正しく動かず、オンラインになりません
         // on_enter を先にやります
+        app.init_resource::<SyncConfig>();
+        app.init_resource::<PositionSync>();
+        app.init_resource::<NetworkStats>();
+
         app.add_systems(OnEnter(GameState::InGame), on_enter.before(setup_level));
 
         app.add_systems(OnExit(GameState::InGame), on_exit);
@@ -359,11 +810,15 @@ impl Plugin for RemotePlayerPlugin {
             FixedUpdate,
             (
                 send_player_states,
+                send_player_metadata,
+                send_acks,
                 receive_events,
                 despawn_no_contact_remotes,
             )
                 .run_if(in_state(GameState::InGame))
                 .before(PhysicsSet::SyncBackend),
         );
+
+        app.add_systems(Update, (update_remote_name_plate, update_network_stats));
     }
 }
@@ -0,0 +1,513 @@
+use crate::asset::GameAssets;
+use crate::controller::network_simulator::OutgoingMessage;
+use crate::controller::player::Player;
+use crate::controller::remote::{send_remote_message, RemoteMessage};
+use crate::controller::snapshot::EntitySnapshot;
+use crate::enemy::eyeball::{spawn_eyeball, EyeballControl};
+use crate::enemy::slime::{spawn_slime, SlimeControl};
+use crate::entity::actor::{Actor, ActorGroup};
+use crate::entity::book_shelf::{spawn_book_shelf, Bookshelf};
+use crate::entity::chest::{spawn_chest, Chest, ChestType};
+use crate::entity::life::Life;
+use crate::hud::life_bar::LifeBarResource;
+use crate::level::{CurrentLevel, GameLevel};
+use crate::states::GameState;
+use bevy::{core::FrameCount, prelude::*, utils::HashMap};
+use bevy_rapier2d::{plugin::PhysicsSet, prelude::Velocity};
+use bevy_simple_websocket::{ReadyState, WebSocketState};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// ホストがエンティティの状態を送信する間隔(フレーム数)
+const ENTITY_SEND_INTERVAL: u32 = 8;
+
+/// ホストから通知されなくなったエンティティを削除するまでのフレーム数
+/// 同じレベルに同時に到達したときに生成された余計なモンスターもこれで削除されます
+const ENTITY_TIMEOUT: u32 = 120;
+
+/// 他のプレイヤーから通知がなくなったとみなすまでのフレーム数
+const PEER_TIMEOUT: u32 = 120;
+
+/// 受信したエンティティの情報を保持するフレーム数
+/// 別のレベルにいるあいだも情報を保持しておき、そのレベルに入ったときにスポーンします
+const REMOTE_STATE_LIFETIME: u32 = 600;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteEntityContent {
+    Slime,
+    Eyeball,
+    Chest,
+    Crate,
+    BookShelf,
+}
+
+/// ネットワークで同期されるエンティティを表します
+/// ホストのワールドで生成されたエンティティと、ホストから通知されてスポーンしたエンティティの両方に付与されます
+#[derive(Component)]
+pub struct Replicated {
+    pub uuid: Uuid,
+    pub content: RemoteEntityContent,
+    pub last_update: FrameCount,
+}
+
+/// レベルの生成時に配置されたエンティティのタイル座標です
+/// 同じレベルの同じタイルに生成されたエンティティは、どのプレイヤーのワールドでも同じUUIDになります
+#[derive(Component)]
+pub struct SpawnTile(pub i32, pub i32);
+
+impl SpawnTile {
+    /// レベルとタイル座標から同期用のUUIDを導出します
+    fn uuid(&self, level: GameLevel) -> Uuid {
+        let level = match level {
+            GameLevel::Level(level) => level as u32,
+            GameLevel::MultiPlayArena => u32::MAX,
+        };
+        Uuid::from_u128(
+            ((level as u128) << 64) | ((self.0 as u32 as u128) << 32) | (self.1 as u32 as u128),
+        )
+    }
+}
+
+/// ホストではないクライアントで、ホストから位置やライフを受け取っているエンティティを表します
+/// このエンティティは自分では行動を選択せず、ライフがゼロになっても消滅しません
+/// 消滅はホストからの Die の通知で行います
+#[derive(Component)]
+pub struct RemoteControlled;
+
+/// 他のプレイヤーの情報です
+pub struct RemotePeer {
+    pub name: String,
+    pub golds: i32,
+    pub level: Option<GameLevel>,
+
+    /// メタデータで通知された部屋
+    pub room: String,
+
+    pub last_seen: FrameCount,
+}
+
+struct RemoteEntity {
+    /// このエンティティを通知したホスト
+    owner: Uuid,
+    last_update: FrameCount,
+    snapshot: EntitySnapshot,
+    level: GameLevel,
+}
+
+/// 受信した他のプレイヤーとエンティティの情報です
+/// 現在のレベル以外の情報も保持しています
+#[derive(Resource, Default)]
+pub struct RemoteStates {
+    pub peers: HashMap<Uuid, RemotePeer>,
+    entities: HashMap<Uuid, RemoteEntity>,
+
+    /// ホストから消滅を通知されたエンティティ
+    killed: Vec<Uuid>,
+}
+
+impl RemoteStates {
+    /// ホストから通知されたエンティティの状態を記録します
+    /// 自分よりUUIDの小さいプレイヤーからの通知は、既存の情報がタイムアウトするまで無視します
+    pub fn receive_entities(
+        &mut self,
+        sender: Uuid,
+        level: GameLevel,
+        entities: Vec<EntitySnapshot>,
+        frame_count: &FrameCount,
+    ) {
+        for snapshot in entities {
+            if let Some(existing) = self.entities.get(&snapshot.uuid) {
+                let elapsed = frame_count.0.wrapping_sub(existing.last_update.0);
+                if sender < existing.owner && elapsed < ENTITY_TIMEOUT {
+                    continue;
+                }
+            }
+            self.entities.insert(
+                snapshot.uuid,
+                RemoteEntity {
+                    owner: sender,
+                    last_update: *frame_count,
+                    snapshot,
+                    level,
+                },
+            );
+        }
+    }
+
+    pub fn receive_die(&mut self, uuid: Uuid) {
+        if self.entities.remove(&uuid).is_some() {
+            self.killed.push(uuid);
+        }
+    }
+
+    pub fn touch_peer(&mut self, uuid: Uuid, frame_count: &FrameCount) {
+        let peer = self.peers.entry(uuid).or_insert(RemotePeer {
+            name: String::new(),
+            golds: 0,
+            level: None,
+            room: String::new(),
+            last_seen: *frame_count,
+        });
+        peer.last_seen = *frame_count;
+    }
+
+    /// 指定したレベルにいるプレイヤーのUUIDの一覧を返します
+    pub fn peers_in(&self, level: GameLevel, frame_count: &FrameCount) -> Vec<Uuid> {
+        self.peers
+            .iter()
+            .filter(|(_, peer)| {
+                peer.level == Some(level)
+                    && frame_count.0.wrapping_sub(peer.last_seen.0) < PEER_TIMEOUT
+            })
+            .map(|(uuid, _)| *uuid)
+            .collect()
+    }
+}
+
+/// 現在のレベルのホストです
+/// そのレベルにいる最もUUIDの大きいプレイヤーがホストになります
+#[derive(Resource, Default)]
+pub struct LevelHost {
+    pub host: Option<Uuid>,
+    pub is_host: bool,
+}
+
+/// モンスターや宝箱に同期用のUUIDを付与します
+fn tag_replicated(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            Option<&Actor>,
+            Option<&Chest>,
+            Option<&SpawnTile>,
+            Has<SlimeControl>,
+            Has<EyeballControl>,
+            Has<Bookshelf>,
+        ),
+        (
+            Without<Replicated>,
+            Or<(
+                With<SlimeControl>,
+                With<EyeballControl>,
+                With<Chest>,
+                With<Bookshelf>,
+            )>,
+        ),
+    >,
+    current: Res<CurrentLevel>,
+    frame_count: Res<FrameCount>,
+) {
+    for (entity, actor, chest, spawn_tile, slime, eyeball, book_shelf) in query.iter() {
+        let content = if slime {
+            // プレイヤーが召喚したスライムは、召喚したプレイヤーのワールドでそれぞれ生成されるので同期しません
+            if actor.map(|a| a.actor_group) != Some(ActorGroup::Enemy) {
+                continue;
+            }
+            RemoteEntityContent::Slime
+        } else if eyeball {
+            RemoteEntityContent::Eyeball
+        } else if book_shelf {
+            RemoteEntityContent::BookShelf
+        } else if let Some(chest) = chest {
+            match chest.chest_type {
+                ChestType::Chest => RemoteEntityContent::Chest,
+                ChestType::Crate => RemoteEntityContent::Crate,
+            }
+        } else {
+            continue;
+        };
+
+        // レベルの生成時に配置されたものはタイル座標からUUIDを決めるので、
+        // ホストから通知されたエンティティと自分のワールドのエンティティが一致します
+        let uuid = match (spawn_tile, current.level) {
+            (Some(tile), Some(level)) => tile.uuid(level),
+            _ => actor.map(|a| a.uuid).unwrap_or_else(Uuid::new_v4),
+        };
+
+        commands.entity(entity).insert(Replicated {
+            uuid,
+            content,
+            last_update: *frame_count,
+        });
+    }
+}
+
+/// 現在のレベルのホストを選出します
+/// ホストがいなくなった場合は、次にUUIDの大きいプレイヤーが自動的にホストを引き継ぎます
+fn elect_host(
+    player_query: Query<&Actor, With<Player>>,
+    current: Res<CurrentLevel>,
+    states: Res<RemoteStates>,
+    frame_count: Res<FrameCount>,
+    mut host: ResMut<LevelHost>,
+) {
+    if let (Ok(actor), Some(level)) = (player_query.get_single(), current.level) {
+        let mut candidates = states.peers_in(level, &frame_count);
+        candidates.push(actor.uuid);
+        let elected = candidates.into_iter().max();
+        if host.host != elected {
+            info!("Level host changed: {:?}", elected);
+            host.host = elected;
+        }
+        host.is_host = elected == Some(actor.uuid);
+    }
+}
+
+/// ホストであるかどうかに応じて、エンティティを自分で操作するかどうかを切り替えます
+fn update_remote_controlled(
+    mut commands: Commands,
+    local_query: Query<Entity, (With<Replicated>, Without<RemoteControlled>)>,
+    remote_query: Query<Entity, (With<Replicated>, With<RemoteControlled>)>,
+    host: Res<LevelHost>,
+) {
+    if host.is_host {
+        for entity in remote_query.iter() {
+            commands.entity(entity).remove::<RemoteControlled>();
+        }
+    } else {
+        for entity in local_query.iter() {
+            commands.entity(entity).insert(RemoteControlled);
+        }
+    }
+}
+
+/// ホストは、現在のレベルのエンティティの状態を定期的に他のプレイヤーに通知します
+fn broadcast_entities(
+    mut writer: EventWriter<OutgoingMessage>,
+    player_query: Query<&Actor, With<Player>>,
+    query: Query<(&Replicated, &Transform, &Life, Option<&Velocity>)>,
+    state: Res<WebSocketState>,
+    current: Res<CurrentLevel>,
+    states: Res<RemoteStates>,
+    host: Res<LevelHost>,
+    frame_count: Res<FrameCount>,
+) {
+    if !host.is_host
+        || state.ready_state != ReadyState::OPEN
+        || frame_count.0 % ENTITY_SEND_INTERVAL != 0
+        || states.peers.is_empty()
+    {
+        return;
+    }
+    if let (Ok(actor), Some(level)) = (player_query.get_single(), current.level) {
+        let entities: Vec<EntitySnapshot> = query
+            .iter()
+            .map(|(replicated, transform, life, velocity)| {
+                EntitySnapshot::quantize(
+                    replicated.uuid,
+                    replicated.content,
+                    transform.translation.truncate(),
+                    velocity.map(|v| v.linvel).unwrap_or(Vec2::ZERO),
+                    life.life,
+                )
+            })
+            .collect();
+
+        send_remote_message(
+            &mut writer,
+            true,
+            &RemoteMessage::Entities {
+                sender: actor.uuid,
+                level,
+                entities,
+            },
+        );
+    }
+}
+
+/// ホストは、エンティティが消滅したことを他のプレイヤーに通知します
+fn broadcast_despawned(
+    mut writer: EventWriter<OutgoingMessage>,
+    mut removed: RemovedComponents<Replicated>,
+    mut uuids: Local<HashMap<Entity, Uuid>>,
+    query: Query<(Entity, &Replicated), Added<Replicated>>,
+    player_query: Query<&Actor, With<Player>>,
+    state: Res<WebSocketState>,
+    host: Res<LevelHost>,
+) {
+    for (entity, replicated) in query.iter() {
+        uuids.insert(entity, replicated.uuid);
+    }
+
+    for entity in removed.read() {
+        if let Some(uuid) = uuids.remove(&entity) {
+            if host.is_host && state.ready_state == ReadyState::OPEN {
+                if let Ok(actor) = player_query.get_single() {
+                    send_remote_message(
+                        &mut writer,
+                        true,
+                        &RemoteMessage::Die {
+                            sender: actor.uuid,
+                            uuid,
+                            killer: None,
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// ホストから受信した状態を、現在のレベルのエンティティに反映します
+/// まだスポーンしていないエンティティはここでスポーンします
+fn apply_remote_entities(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Replicated,
+        &mut Transform,
+        &mut Life,
+        Option<&mut Velocity>,
+    )>,
+    assets: Res<GameAssets>,
+    life_bar_res: Res<LifeBarResource>,
+    current: Res<CurrentLevel>,
+    mut states: ResMut<RemoteStates>,
+    host: Res<LevelHost>,
+    frame_count: Res<FrameCount>,
+) {
+    // 古い情報を削除します
+    states
+        .entities
+        .retain(|_, e| frame_count.0.wrapping_sub(e.last_update.0) < REMOTE_STATE_LIFETIME);
+    states
+        .peers
+        .retain(|_, p| frame_count.0.wrapping_sub(p.last_seen.0) < REMOTE_STATE_LIFETIME);
+
+    // ホストから消滅を通知されたエンティティは、ライフをゼロにして通常の消滅処理に任せます
+    let killed = std::mem::take(&mut states.killed);
+    for (entity, replicated, _, mut life, _) in query.iter_mut() {
+        if killed.contains(&replicated.uuid) {
+            life.life = 0;
+            commands.entity(entity).remove::<RemoteControlled>();
+        }
+    }
+
+    if host.is_host {
+        return;
+    }
+
+    let level = match current.level {
+        Some(level) => level,
+        None => return,
+    };
+
+    let mut locals: HashMap<Uuid, Entity> = HashMap::default();
+    for (entity, replicated, _, _, _) in query.iter() {
+        locals.insert(replicated.uuid, entity);
+    }
+
+    for (uuid, remote) in states.entities.iter() {
+        if remote.level != level || Some(remote.owner) != host.host {
+            continue;
+        }
+
+        if let Some(entity) = locals.get(uuid) {
+            if let Ok((_, mut replicated, mut transform, mut life, velocity)) =
+                query.get_mut(*entity)
+            {
+                if replicated.last_update.0 < remote.last_update.0 {
+                    replicated.last_update = remote.last_update;
+                    let position = remote.snapshot.position();
+                    transform.translation.x = position.x;
+                    transform.translation.y = position.y;
+                    life.life = remote.snapshot.life as i32;
+                    if let Some(mut velocity) = velocity {
+                        velocity.linvel = remote.snapshot.velocity();
+                    }
+                }
+            }
+        } else {
+            let position = remote.snapshot.position();
+            let entity = match remote.snapshot.content {
+                RemoteEntityContent::Slime => spawn_slime(
+                    &mut commands,
+                    &assets,
+                    position,
+                    &life_bar_res,
+                    0,
+                    3,
+                    ActorGroup::Enemy,
+                    None,
+                ),
+                RemoteEntityContent::Eyeball => {
+                    spawn_eyeball(&mut commands, &assets, position, &life_bar_res)
+                }
+                RemoteEntityContent::Chest => spawn_chest(
+                    &mut commands,
+                    assets.atlas.clone(),
+                    position.x,
+                    position.y,
+                    ChestType::Chest,
+                ),
+                RemoteEntityContent::Crate => spawn_chest(
+                    &mut commands,
+                    assets.atlas.clone(),
+                    position.x,
+                    position.y,
+                    ChestType::Crate,
+                ),
+                RemoteEntityContent::BookShelf => {
+                    spawn_book_shelf(&mut commands, assets.atlas.clone(), position.x, position.y)
+                }
+            };
+            commands.entity(entity).insert((
+                Replicated {
+                    uuid: *uuid,
+                    content: remote.snapshot.content,
+                    last_update: remote.last_update,
+                },
+                RemoteControlled,
+            ));
+        }
+    }
+}
+
+/// ホストではない場合、ホストから通知されなくなったエンティティを削除します
+/// 同じレベルに同時に到達して、自分のワールドにだけ生成された余計なモンスターもここで削除されます
+fn despawn_orphan_entities(
+    mut commands: Commands,
+    query: Query<(Entity, &Replicated)>,
+    host: Res<LevelHost>,
+    frame_count: Res<FrameCount>,
+) {
+    if host.is_host {
+        return;
+    }
+    for (entity, replicated) in query.iter() {
+        if ENTITY_TIMEOUT < frame_count.0.wrapping_sub(replicated.last_update.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn on_enter(mut states: ResMut<RemoteStates>, mut host: ResMut<LevelHost>) {
+    states.killed.clear();
+    *host = LevelHost::default();
+}
+
+pub struct ReplicationPlugin;
+
+impl Plugin for ReplicationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteStates>();
+        app.init_resource::<LevelHost>();
+        app.add_systems(OnEnter(GameState::InGame), on_enter);
+        app.add_systems(
+            FixedUpdate,
+            (
+                tag_replicated,
+                elect_host,
+                update_remote_controlled,
+                broadcast_entities,
+                broadcast_despawned,
+                apply_remote_entities,
+                despawn_orphan_entities,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
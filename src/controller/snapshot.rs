@@ -0,0 +1,192 @@
+use crate::controller::replication::RemoteEntityContent;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use uuid::Uuid;
+
+/// 位置の量子化の倍率
+/// 0.5ピクセル単位で送信するので、i16で ±16383 ピクセルの範囲を表現できます
+const POSITION_SCALE: f32 = 2.0;
+
+/// 速度の量子化の倍率
+/// 1ピクセル毎秒単位で送信します
+const VELOCITY_SCALE: f32 = 1.0;
+
+/// 明るさの量子化の倍率
+const INTENSITY_SCALE: f32 = 10.0;
+
+/// 角度の分解能
+/// 256段階なので約1.4度単位になります
+const ANGLE_STEPS: f32 = 256.0;
+
+/// 送信用に量子化されたプレイヤーキャラクターの状態です
+/// bincode は固定長で整数をエンコードするため、f32 をそのまま送るよりも小さくなります
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub x: i16,
+    pub y: i16,
+    pub vx: i16,
+    pub vy: i16,
+    pub angle: u8,
+    pub intensity: u8,
+    pub life: u16,
+    pub max_life: u16,
+}
+
+/// 基準となる状態からの差分です
+/// 変化していないフィールドは None になり、bincode では1バイトだけで済みます
+/// すべてのフィールドが Some の場合はキーフレームとして扱われます
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerDelta {
+    pub x: Option<i16>,
+    pub y: Option<i16>,
+    pub vx: Option<i16>,
+    pub vy: Option<i16>,
+    pub angle: Option<u8>,
+    pub intensity: Option<u8>,
+    pub life: Option<u16>,
+    pub max_life: Option<u16>,
+}
+
+impl PlayerSnapshot {
+    pub fn quantize(
+        position: Vec2,
+        velocity: Vec2,
+        angle: f32,
+        intensity: f32,
+        life: i32,
+        max_life: i32,
+    ) -> Self {
+        Self {
+            x: quantize_i16(position.x, POSITION_SCALE),
+            y: quantize_i16(position.y, POSITION_SCALE),
+            vx: quantize_i16(velocity.x, VELOCITY_SCALE),
+            vy: quantize_i16(velocity.y, VELOCITY_SCALE),
+            angle: quantize_angle(angle),
+            intensity: (intensity * INTENSITY_SCALE)
+                .round()
+                .clamp(0.0, u8::MAX as f32) as u8,
+            life: life.clamp(0, u16::MAX as i32) as u16,
+            max_life: max_life.clamp(0, u16::MAX as i32) as u16,
+        }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32) / POSITION_SCALE
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        Vec2::new(self.vx as f32, self.vy as f32) / VELOCITY_SCALE
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.angle as f32 / ANGLE_STEPS * 2.0 * PI - PI
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity as f32 / INTENSITY_SCALE
+    }
+
+    /// base からこの状態への差分を返します
+    pub fn diff(&self, base: &PlayerSnapshot) -> PlayerDelta {
+        PlayerDelta {
+            x: changed(self.x, base.x),
+            y: changed(self.y, base.y),
+            vx: changed(self.vx, base.vx),
+            vy: changed(self.vy, base.vy),
+            angle: changed(self.angle, base.angle),
+            intensity: changed(self.intensity, base.intensity),
+            life: changed(self.life, base.life),
+            max_life: changed(self.max_life, base.max_life),
+        }
+    }
+
+    /// この状態を基準として差分を適用した状態を返します
+    pub fn apply(&self, delta: &PlayerDelta) -> PlayerSnapshot {
+        PlayerSnapshot {
+            x: delta.x.unwrap_or(self.x),
+            y: delta.y.unwrap_or(self.y),
+            vx: delta.vx.unwrap_or(self.vx),
+            vy: delta.vy.unwrap_or(self.vy),
+            angle: delta.angle.unwrap_or(self.angle),
+            intensity: delta.intensity.unwrap_or(self.intensity),
+            life: delta.life.unwrap_or(self.life),
+            max_life: delta.max_life.unwrap_or(self.max_life),
+        }
+    }
+}
+
+impl PlayerDelta {
+    /// すべてのフィールドを含むキーフレームを作成します
+    pub fn full(snapshot: &PlayerSnapshot) -> Self {
+        PlayerDelta {
+            x: Some(snapshot.x),
+            y: Some(snapshot.y),
+            vx: Some(snapshot.vx),
+            vy: Some(snapshot.vy),
+            angle: Some(snapshot.angle),
+            intensity: Some(snapshot.intensity),
+            life: Some(snapshot.life),
+            max_life: Some(snapshot.max_life),
+        }
+    }
+}
+
+fn changed<T: PartialEq>(value: T, base: T) -> Option<T> {
+    if value == base {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn quantize_i16(value: f32, scale: f32) -> i16 {
+    (value * scale)
+        .round()
+        .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+fn quantize_angle(angle: f32) -> u8 {
+    let normalized = (angle + PI).rem_euclid(2.0 * PI) / (2.0 * PI);
+    ((normalized * ANGLE_STEPS).round() as u32 % ANGLE_STEPS as u32) as u8
+}
+
+/// 送信用に量子化されたモンスターなどのエンティティの状態です
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntitySnapshot {
+    pub uuid: Uuid,
+    pub content: RemoteEntityContent,
+    pub x: i16,
+    pub y: i16,
+    pub vx: i16,
+    pub vy: i16,
+    pub life: u16,
+}
+
+impl EntitySnapshot {
+    pub fn quantize(
+        uuid: Uuid,
+        content: RemoteEntityContent,
+        position: Vec2,
+        velocity: Vec2,
+        life: i32,
+    ) -> Self {
+        Self {
+            uuid,
+            content,
+            x: quantize_i16(position.x, POSITION_SCALE),
+            y: quantize_i16(position.y, POSITION_SCALE),
+            vx: quantize_i16(velocity.x, VELOCITY_SCALE),
+            vy: quantize_i16(velocity.y, VELOCITY_SCALE),
+            life: life.clamp(0, u16::MAX as i32) as u16,
+        }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32) / POSITION_SCALE
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        Vec2::new(self.vx as f32, self.vy as f32) / VELOCITY_SCALE
+    }
+}
@@ -1,12 +1,13 @@
 use crate::{
     config::GameConfig,
-    controller::player::Player,
+    controller::{network_simulator::NetworkConditions, player::Player},
+    enemy::pathfinding::NavigationDebug,
     entity::{actor::Actor, life::Life},
     hud::overlay::OverlayEvent,
     level::{CurrentLevel, GameLevel},
     physics::GamePhysics,
     player_state::PlayerState,
-    states::GameState,
+    states::{GameMenuState, GameState},
 };
 use bevy::{
     input::{
@@ -25,19 +26,44 @@ fn process_debug_command(
     mut writer: EventWriter<OverlayEvent>,
     mut physics: ResMut<GamePhysics>,
     player_query: Query<(&Player, &Actor, &Life)>,
+    mut conditions: ResMut<NetworkConditions>,
+    menu: Option<Res<State<GameMenuState>>>,
+    mut navigation: ResMut<NavigationDebug>,
 ) {
+    // チャットの入力中はコマンドとして扱いません
+    let chatting = menu.map_or(false, |m| *m.get() == GameMenuState::ChatOpen);
+
+    let mut enter = false;
+
     for ev in evr_kbd.read() {
-        if ev.state == ButtonState::Released {
+        if ev.state == ButtonState::Released || chatting {
             continue;
         }
         match ev.logical_key {
             Key::Character(ref c) => {
                 local.push_str(c);
             }
+            Key::Space => {
+                local.push(' ');
+            }
+            Key::Enter => {
+                enter = true;
+            }
             _ => {}
         }
     }
 
+    // 引数をとるコマンドは Enter で確定します
+    // 例: netlag 120 30 / netloss 0.1
+    if enter {
+        if let Some(index) = local.rfind("net") {
+            if apply_network_command(&mut conditions, &local[index..]) {
+                info!("Network conditions: {:?}", *conditions);
+            }
+        }
+        local.clear();
+    }
+
     if local.ends_with("next") {
         local.clear();
         match level.next_level {
@@ -75,6 +101,66 @@ fn process_debug_command(
     } else if local.ends_with("resume") {
         local.clear();
         physics.active = true;
+    } else if local.ends_with("netbad") {
+        local.clear();
+        *conditions = NetworkConditions {
+            latency_ms: 300,
+            jitter_ms: 150,
+            loss: 0.1,
+            duplication: 0.05,
+            reorder: 0.1,
+        };
+        info!("Network conditions: {:?}", *conditions);
+    } else if local.ends_with("netok") {
+        local.clear();
+        *conditions = NetworkConditions::default();
+        info!("Network conditions: {:?}", *conditions);
+    } else if local.ends_with("navpath") {
+        local.clear();
+        navigation.0 = !navigation.0;
+        info!("Navigation paths: {}", navigation.0);
+    }
+}
+
+/// 通信状態のシミュレーションのコマンドを解釈して、通信状態に反映します
+/// 通信状態は組み合わせて指定でき、引数を省略した場合は代表的な値を使います
+/// コマンドとして解釈できた場合は true を返します
+fn apply_network_command(conditions: &mut NetworkConditions, line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or("");
+    let args: Vec<&str> = words.collect();
+
+    let parse_u32 = |index: usize, default: u32| match args.get(index) {
+        Some(arg) => arg.parse::<u32>().map_err(|_| *arg),
+        None => Ok(default),
+    };
+    let parse_rate = |index: usize, default: f32| match args.get(index) {
+        Some(arg) => arg
+            .parse::<f32>()
+            .map(|rate| rate.clamp(0.0, 1.0))
+            .map_err(|_| *arg),
+        None => Ok(default),
+    };
+
+    let result = match command {
+        "netlag" => parse_u32(0, 200).and_then(|latency| {
+            parse_u32(1, latency / 4).map(|jitter| {
+                conditions.latency_ms = latency;
+                conditions.jitter_ms = jitter;
+            })
+        }),
+        "netloss" => parse_rate(0, 0.2).map(|rate| conditions.loss = rate),
+        "netdup" => parse_rate(0, 0.2).map(|rate| conditions.duplication = rate),
+        "netreorder" => parse_rate(0, 0.2).map(|rate| conditions.reorder = rate),
+        _ => return false,
+    };
+
+    match result {
+        Ok(()) => true,
+        Err(arg) => {
+            warn!("Invalid argument for {}: {}", command, arg);
+            false
+        }
     }
 }
 
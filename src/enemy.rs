@@ -1,5 +1,9 @@
+pub mod ai;
 pub mod basic;
+pub mod boss;
 pub mod buer;
+pub mod elite;
 pub mod eyeball;
 pub mod huge_slime;
+pub mod pathfinding;
 pub mod slime;
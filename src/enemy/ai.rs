@@ -0,0 +1,426 @@
+use crate::constant::*;
+use crate::controller::replication::RemoteControlled;
+use crate::enemy::pathfinding::NavigationGrid;
+use crate::entity::actor::{Actor, ActorFireState, ActorGroup};
+use crate::entity::life::Life;
+use crate::physics::compare_distance;
+use crate::se::{SEEvent, SE};
+use crate::set::GameSet;
+use crate::states::GameState;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::collections::HashMap;
+
+/// 巡回の目的地に到着したとみなす距離
+const PATROL_ARRIVAL: f32 = TILE_SIZE * 0.5;
+
+/// 巡回の目的地に到着してから次の目的地を決めるまでの待ち時間
+const PATROL_WAIT: u32 = 60;
+
+/// 横移動の向きを切り替える間隔
+const STRAFE_INTERVAL: u32 = 90;
+
+/// 標的を見失ってから、最後に見た位置を覚えている時間
+const MEMORY_DURATION: u32 = 300;
+
+/// 覚えている位置に到着したとみなす距離
+const MEMORY_ARRIVAL: f32 = TILE_SIZE;
+
+/// 経路を探索し直す間隔
+const PATH_INTERVAL: u32 = 30;
+
+/// 経路上の地点に到着したとみなす距離
+const WAYPOINT_ARRIVAL: f32 = TILE_SIZE * 0.5;
+
+/// 敵の行動を組み立てる部品です
+/// EnemyAi の behaviors に並べた順に評価され、
+/// 移動の行動は最初に条件を満たしたものだけが採用されます
+/// 詠唱は移動とは独立して評価されます
+#[derive(Clone, Debug)]
+pub enum Behavior {
+    /// 標的が range 以内にいれば、標的を狙って詠唱します
+    Cast { range: f32 },
+
+    /// 標的が range より遠くにいれば接近します
+    Chase { range: f32 },
+
+    /// 標的との距離を min から max の間に保ちます
+    KeepDistance { min: f32, max: f32 },
+
+    /// ライフの割合が life 以下になったら標的から逃げます
+    Flee { life: f32 },
+
+    /// 標的が range 以内にいれば、標的の周りを回るように横に移動します
+    Strafe { range: f32 },
+
+    /// 標的がいないときは、出現地点から radius 以内をうろつきます
+    Patrol { radius: f32 },
+}
+
+/// 知覚と行動の組み合わせで敵の振る舞いを決めるコンポーネントです
+/// 知覚した標的に対して behaviors を評価し、Actor の移動方向と詠唱を操作します
+#[derive(Component, Debug)]
+pub struct EnemyAi {
+    /// 標的が見える範囲
+    /// 壁に遮られている標的は見えません
+    pub vision: f32,
+
+    /// 魔法の詠唱などの物音が聞こえる範囲
+    pub hearing: f32,
+
+    pub behaviors: Vec<Behavior>,
+
+    /// 行動を始めるまでの待ち時間
+    pub wait: u32,
+
+    /// 現在の標的の位置
+    /// 標的が見えないときは、最後に見た位置か物音がした位置です
+    pub target: Option<Vec2>,
+
+    /// 標的が見えているかどうか
+    pub in_sight: bool,
+
+    memory: u32,
+
+    home: Option<Vec2>,
+
+    patrol: Option<Vec2>,
+
+    patrol_wait: u32,
+
+    strafe: f32,
+
+    /// 標的が見えないときにたどる経路
+    path: Vec<Vec2>,
+
+    path_timer: u32,
+
+    animation: u32,
+}
+
+impl EnemyAi {
+    pub fn new(vision: f32, hearing: f32, behaviors: Vec<Behavior>) -> Self {
+        Self {
+            vision,
+            hearing,
+            behaviors,
+            wait: 0,
+            target: None,
+            in_sight: false,
+            memory: 0,
+            home: None,
+            patrol: None,
+            patrol_wait: 0,
+            strafe: 1.0,
+            path: Vec::new(),
+            path_timer: 0,
+            animation: 0,
+        }
+    }
+
+    pub fn with_wait(mut self, wait: u32) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// 現在たどっている経路です
+    pub fn path(&self) -> &[Vec2] {
+        &self.path
+    }
+
+    /// 見えている標的と聞こえた物音から、向かうべき位置を更新します
+    fn update_target(&mut self, origin: Vec2, visible: Option<Vec2>, noise: Option<Vec2>) {
+        if let Some(position) = visible {
+            self.target = Some(position);
+            self.in_sight = true;
+            self.memory = MEMORY_DURATION;
+            return;
+        }
+
+        self.in_sight = false;
+
+        if let Some(position) = noise {
+            self.target = Some(position);
+            self.memory = MEMORY_DURATION;
+            return;
+        }
+
+        // 最後に見た位置まで来ても見つからない場合や、時間が経った場合は諦めます
+        match self.target {
+            Some(target) if 0 < self.memory && MEMORY_ARRIVAL < target.distance(origin) => {
+                self.memory -= 1;
+            }
+            _ => {
+                self.target = None;
+                self.memory = 0;
+            }
+        }
+    }
+
+    /// 標的との間に壁がある場合は経路を探索し、たどるべき経路を更新します
+    /// 標的が見えている場合は経路を破棄し、まっすぐ向かいます
+    fn update_path(&mut self, grid: &NavigationGrid, origin: Vec2) {
+        match self.target {
+            Some(target) if !self.in_sight => {
+                if self.path_timer == 0 || self.path.is_empty() {
+                    self.path = grid.find_path(origin, target).unwrap_or_default();
+                    self.path_timer = PATH_INTERVAL;
+                } else {
+                    self.path_timer -= 1;
+                }
+                while let Some(waypoint) = self.path.first() {
+                    if waypoint.distance(origin) < WAYPOINT_ARRIVAL {
+                        self.path.remove(0);
+                    } else {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                self.path.clear();
+                self.path_timer = 0;
+            }
+        }
+    }
+
+    /// 標的の位置と自分のライフの割合から、移動方向と詠唱の向きを決めます
+    fn decide(&mut self, origin: Vec2, life_ratio: f32) -> (Vec2, Option<Vec2>) {
+        let mut movement: Option<Vec2> = None;
+        let mut fire: Option<Vec2> = None;
+
+        for behavior in self.behaviors.clone().iter() {
+            match (behavior, self.target) {
+                (Behavior::Cast { range }, Some(target)) => {
+                    let diff = target - origin;
+                    if fire.is_none() && self.in_sight && diff.length() < *range {
+                        fire = Some(diff);
+                    }
+                }
+                (Behavior::Chase { range }, Some(target)) => {
+                    let diff = target - origin;
+                    if movement.is_none() && *range <= diff.length() {
+                        movement = Some(self.approach(origin, target));
+                    }
+                }
+                (Behavior::KeepDistance { min, max }, Some(target)) => {
+                    let diff = target - origin;
+                    if movement.is_none() {
+                        if diff.length() < *min {
+                            movement = Some(-diff.normalize_or_zero());
+                        } else if *max < diff.length() {
+                            movement = Some(self.approach(origin, target));
+                        }
+                    }
+                }
+                (Behavior::Flee { life }, Some(target)) => {
+                    if movement.is_none() && life_ratio <= *life {
+                        movement = Some((origin - target).normalize_or_zero());
+                    }
+                }
+                (Behavior::Strafe { range }, Some(target)) => {
+                    let diff = target - origin;
+                    if movement.is_none() && self.in_sight && diff.length() < *range {
+                        if self.animation % STRAFE_INTERVAL == 0 && rand::random::<bool>() {
+                            self.strafe = -self.strafe;
+                        }
+                        movement = Some(diff.normalize_or_zero().perp() * self.strafe);
+                    }
+                }
+                (Behavior::Patrol { radius }, None) => {
+                    if movement.is_none() {
+                        movement = Some(self.patrol(origin, *radius));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        (movement.unwrap_or(Vec2::ZERO), fire)
+    }
+
+    /// 標的に近づく方向を返します
+    /// 経路がある場合は経路上の次の地点に向かいます
+    fn approach(&self, origin: Vec2, target: Vec2) -> Vec2 {
+        let destination = self.path.first().copied().unwrap_or(target);
+        (destination - origin).normalize_or_zero()
+    }
+
+    fn patrol(&mut self, origin: Vec2, radius: f32) -> Vec2 {
+        let home = *self.home.get_or_insert(origin);
+        match self.patrol {
+            Some(destination) if PATROL_ARRIVAL < destination.distance(origin) => {
+                (destination - origin).normalize_or_zero()
+            }
+            Some(_) => {
+                self.patrol = None;
+                self.patrol_wait = PATROL_WAIT;
+                Vec2::ZERO
+            }
+            None if 0 < self.patrol_wait => {
+                self.patrol_wait -= 1;
+                Vec2::ZERO
+            }
+            None => {
+                let angle = rand::random::<f32>() * std::f32::consts::TAU;
+                let distance = rand::random::<f32>() * radius;
+                self.patrol = Some(home + Vec2::from_angle(angle) * distance);
+                Vec2::ZERO
+            }
+        }
+    }
+}
+
+/// 指定した範囲にいる、自分以外で、かつ別のグループに所属するアクターのうち、
+/// 壁に遮られずに見えている最も近いものの位置を返します
+fn perceive(
+    context: &RapierContext,
+    map: &HashMap<Entity, (ActorGroup, Vec2)>,
+    entity: Entity,
+    group: ActorGroup,
+    origin: Vec2,
+    range: f32,
+) -> Option<Vec2> {
+    let mut enemies: Vec<Vec2> = Vec::new();
+    context.intersections_with_shape(
+        origin,
+        0.0,
+        &Collider::ball(range),
+        QueryFilter {
+            groups: Some(CollisionGroups::new(ENEMY_GROUP, WITCH_GROUP | ENEMY_GROUP)),
+            ..default()
+        },
+        |e| {
+            if e != entity {
+                if let Some((e_g, e_t)) = map.get(&e) {
+                    if *e_g != group {
+                        enemies.push(*e_t);
+                    }
+                }
+            }
+            true // 交差図形の検索を続ける
+        },
+    );
+    enemies.sort_by(compare_distance(origin));
+    enemies
+        .into_iter()
+        .find(|enemy| is_in_sight(context, origin, *enemy))
+}
+
+/// 指定した範囲で聞こえた物音のうち、最も近いものの位置を返します
+/// 自分と同じグループが立てた物音は無視します
+fn hear(
+    noises: &[(Vec2, Option<ActorGroup>)],
+    origin: Vec2,
+    range: f32,
+    group: ActorGroup,
+) -> Option<Vec2> {
+    let mut heard: Vec<Vec2> = noises
+        .iter()
+        .filter(|(noise, source)| noise.distance(origin) < range && *source != Some(group))
+        .map(|(noise, _)| *noise)
+        .collect();
+    heard.sort_by(compare_distance(origin));
+    heard.first().cloned()
+}
+
+/// 敵を引き寄せる物音かどうかを返します
+fn is_noise(se: SE) -> bool {
+    matches!(se, SE::Fire | SE::Shuriken | SE::Damage | SE::Break)
+}
+
+/// 二点の間に壁がないかどうかを返します
+fn is_in_sight(context: &RapierContext, origin: Vec2, target: Vec2) -> bool {
+    context
+        .cast_ray(
+            origin,
+            target - origin,
+            1.0,
+            true,
+            QueryFilter {
+                groups: Some(CollisionGroups::new(ENEMY_GROUP, WALL_GROUP)),
+                ..default()
+            },
+        )
+        .is_none()
+}
+
+fn update_enemy_ai(
+    mut actor_query: Query<(
+        Entity,
+        Option<&mut EnemyAi>,
+        &mut Actor,
+        &Transform,
+        Option<&Life>,
+        Has<RemoteControlled>,
+    )>,
+    rapier_context: Query<&RapierContext, With<DefaultRapierContext>>,
+    grid: Res<NavigationGrid>,
+    mut se_reader: EventReader<SEEvent>,
+) {
+    let context: &RapierContext = rapier_context.single();
+
+    let noises: Vec<(Vec2, Option<ActorGroup>)> = se_reader
+        .read()
+        .filter(|event| is_noise(event.se()))
+        .filter_map(|event| event.position().map(|position| (position, event.source())))
+        .collect();
+
+    // 多対多の参照になるので、HashMapでキャッシュしておく
+    let map: HashMap<Entity, (ActorGroup, Vec2)> = actor_query
+        .iter()
+        .map(|(e, _, a, t, _, _)| (e, (a.actor_group, t.translation.truncate())))
+        .collect();
+
+    for (entity, ai_optional, mut actor, transform, life, remote_controlled) in
+        actor_query.iter_mut()
+    {
+        if let Some(mut ai) = ai_optional {
+            actor.move_direction = Vec2::ZERO;
+            actor.fire_state = ActorFireState::Idle;
+
+            // ホストから操作されている敵は自分では行動しません
+            if remote_controlled {
+                continue;
+            }
+
+            if 0 < ai.wait {
+                ai.wait -= 1;
+                continue;
+            }
+
+            let origin = transform.translation.truncate();
+            let visible = perceive(context, &map, entity, actor.actor_group, origin, ai.vision);
+            let noise = hear(&noises, origin, ai.hearing, actor.actor_group);
+            ai.update_target(origin, visible, noise);
+            ai.update_path(&grid, origin);
+
+            let life_ratio = match life {
+                Some(life) if 0 < life.max_life => life.life as f32 / life.max_life as f32,
+                _ => 1.0,
+            };
+
+            let (movement, fire) = ai.decide(origin, life_ratio);
+            actor.move_direction = movement;
+            if let Some(pointer) = fire {
+                actor.pointer = pointer;
+                actor.fire_state = ActorFireState::Fire;
+            }
+
+            ai.animation += 1;
+        }
+    }
+}
+
+pub struct EnemyAiPlugin;
+
+impl Plugin for EnemyAiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            update_enemy_ai
+                .run_if(in_state(GameState::InGame))
+                .in_set(GameSet)
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
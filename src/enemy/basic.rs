@@ -1,43 +1,53 @@
+use crate::asset::GameAssets;
 use crate::constant::*;
 use crate::controller::despawn_with_gold::DespawnWithGold;
+use crate::enemy::ai::EnemyAi;
+use crate::enemy::eyeball::spawn_eyeball;
+use crate::enemy::slime::spawn_slime;
+use crate::enemy_props::EnemyType;
 use crate::entity::actor::{Actor, ActorFireState, ActorGroup, ActorState};
 use crate::entity::bullet::HomingTarget;
 use crate::entity::life::{Life, LifeBeingSprite};
 use crate::entity::EntityDepth;
 use crate::hud::life_bar::{spawn_life_bar, LifeBarResource};
 use crate::inventory::Inventory;
-use crate::spell::SpellType;
 use crate::states::GameState;
-use crate::wand::{Wand, WandSpell, WandType};
+use crate::wand::{Wand, WandItem, WandSpell, WandType};
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::*;
 use bevy_rapier2d::prelude::*;
 use uuid::*;
 
+/// 敵の種類の基礎情報に従って敵を生成します
+/// marker には種類ごとのコンポーネントを指定します
 pub fn spawn_basic_enemy<T: Component>(
     commands: &mut Commands,
-    aseprite: Handle<Aseprite>,
+    assets: &Res<GameAssets>,
+    enemy_type: EnemyType,
     position: Vec2,
     life_bar_locals: &Res<LifeBarResource>,
     marker: T,
-    name: &str,
-    spell: SpellType,
-    move_force: f32,
+    initial_wait: u32,
     gold: u32,
     actor_group: ActorGroup,
-) {
+) -> Entity {
+    let props = enemy_type.to_props();
+    let aseprite = (props.aseprite)(assets, actor_group);
+
     let mut slots = [None; MAX_SPELLS_IN_WAND];
     slots[0] = Some(WandSpell {
-        spell_type: spell,
+        spell_type: props.spell,
         price: 0,
     });
 
     commands
         .spawn((
-            Name::new(name.to_string()),
+            Name::new(props.name),
             StateScoped(GameState::InGame),
             DespawnWithGold { gold },
             marker,
+            EnemyAi::new(props.vision, props.hearing, props.behaviors.to_vec())
+                .with_wait(initial_wait),
             Actor {
                 uuid: Uuid::new_v4(),
                 spell_delay: 0,
@@ -45,7 +55,7 @@ pub fn spawn_basic_enemy<T: Component>(
                 pointer: Vec2::ZERO,
                 intensity: 0.0,
                 move_direction: Vec2::ZERO,
-                move_force: move_force,
+                move_force: props.move_force,
                 fire_state: ActorFireState::Idle,
                 fire_state_secondary: ActorFireState::Idle,
                 current_wand: 0,
@@ -56,7 +66,7 @@ pub fn spawn_basic_enemy<T: Component>(
                 equipments: [None; MAX_ITEMS_IN_EQUIPMENT],
                 wands: [
                     Some(Wand {
-                        wand_type: WandType::CypressWand,
+                        item: WandItem::new(WandType::CypressWand),
                         price: 0,
                         slots,
                         index: 0,
@@ -69,8 +79,8 @@ pub fn spawn_basic_enemy<T: Component>(
             ActorState::default(),
             EntityDepth,
             Life {
-                life: 15,
-                max_life: 15,
+                life: props.life,
+                max_life: props.life,
                 amplitude: 0.0,
             },
             HomingTarget,
@@ -114,5 +124,29 @@ pub fn spawn_basic_enemy<T: Component>(
             ));
 
             spawn_life_bar(&mut parent, &life_bar_locals);
-        });
+        })
+        .id()
+}
+
+/// 指定した種類の敵を、その種類の標準の設定で生成します
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    enemy_type: EnemyType,
+    position: Vec2,
+    life_bar_locals: &Res<LifeBarResource>,
+) -> Entity {
+    match enemy_type {
+        EnemyType::Slime => spawn_slime(
+            commands,
+            assets,
+            position,
+            life_bar_locals,
+            0,
+            enemy_type.to_props().gold,
+            ActorGroup::Enemy,
+            None,
+        ),
+        EnemyType::Eyeball => spawn_eyeball(commands, assets, position, life_bar_locals),
+    }
 }
@@ -0,0 +1,430 @@
+use crate::asset::GameAssets;
+use crate::audio::NextBGM;
+use crate::camera::GameCamera;
+use crate::constant::*;
+use crate::controller::player::Player;
+use crate::enemy::ai::EnemyAi;
+use crate::entity::dropped_item::spawn_dropped_item;
+use crate::entity::gold::spawn_gold;
+use crate::entity::life::Life;
+use crate::inventory::InventoryItem;
+use crate::se::{SEEvent, SE};
+use crate::set::GameSet;
+use crate::states::GameState;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// 登場演出の長さ
+const INTRO_FRAMES: u32 = 300;
+
+/// 登場演出の途中で咆哮するタイミング
+const INTRO_GROWL_FRAME: u32 = 120;
+
+/// プレイヤーが結界の縁からこの距離より内側に入ったら戦闘が始まります
+const INTRO_MARGIN: f32 = TILE_SIZE * 2.0;
+
+/// 結界の衝突形状を構成する頂点の数
+const BARRIER_SEGMENTS: usize = 48;
+
+/// フェイズが変わったときの画面の明滅の強さ
+const FLASH_ALPHA: f32 = 0.8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BossState {
+    /// プレイヤーが近づくのを待っています
+    Dormant,
+
+    /// 登場演出の最中です
+    Intro(u32),
+
+    Fighting,
+
+    Defeated,
+}
+
+/// ボスを表すコンポーネントです
+/// プレイヤーが近づくと登場演出を行って結界で部屋を閉ざし、
+/// ライフが閾値を下回るたびにフェイズが進み、倒されると戦利品を残して消滅します
+/// ボスごとの攻撃はフェイズを参照してそれぞれのモジュールで実装します
+#[derive(Component)]
+pub struct Boss {
+    /// 結界の中心
+    center: Vec2,
+
+    /// 結界の半径
+    arena_radius: f32,
+
+    /// ライフの割合がこれらの値を下回るとフェイズが進みます
+    phases: Vec<f32>,
+
+    /// 戦闘中に流すBGM
+    bgm: Option<Handle<AudioSource>>,
+
+    /// 倒したあとに流すBGM
+    /// 指定しない場合は戦闘前のBGMに戻します
+    victory_bgm: Option<Handle<AudioSource>>,
+
+    previous_bgm: Option<Handle<AudioSource>>,
+
+    /// 倒したときに必ず落とすアイテム
+    loot: Vec<InventoryItem>,
+
+    gold: u32,
+
+    /// 倒すとエンディングに進むかどうか
+    ending: bool,
+
+    phase: usize,
+
+    state: BossState,
+}
+
+impl Boss {
+    pub fn new(center: Vec2, arena_radius: f32) -> Self {
+        Self {
+            center,
+            arena_radius,
+            phases: Vec::new(),
+            bgm: None,
+            victory_bgm: None,
+            previous_bgm: None,
+            loot: Vec::new(),
+            gold: 0,
+            ending: false,
+            phase: 0,
+            state: BossState::Dormant,
+        }
+    }
+
+    pub fn with_phases(mut self, phases: Vec<f32>) -> Self {
+        self.phases = phases;
+        self
+    }
+
+    pub fn with_bgm(mut self, bgm: Handle<AudioSource>) -> Self {
+        self.bgm = Some(bgm);
+        self
+    }
+
+    pub fn with_victory_bgm(mut self, bgm: Handle<AudioSource>) -> Self {
+        self.victory_bgm = Some(bgm);
+        self
+    }
+
+    pub fn with_loot(mut self, loot: Vec<InventoryItem>) -> Self {
+        self.loot = loot;
+        self
+    }
+
+    pub fn with_gold(mut self, gold: u32) -> Self {
+        self.gold = gold;
+        self
+    }
+
+    pub fn with_ending(mut self) -> Self {
+        self.ending = true;
+        self
+    }
+
+    /// 現在のフェイズです
+    /// 最初のフェイズは 0 です
+    pub fn phase(&self) -> usize {
+        self.phase
+    }
+
+    pub fn is_fighting(&self) -> bool {
+        self.state == BossState::Fighting
+    }
+}
+
+/// ボスのフェイズが進んだときに送られるイベントです
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BossPhaseChanged {
+    pub boss: Entity,
+    pub phase: usize,
+}
+
+/// ボスが倒されたときに送られるイベントです
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BossDefeated {
+    pub boss: Entity,
+    pub ending: bool,
+}
+
+/// 戦闘中にプレイヤーを閉じ込める結界です
+#[derive(Component)]
+struct BossBarrier {
+    boss: Entity,
+    center: Vec2,
+    radius: f32,
+}
+
+/// 登場演出で表示するボスの名前です
+#[derive(Component)]
+struct BossIntroText;
+
+/// フェイズが変わったときに画面を明滅させます
+#[derive(Component)]
+struct BossFlash;
+
+fn spawn_boss_ui(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Name::new("boss_intro"),
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX + 1),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(30.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                BossIntroText,
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 48.0,
+                    ..default()
+                },
+            ));
+        });
+
+    commands.spawn((
+        Name::new("boss_flash"),
+        BossFlash,
+        StateScoped(GameState::InGame),
+        GlobalZIndex(HUD_Z_INDEX + 2),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.0)),
+    ));
+}
+
+fn spawn_barrier(commands: &mut Commands, boss: Entity, center: Vec2, radius: f32) {
+    let mut vertices: Vec<Vec2> = (0..BARRIER_SEGMENTS)
+        .map(|i| {
+            Vec2::from_angle(std::f32::consts::TAU * i as f32 / BARRIER_SEGMENTS as f32) * radius
+        })
+        .collect();
+    vertices.push(vertices[0]);
+
+    commands.spawn((
+        Name::new("boss_barrier"),
+        BossBarrier {
+            boss,
+            center,
+            radius,
+        },
+        StateScoped(GameState::InGame),
+        Transform::from_translation(center.extend(0.0)),
+        GlobalTransform::default(),
+        RigidBody::Fixed,
+        Collider::polyline(vertices, None),
+        CollisionGroups::new(WALL_GROUP, WITCH_GROUP | ENEMY_GROUP),
+    ));
+}
+
+/// プレイヤーが結界の内側に入ったら登場演出を始めます
+fn start_boss_intro(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &mut Boss)>,
+    player_query: Query<&Transform, With<Player>>,
+    mut next_bgm: ResMut<NextBGM>,
+) {
+    for (entity, mut boss) in boss_query.iter_mut() {
+        if boss.state != BossState::Dormant {
+            continue;
+        }
+        let entered = player_query.iter().any(|transform| {
+            transform.translation.truncate().distance(boss.center)
+                < boss.arena_radius - INTRO_MARGIN
+        });
+        if entered {
+            boss.state = BossState::Intro(0);
+            spawn_barrier(&mut commands, entity, boss.center, boss.arena_radius);
+            if let Some(bgm) = boss.bgm.clone() {
+                boss.previous_bgm = next_bgm.0.clone();
+                next_bgm.0 = Some(bgm);
+            }
+        }
+    }
+}
+
+/// 登場演出の間はカメラをボスに向け、ボスの行動を止めておきます
+fn update_boss_intro(
+    mut boss_query: Query<(&mut Boss, &Name, &Transform, Option<&mut EnemyAi>)>,
+    mut camera_query: Query<&mut GameCamera>,
+    mut text_query: Query<&mut Text, With<BossIntroText>>,
+    mut se_writer: EventWriter<SEEvent>,
+) {
+    let mut intro_name: Option<String> = None;
+
+    for (mut boss, name, transform, ai) in boss_query.iter_mut() {
+        let position = transform.translation.truncate();
+        if let BossState::Intro(frame) = boss.state {
+            if frame == 0 {
+                if let Ok(mut camera) = camera_query.get_single_mut() {
+                    camera.focus = Some(position);
+                }
+            }
+            if frame == INTRO_GROWL_FRAME {
+                se_writer.send(SEEvent::pos(SE::Growl, position));
+            }
+            if INTRO_FRAMES <= frame {
+                boss.state = BossState::Fighting;
+                if let Ok(mut camera) = camera_query.get_single_mut() {
+                    camera.focus = None;
+                }
+            } else {
+                boss.state = BossState::Intro(frame + 1);
+                intro_name = Some(name.as_str().to_string());
+            }
+        }
+
+        if let Some(mut ai) = ai {
+            if !boss.is_fighting() {
+                ai.wait = ai.wait.max(1);
+            }
+        }
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        let value = intro_name.unwrap_or_default();
+        if text.0 != value {
+            text.0 = value;
+        }
+    }
+}
+
+/// ライフの割合に応じてフェイズを進めます
+fn update_boss_phase(
+    mut boss_query: Query<(Entity, &mut Boss, &Life)>,
+    mut writer: EventWriter<BossPhaseChanged>,
+    mut flash_query: Query<&mut BackgroundColor, With<BossFlash>>,
+) {
+    for (entity, mut boss, life) in boss_query.iter_mut() {
+        if !boss.is_fighting() {
+            continue;
+        }
+        let ratio = life.life as f32 / life.max_life.max(1) as f32;
+        while boss.phase < boss.phases.len() && ratio < boss.phases[boss.phase] {
+            boss.phase += 1;
+            writer.send(BossPhaseChanged {
+                boss: entity,
+                phase: boss.phase,
+            });
+            if let Ok(mut flash) = flash_query.get_single_mut() {
+                flash.0 = Color::srgba(1.0, 1.0, 1.0, FLASH_ALPHA);
+            }
+        }
+    }
+}
+
+fn fade_boss_flash(mut flash_query: Query<&mut BackgroundColor, With<BossFlash>>) {
+    if let Ok(mut flash) = flash_query.get_single_mut() {
+        let alpha = flash.0.alpha();
+        if 0.0 < alpha {
+            flash.0.set_alpha((alpha - 0.02).max(0.0));
+        }
+    }
+}
+
+/// ライフがなくなったボスを消滅させ、戦利品を落とします
+fn defeat_boss(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    mut boss_query: Query<(Entity, &mut Boss, &Life, &Transform)>,
+    barrier_query: Query<(Entity, &BossBarrier)>,
+    mut camera_query: Query<&mut GameCamera>,
+    mut next_bgm: ResMut<NextBGM>,
+    mut writer: EventWriter<BossDefeated>,
+    mut se_writer: EventWriter<SEEvent>,
+) {
+    for (entity, mut boss, life, transform) in boss_query.iter_mut() {
+        if 0 < life.life || boss.state == BossState::Defeated {
+            continue;
+        }
+        let position = transform.translation.truncate();
+
+        if let BossState::Intro(_) = boss.state {
+            if let Ok(mut camera) = camera_query.get_single_mut() {
+                camera.focus = None;
+            }
+        }
+        boss.state = BossState::Defeated;
+
+        for item in boss.loot.iter() {
+            let offset = Vec2::from_angle(rand::random::<f32>() * std::f32::consts::TAU) * 16.0;
+            spawn_dropped_item(&mut commands, &assets, position + offset, *item);
+        }
+        for _ in 0..boss.gold {
+            spawn_gold(&mut commands, &assets, position.x, position.y);
+        }
+
+        if let Some(ref bgm) = boss.victory_bgm {
+            next_bgm.0 = Some(bgm.clone());
+        } else if boss.bgm.is_some() {
+            next_bgm.0 = boss.previous_bgm.clone();
+        }
+
+        for (barrier_entity, barrier) in barrier_query.iter() {
+            if barrier.boss == entity {
+                commands.entity(barrier_entity).despawn_recursive();
+            }
+        }
+
+        se_writer.send(SEEvent::pos(SE::Cry, position));
+        writer.send(BossDefeated {
+            boss: entity,
+            ending: boss.ending,
+        });
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_boss_barrier(mut gizmos: Gizmos, barrier_query: Query<&BossBarrier>) {
+    for barrier in barrier_query.iter() {
+        gizmos.circle_2d(
+            Isometry2d::from_translation(barrier.center),
+            barrier.radius,
+            Color::srgba(0.8, 0.3, 1.0, 0.6),
+        );
+    }
+}
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BossPhaseChanged>();
+        app.add_event::<BossDefeated>();
+        app.add_systems(OnEnter(GameState::InGame), spawn_boss_ui);
+        app.add_systems(
+            FixedUpdate,
+            (
+                start_boss_intro,
+                update_boss_intro,
+                update_boss_phase,
+                fade_boss_flash,
+                defeat_boss,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame))
+                .in_set(GameSet)
+                .before(PhysicsSet::SyncBackend),
+        );
+        app.add_systems(
+            Update,
+            draw_boss_barrier.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
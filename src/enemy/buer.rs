@@ -1,79 +1,208 @@
-// use crate::constant::*;
-// use crate::controller::enemy::Enemy;
-// use crate::entity::actor::{Actor, ActorFireState, ActorState};
-// use crate::entity::life::Life;
-// use crate::entity::EntityDepth;
-// use crate::spell::SpellType;
-// use crate::states::GameState;
-// use crate::wand::{Wand, WandType};
-// use bevy::prelude::*;
-// use bevy_aseprite_ultra::prelude::*;
-// use bevy_rapier2d::prelude::*;
-// use uuid::*;
-
-// #[derive(Component)]
-// pub struct Buer;
-
-// pub fn spawn_buer(commands: &mut Commands, aseprite: Handle<Aseprite>, position: Vec2) {
-//     let mut slots = [None; MAX_SPELLS_IN_WAND];
-//     slots[0] = Some(SpellType::MagicBolt);
-
-//     commands.spawn((
-//         Name::new("buer"),
-//         StateScoped(GameState::InGame),
-//         Enemy,
-//         Buer,
-//         Life {
-//             life: 15,
-//             max_life: 20,
-//             amplitude: 0.0,
-//         },
-//         Actor {
-//             uuid: Uuid::new_v4(),
-//             spell_delay: 0,
-//             pointer: Vec2::ZERO,
-//             intensity: 0.0,
-//             move_direction: Vec2::ZERO,
-//             move_force: 0.0,
-//             fire_state: ActorFireState::Idle,
-//             group: ENEMY_GROUP,
-//             filter: ENTITY_GROUP | WALL_GROUP | WITCH_GROUP,
-//             current_wand: 0,
-//             effects: default(),
-//             wands: [
-//                 Some(Wand {
-//                     wand_type: WandType::CypressWand,
-//                     slots,
-//                     index: 0,
-//                 }),
-//                 None,
-//                 None,
-//                 None,
-//             ],
-//         },
-//         ActorState::default(),
-//         EntityDepth,
-//         AseSpriteAnimation {
-//             aseprite: aseprite,
-//             animation: Animation::default().with_tag("idle"),
-//         },
-//         Transform::from_translation(position.extend(0.0)),
-//         (
-//             RigidBody::Dynamic,
-//             Collider::ball(48.0),
-//             GravityScale(0.0),
-//             LockedAxes::ROTATION_LOCKED,
-//             Damping {
-//                 linear_damping: 10.0,
-//                 angular_damping: 1.0,
-//             },
-//             ExternalForce::default(),
-//             ExternalImpulse::default(),
-//             ActiveEvents::COLLISION_EVENTS,
-//             CollisionGroups::new(
-//                 ENEMY_GROUP,
-//                 ENTITY_GROUP | WALL_GROUP | WITCH_GROUP | WITCH_BULLET_GROUP | ENEMY_GROUP,
-//             ),
-//         ),
-//     ));
-// }
+use crate::asset::GameAssets;
+use crate::constant::*;
+use crate::enemy::ai::{Behavior, EnemyAi};
+use crate::enemy::boss::Boss;
+use crate::entity::actor::{Actor, ActorFireState, ActorGroup, ActorState};
+use crate::entity::bullet::HomingTarget;
+use crate::entity::life::{Life, LifeBeingSprite};
+use crate::entity::EntityDepth;
+use crate::inventory::{Inventory, InventoryItem};
+use crate::inventory_item::InventoryItemType;
+use crate::set::GameSet;
+use crate::spell::SpellType;
+use crate::states::GameState;
+use crate::wand::{Wand, WandItem, WandSpell, WandType};
+use bevy::prelude::*;
+use bevy_aseprite_ultra::prelude::*;
+use bevy_rapier2d::prelude::*;
+use uuid::*;
+
+const BUER_COLLIDER_RADIUS: f32 = 48.0;
+
+const BUER_MAX_LIFE: i32 = 600;
+
+const BUER_VISION_RANGE: f32 = TILE_SIZE * 16.0;
+
+const BUER_HEARING_RANGE: f32 = TILE_SIZE * 16.0;
+
+const BUER_ARENA_RADIUS: f32 = TILE_SIZE * 12.0;
+
+/// 魔弾の連射、追尾弾、スライムの召喚を順番に繰り返します
+const BARRAGE_FRAMES: u32 = 180;
+
+const HOMING_FRAMES: u32 = 180;
+
+const SUMMON_FRAMES: u32 = 60;
+
+const ENRAGED_SPEED: u32 = 2;
+
+#[derive(Component)]
+pub struct Buer {
+    animation: u32,
+}
+
+/// 攻撃の種類ごとに異なる杖を使います
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BuerAttack {
+    /// 三連の紫の魔弾をばらまきます
+    Barrage = 0,
+
+    /// 追尾する魔弾を撃ちます
+    Homing = 1,
+
+    /// 手下のスライムを呼び出します
+    Summon = 2,
+}
+
+fn wand(spells: &[SpellType]) -> Option<Wand> {
+    let mut slots = [None; MAX_SPELLS_IN_WAND];
+    for (i, spell) in spells.iter().enumerate() {
+        slots[i] = Some(WandSpell {
+            spell_type: *spell,
+            price: 0,
+        });
+    }
+    Some(Wand {
+        item: WandItem::new(WandType::CypressWand),
+        price: 0,
+        slots,
+        index: 0,
+    })
+}
+
+pub fn spawn_buer(commands: &mut Commands, assets: &Res<GameAssets>, position: Vec2) -> Entity {
+    commands
+        .spawn((
+            Name::new("魔神ブエル"),
+            StateScoped(GameState::InGame),
+            // ライフが半分を切ると、攻撃の間隔が短くなります
+            Boss::new(position, BUER_ARENA_RADIUS)
+                .with_phases(vec![0.5])
+                .with_bgm(assets.battle_fight.clone())
+                .with_loot(vec![InventoryItem {
+                    item_type: InventoryItemType::Spell(SpellType::Homing),
+                    price: 0,
+                    count: 1,
+                }])
+                .with_gold(50),
+            Buer { animation: 0 },
+            Life {
+                life: BUER_MAX_LIFE,
+                max_life: BUER_MAX_LIFE,
+                amplitude: 0.0,
+            },
+            HomingTarget,
+            // 一定の距離を保ちながら横に回り込み、魔法を撃ち続けます
+            EnemyAi::new(
+                BUER_VISION_RANGE,
+                BUER_HEARING_RANGE,
+                vec![
+                    Behavior::Cast {
+                        range: TILE_SIZE * 12.0,
+                    },
+                    Behavior::KeepDistance {
+                        min: TILE_SIZE * 4.0,
+                        max: TILE_SIZE * 8.0,
+                    },
+                    Behavior::Strafe {
+                        range: TILE_SIZE * 8.0,
+                    },
+                    Behavior::Patrol {
+                        radius: TILE_SIZE * 4.0,
+                    },
+                ],
+            ),
+            Actor {
+                uuid: Uuid::new_v4(),
+                spell_delay: 0,
+                spell_delay_secondary: 0,
+                pointer: Vec2::ZERO,
+                intensity: 0.0,
+                move_direction: Vec2::ZERO,
+                move_force: 400000.0,
+                fire_state: ActorFireState::Idle,
+                fire_state_secondary: ActorFireState::Idle,
+                current_wand: BuerAttack::Barrage as usize,
+                effects: default(),
+                actor_group: ActorGroup::Enemy,
+                golds: 0,
+                inventory: Inventory::new(),
+                equipments: [None; MAX_ITEMS_IN_EQUIPMENT],
+                wands: [
+                    wand(&[
+                        SpellType::TripleCast,
+                        SpellType::PurpleBolt,
+                        SpellType::PurpleBolt,
+                        SpellType::PurpleBolt,
+                    ]),
+                    wand(&[SpellType::Homing, SpellType::MagicBolt]),
+                    wand(&[SpellType::SummonEnemySlime]),
+                    None,
+                ],
+            },
+            ActorState::default(),
+            EntityDepth,
+            Transform::from_translation(position.extend(5.0)),
+            GlobalTransform::default(),
+            InheritedVisibility::default(),
+            (
+                RigidBody::Dynamic,
+                Collider::ball(BUER_COLLIDER_RADIUS),
+                GravityScale(0.0),
+                LockedAxes::ROTATION_LOCKED,
+                Damping {
+                    linear_damping: 10.0,
+                    angular_damping: 1.0,
+                },
+                ExternalForce::default(),
+                ExternalImpulse::default(),
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    ENEMY_GROUP,
+                    ENTITY_GROUP | WALL_GROUP | WITCH_GROUP | WITCH_BULLET_GROUP | ENEMY_GROUP,
+                ),
+            ),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                LifeBeingSprite,
+                AseSpriteAnimation {
+                    aseprite: assets.buer.clone(),
+                    animation: Animation::default().with_tag("idle"),
+                },
+            ));
+        })
+        .id()
+}
+
+/// 経過時間に応じて使う杖を切り替えます
+fn update_buer_attack(mut query: Query<(&mut Buer, &mut Actor, &Boss)>) {
+    for (mut buer, mut actor, boss) in query.iter_mut() {
+        let speed = if 0 < boss.phase() { ENRAGED_SPEED } else { 1 };
+        buer.animation =
+            (buer.animation + speed) % (BARRAGE_FRAMES + HOMING_FRAMES + SUMMON_FRAMES);
+
+        let attack = if buer.animation < BARRAGE_FRAMES {
+            BuerAttack::Barrage
+        } else if buer.animation < BARRAGE_FRAMES + HOMING_FRAMES {
+            BuerAttack::Homing
+        } else {
+            BuerAttack::Summon
+        };
+        actor.current_wand = attack as usize;
+    }
+}
+
+pub struct BuerPlugin;
+
+impl Plugin for BuerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            update_buer_attack
+                .run_if(in_state(GameState::InGame))
+                .in_set(GameSet)
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use crate::asset::GameAssets;
+use crate::constant::*;
+use crate::controller::despawn_with_gold::DespawnWithGold;
+use crate::controller::replication::RemoteControlled;
+use crate::enemy::slime::spawn_slime;
+use crate::entity::actor::Actor;
+use crate::entity::bomb::explode_bomb;
+use crate::entity::bullet::bullet_collision;
+use crate::entity::life::{DamageEvent, Life, LifeBeingSprite};
+use crate::hud::life_bar::LifeBarResource;
+use crate::level::GameLevel;
+use crate::set::GameSet;
+use crate::states::GameState;
+use bevy::prelude::*;
+use bevy_light_2d::light::PointLight2d;
+use bevy_rapier2d::plugin::PhysicsSet;
+
+/// レベル1でのエリートの出現率
+/// 深い階層ほど出現率が上がります
+const BASE_ELITE_CHANCE: f32 = 0.05;
+
+const ELITE_CHANCE_PER_LEVEL: f32 = 0.05;
+
+/// エリートが複数の特性を持つ確率
+const EXTRA_AFFIX_CHANCE: f32 = 0.25;
+
+/// シールドが最後にダメージを受けてから回復し始めるまでのフレーム数
+const SHIELD_REGENERATION_DELAY: u32 = 180;
+
+/// 分裂したときに生まれるスライムの数
+const SPLIT_COUNT: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EliteAffix {
+    /// ライフが大幅に増えます
+    Tough,
+
+    /// ダメージを吸収するシールドを持ち、しばらく攻撃を受けないと回復します
+    Shielded,
+
+    /// 倒されると複数のスライムに分裂します
+    Splitting,
+
+    /// 移動が速くなります
+    Fast,
+
+    /// 攻撃を当てるとライフを回復します
+    Vampiric,
+}
+
+const AFFIXES: [EliteAffix; 5] = [
+    EliteAffix::Tough,
+    EliteAffix::Shielded,
+    EliteAffix::Splitting,
+    EliteAffix::Fast,
+    EliteAffix::Vampiric,
+];
+
+impl EliteAffix {
+    /// 特性ごとのスプライトの色と光の色
+    pub fn color(&self) -> Color {
+        match self {
+            EliteAffix::Tough => Color::srgb(1.0, 0.6, 0.4),
+            EliteAffix::Shielded => Color::srgb(0.5, 0.8, 1.0),
+            EliteAffix::Splitting => Color::srgb(0.6, 1.0, 0.5),
+            EliteAffix::Fast => Color::srgb(1.0, 1.0, 0.4),
+            EliteAffix::Vampiric => Color::srgb(1.0, 0.4, 0.6),
+        }
+    }
+}
+
+/// 特性を持つ強化された敵を表します
+/// スポーン時に敵のエンティティにこのコンポーネントを追加すると、
+/// 特性に応じてライフや移動速度、落とすゴールドが変化します
+#[derive(Component, Debug)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+    shield: i32,
+    max_shield: i32,
+    shield_wait: u32,
+}
+
+impl Elite {
+    pub fn new(affixes: Vec<EliteAffix>) -> Self {
+        Self {
+            affixes,
+            shield: 0,
+            max_shield: 0,
+            shield_wait: 0,
+        }
+    }
+
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// シールドでダメージを吸収し、吸収しきれなかったダメージを返します
+    /// ライフを減らす前に呼び出す必要があります
+    pub fn absorb(&mut self, damage: i32) -> i32 {
+        if !self.has(EliteAffix::Shielded) {
+            return damage;
+        }
+        let absorbed = self.shield.min(damage).max(0);
+        self.shield -= absorbed;
+        self.shield_wait = SHIELD_REGENERATION_DELAY;
+        damage - absorbed
+    }
+}
+
+/// レベルの深さに応じた確率で、エリートの特性を選びます
+/// エリートにならない場合は空のリストを返します
+pub fn random_affixes(level: GameLevel) -> Vec<EliteAffix> {
+    let depth = match level {
+        GameLevel::Level(level) => level.max(0) as f32,
+        GameLevel::MultiPlayArena => 1.0,
+    };
+    let chance = BASE_ELITE_CHANCE + ELITE_CHANCE_PER_LEVEL * (depth - 1.0).max(0.0);
+    if chance <= rand::random::<f32>() {
+        return Vec::new();
+    }
+
+    let mut candidates = AFFIXES.to_vec();
+    let mut affixes = Vec::new();
+    affixes.push(candidates.remove(rand::random::<usize>() % candidates.len()));
+    if rand::random::<f32>() < EXTRA_AFFIX_CHANCE {
+        affixes.push(candidates.remove(rand::random::<usize>() % candidates.len()));
+    }
+    affixes
+}
+
+/// エリートが追加されたときに、特性に応じて能力値を変更します
+fn apply_elite_affixes(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut Elite,
+            &mut Actor,
+            &mut Life,
+            &mut DespawnWithGold,
+        ),
+        Added<Elite>,
+    >,
+) {
+    for (entity, mut elite, mut actor, mut life, mut despawn) in query.iter_mut() {
+        if elite.has(EliteAffix::Tough) {
+            life.max_life *= 3;
+            life.life = life.max_life;
+        }
+        if elite.has(EliteAffix::Fast) {
+            actor.move_force *= 1.6;
+        }
+        if elite.has(EliteAffix::Shielded) {
+            elite.max_shield = life.max_life;
+            elite.shield = elite.max_shield;
+        }
+
+        // 特性が多いほど多くのゴールドを落とします
+        despawn.gold *= 2 + elite.affixes.len() as u32;
+
+        if let Some(affix) = elite.affixes.first() {
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((
+                    Transform::default(),
+                    PointLight2d {
+                        radius: 48.0,
+                        intensity: 1.0,
+                        falloff: 10.0,
+                        color: affix.color(),
+                        ..default()
+                    },
+                ));
+            });
+        }
+    }
+}
+
+/// エリートのスプライトを特性の色で着色します
+fn tint_elite_sprite(
+    query: Query<(&Elite, &Children)>,
+    mut sprite_query: Query<&mut Sprite, With<LifeBeingSprite>>,
+) {
+    for (elite, children) in query.iter() {
+        if let Some(affix) = elite.affixes.first() {
+            let color = affix.color();
+            for child in children.iter() {
+                if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                    if sprite.color != color {
+                        sprite.color = color;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 吸血の特性を持つエリートの攻撃が当たったらライフを回復します
+/// シールドによる吸収は、ライフを減らす側で Elite::absorb によって行います
+/// 弾丸の衝突より後、ライフがゼロになったかどうかを判定するより前に実行する必要があります
+fn process_elite_damage(
+    mut reader: EventReader<DamageEvent>,
+    mut query: Query<(Entity, &Elite, &Actor, &mut Life), Without<RemoteControlled>>,
+) {
+    for event in reader.read() {
+        if let Some(attacker) = event.attacker {
+            for (entity, elite, actor, mut life) in query.iter_mut() {
+                if actor.uuid == attacker
+                    && entity != event.target
+                    && elite.has(EliteAffix::Vampiric)
+                    && 0 < life.life
+                {
+                    life.life = (life.life + (event.damage / 2).max(1)).min(life.max_life);
+                }
+            }
+        }
+    }
+}
+
+/// しばらくダメージを受けていないシールドを少しずつ回復します
+fn regenerate_shield(mut query: Query<&mut Elite>) {
+    for mut elite in query.iter_mut() {
+        if 0 < elite.shield_wait {
+            elite.shield_wait -= 1;
+        } else if elite.shield < elite.max_shield {
+            elite.shield += 1;
+        }
+    }
+}
+
+/// 分裂の特性を持つエリートが倒されたら、その場にスライムを生成します
+/// 消滅の処理は DespawnWithGold が行います
+fn split_elite(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    life_bar_res: Res<LifeBarResource>,
+    query: Query<(&Elite, &Actor, &Life, &Transform), Without<RemoteControlled>>,
+) {
+    for (elite, actor, life, transform) in query.iter() {
+        if life.life <= 0 && elite.has(EliteAffix::Splitting) {
+            let position = transform.translation.truncate();
+            for i in 0..SPLIT_COUNT {
+                let angle = std::f32::consts::TAU * i as f32 / SPLIT_COUNT as f32;
+                spawn_slime(
+                    &mut commands,
+                    &assets,
+                    position + Vec2::from_angle(angle) * TILE_HALF,
+                    &life_bar_res,
+                    30,
+                    0,
+                    actor.actor_group,
+                    None,
+                );
+            }
+        }
+    }
+}
+
+pub struct ElitePlugin;
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                apply_elite_affixes,
+                process_elite_damage,
+                regenerate_shield,
+                split_elite,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame))
+                .after(bullet_collision)
+                .after(explode_bomb)
+                .before(GameSet)
+                .before(PhysicsSet::SyncBackend),
+        );
+        app.add_systems(
+            Update,
+            tint_elite_sprite.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
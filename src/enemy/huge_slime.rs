@@ -1,18 +1,19 @@
 use crate::asset::GameAssets;
-use crate::audio::NextBGM;
 use crate::constant::*;
-use crate::controller::player::Player;
+use crate::enemy::ai::{Behavior, EnemyAi};
+use crate::enemy::boss::{Boss, BossPhaseChanged};
 use crate::entity::actor::{Actor, ActorFireState, ActorGroup, ActorState};
 use crate::entity::bullet::HomingTarget;
 use crate::entity::impact::SpawnImpact;
 use crate::entity::life::Life;
 use crate::entity::slime_seed::SpawnSlimeSeed;
 use crate::entity::EntityDepth;
-use crate::inventory::Inventory;
+use crate::inventory::{Inventory, InventoryItem};
+use crate::inventory_item::InventoryItemType;
 use crate::se::{SEEvent, SE};
 use crate::spell::SpellType;
 use crate::states::GameState;
-use crate::wand::{Wand, WandSpell, WandType};
+use crate::wand::{Wand, WandItem, WandSpell, WandType};
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::*;
 use bevy_rapier2d::prelude::*;
@@ -22,20 +23,21 @@ const HUGE_SLIME_COLLIDER_RADIUS: f32 = 24.0;
 
 const IMPACT_MARGIN: f32 = 16.0;
 
-#[derive(Component)]
-pub struct Boss;
+const HUGE_SLIME_VISION_RANGE: f32 = TILE_SIZE * 30.0;
+
+const HUGE_SLIME_HEARING_RANGE: f32 = TILE_SIZE * 30.0;
+
+const HUGE_SLIME_ARENA_RADIUS: f32 = TILE_SIZE * 14.0;
 
 #[derive(Component)]
 pub struct HugeSlime {
     up_velocity: f32,
     state: HugeSlimeState,
     animation: u32,
-    promoted: bool,
 }
 
 #[derive(Clone)]
 pub enum HugeSlimeState {
-    Growl,
     Approach,
     Summon,
     Promote,
@@ -55,19 +57,32 @@ pub fn spawn_huge_slime(commands: &mut Commands, assets: &Res<GameAssets>, posit
         .spawn((
             Name::new("スライムの王 エミルス"),
             StateScoped(GameState::InGame),
-            Boss,
-            DespawnHugeSlime,
+            // ライフが半分を切ると本気を出します
+            Boss::new(position, HUGE_SLIME_ARENA_RADIUS)
+                .with_phases(vec![0.5])
+                .with_victory_bgm(assets.dokutsu.clone())
+                .with_loot(vec![InventoryItem {
+                    item_type: InventoryItemType::Spell(SpellType::SummonFriendSlime),
+                    price: 0,
+                    count: 1,
+                }])
+                .with_ending(),
             Life {
                 life: 1200,
                 max_life: 1200,
                 amplitude: 0.0,
             },
             HomingTarget,
+            // 遠くからでも標的を見つけ出し、まっすぐ向かってきます
+            EnemyAi::new(
+                HUGE_SLIME_VISION_RANGE,
+                HUGE_SLIME_HEARING_RANGE,
+                vec![Behavior::Chase { range: 0.0 }],
+            ),
             HugeSlime {
                 up_velocity: 0.0,
-                state: HugeSlimeState::Growl,
+                state: HugeSlimeState::Approach,
                 animation: 0,
-                promoted: false,
             },
             Actor {
                 uuid: Uuid::new_v4(),
@@ -87,7 +102,7 @@ pub fn spawn_huge_slime(commands: &mut Commands, assets: &Res<GameAssets>, posit
                 equipments: [None; MAX_ITEMS_IN_EQUIPMENT],
                 wands: [
                     Some(Wand {
-                        wand_type: WandType::CypressWand,
+                        item: WandItem::new(WandType::CypressWand),
                         price: 0,
                         slots,
                         index: 0,
@@ -137,12 +152,8 @@ pub fn spawn_huge_slime(commands: &mut Commands, assets: &Res<GameAssets>, posit
 }
 
 fn update_huge_slime(
-    player_query: Query<&Transform, With<Player>>,
-    mut slime_query: Query<(&mut HugeSlime, &Transform, &mut Actor), Without<Player>>,
-    mut sprite_query: Query<
-        (&Parent, &mut Transform),
-        (With<HugeSlimeSprite>, Without<HugeSlime>, Without<Player>),
-    >,
+    mut slime_query: Query<(&mut HugeSlime, &Transform, &mut Actor)>,
+    mut sprite_query: Query<(&Parent, &mut Transform), (With<HugeSlimeSprite>, Without<HugeSlime>)>,
     mut impact_writer: EventWriter<SpawnImpact>,
 ) {
     const GRAVITY: f32 = 0.2;
@@ -151,24 +162,17 @@ fn update_huge_slime(
         huge_slime.up_velocity -= GRAVITY;
         let next = (offset.translation.y + huge_slime.up_velocity as f32).max(0.0);
 
-        actor.move_force = 0.0;
-
-        // プレイヤーがいる場合はジャンプしながら接近
-        // 空中にいる場合は移動の外力が働く
-
-        if let Ok(player_transform) = player_query.get_single() {
-            if 0.0 < offset.translation.y {
-                let direction = (player_transform.translation.truncate()
-                    - transform.translation.truncate())
-                .normalize_or_zero();
+        // 移動方向は EnemyAi が標的に向けて設定します
+        // 空中にいる場合だけ移動の外力が働き、ジャンプしながら接近します
 
-                // スライムを移動するのに、ExternalForceを直接操作しないこと
-                // 直接操作すると、実行順序の関係で移動したりしなかったりという不安定なバグになります
-                // ExternalForce は Actor の apply_external_force を通じて設定します
-                actor.move_direction = direction;
-                actor.move_force = 4000000.0;
-            };
-        }
+        // スライムを移動するのに、ExternalForceを直接操作しないこと
+        // 直接操作すると、実行順序の関係で移動したりしなかったりという不安定なバグになります
+        // ExternalForce は Actor の apply_external_force を通じて設定します
+        actor.move_force = if 0.0 < offset.translation.y {
+            4000000.0
+        } else {
+            0.0
+        };
 
         // 着地判定
         if 0.0 < offset.translation.y && next == 0.0 {
@@ -186,144 +190,82 @@ fn update_huge_slime(
     }
 }
 
-fn update_huge_slime_growl(
-    mut huge_slime_query: Query<(&mut HugeSlime, &Transform), Without<Player>>,
-    mut sprite_query: Query<&Parent, (With<HugeSlimeSprite>, Without<HugeSlime>, Without<Player>)>,
+/// 接近、召喚の各行動を切り替えます
+/// 登場演出の間は行動しません
+fn update_huge_slime_state(
+    mut huge_slime_query: Query<(Entity, &mut HugeSlime, &Boss, &EnemyAi, &Transform)>,
     mut se_writer: EventWriter<SEEvent>,
-) {
-    for parent in sprite_query.iter_mut() {
-        let (mut huge_slime, transform) = huge_slime_query.get_mut(parent.get()).unwrap();
-        if let HugeSlimeState::Growl = huge_slime.state {
-            if huge_slime.animation == 120 {
-                se_writer.send(SEEvent::pos(SE::Growl, transform.translation.truncate()));
-            } else if 300 <= huge_slime.animation {
-                huge_slime.state = HugeSlimeState::Approach;
-                huge_slime.animation = 0;
-            }
-        }
-    }
-}
-
-fn update_huge_slime_approach(
-    player_query: Query<(&mut Life, &Transform, &mut ExternalImpulse), With<Player>>,
-    mut huge_slime_query: Query<&mut HugeSlime, Without<Player>>,
-    mut huge_slime_sprite_query: Query<
-        &Parent,
-        (With<HugeSlimeSprite>, Without<HugeSlime>, Without<Player>),
-    >,
+    mut seed_writer: EventWriter<SpawnSlimeSeed>,
 ) {
     const JUMP_POWER: f32 = 3.0;
 
-    for parent in huge_slime_sprite_query.iter_mut() {
-        let mut huge_slime = huge_slime_query.get_mut(parent.get()).unwrap();
-        let timespan = if huge_slime.promoted { 35 } else { 60 };
-        if let HugeSlimeState::Approach = huge_slime.state.clone() {
-            // プレイヤーがいる場合はジャンプしながら接近
-            if let Ok(_) = player_query.get_single() {
-                // 60フレームに一度ジャンプ
-                if huge_slime.animation % timespan == 0 {
-                    huge_slime.up_velocity = JUMP_POWER;
-                    info!("jump");
-                } else {
-                    warn!("Jumping");
+    for (huge_slime_entity, mut huge_slime, boss, ai, transform) in huge_slime_query.iter_mut() {
+        if !boss.is_fighting() {
+            continue;
+        }
+
+        let promoted = 0 < boss.phase();
+        let position = transform.translation.truncate();
+        match huge_slime.state.clone() {
+            HugeSlimeState::Promote => {
+                if huge_slime.animation == 120 {
+                    se_writer.send(SEEvent::pos(SE::Growl, position));
+                } else if 300 <= huge_slime.animation {
+                    huge_slime.state = HugeSlimeState::Approach;
+                    huge_slime.animation = 0;
                 }
-            } else {
-                warn!("Player not found");
             }
+            HugeSlimeState::Approach => {
+                // 標的がいる場合は一定の間隔でジャンプ
+                let timespan = if promoted { 35 } else { 60 };
+                if ai.target.is_some() && huge_slime.animation % timespan == 0 {
+                    huge_slime.up_velocity = JUMP_POWER;
+                }
 
-            // 6秒ごとに召喚フェイズに移行
-            if huge_slime.animation == 360 {
-                huge_slime.state = HugeSlimeState::Summon;
-                huge_slime.animation = 0;
+                // 6秒ごとに召喚フェイズに移行
+                if 360 <= huge_slime.animation {
+                    huge_slime.state = HugeSlimeState::Summon;
+                    huge_slime.animation = 0;
+                }
             }
-        }
-    }
-}
-
-fn update_huge_slime_summon(
-    player_query: Query<&Transform, With<Player>>,
-    mut huge_slime_query: Query<(Entity, &mut HugeSlime, &Transform), Without<Player>>,
-    mut sprite_query: Query<&Parent, (With<HugeSlimeSprite>, Without<HugeSlime>, Without<Player>)>,
-    mut se_writer: EventWriter<SEEvent>,
-    mut seed_writer: EventWriter<SpawnSlimeSeed>,
-) {
-    for parent in sprite_query.iter_mut() {
-        let (huge_slime_entity, mut huge_slime, transform) =
-            huge_slime_query.get_mut(**parent).unwrap();
-
-        if let HugeSlimeState::Summon = huge_slime.state {
-            if let Ok(player) = player_query.get_single() {
-                if huge_slime.animation == 60 {
-                    let slimes = if huge_slime.promoted { 8 } else { 4 };
-                    let circles = if huge_slime.promoted { 4 } else { 1 };
-                    for n in 0..circles {
-                        for i in 0..slimes {
-                            let t = std::f32::consts::PI * 2.0 / slimes as f32; // 等間隔に配置した場合の角度
-                            let a = rand::random::<f32>() * 3.0; // 起点は適当にばらけさせる
-                            let angle = a + t * i as f32 + t * 0.5 * rand::random::<f32>(); // 少しランダムにずらす
-                            let offset = Vec2::from_angle(angle) * 100.0 * (1.0 + n as f32); // 100ピクセルの演習場にばらまく
-                            let to = player.translation.truncate() + offset;
-                            seed_writer.send(SpawnSlimeSeed {
-                                from: transform.translation.truncate(),
-                                to,
-                                actor_group: ActorGroup::Enemy,
-                                owner: huge_slime_entity,
-                            });
+            HugeSlimeState::Summon => {
+                if let Some(target) = ai.target {
+                    if huge_slime.animation == 60 {
+                        let slimes = if promoted { 8 } else { 4 };
+                        let circles = if promoted { 4 } else { 1 };
+                        for n in 0..circles {
+                            for i in 0..slimes {
+                                let t = std::f32::consts::PI * 2.0 / slimes as f32; // 等間隔に配置した場合の角度
+                                let a = rand::random::<f32>() * 3.0; // 起点は適当にばらけさせる
+                                let angle = a + t * i as f32 + t * 0.5 * rand::random::<f32>(); // 少しランダムにずらす
+                                let offset = Vec2::from_angle(angle) * 100.0 * (1.0 + n as f32); // 100ピクセルの演習場にばらまく
+                                seed_writer.send(SpawnSlimeSeed {
+                                    from: position,
+                                    to: target + offset,
+                                    actor_group: ActorGroup::Enemy,
+                                    owner: huge_slime_entity,
+                                });
+                            }
                         }
+                        se_writer.send(SEEvent::pos(SE::Puyon, position));
                     }
-                    se_writer.send(SEEvent::pos(SE::Puyon, transform.translation.truncate()));
                 }
-            }
-
-            if 120 <= huge_slime.animation {
-                huge_slime.animation = 0;
-                huge_slime.state = HugeSlimeState::Approach;
-            }
-        }
-    }
-}
 
-fn update_huge_slime_promote(
-    mut huge_slime_query: Query<(&mut HugeSlime, &Transform), Without<Player>>,
-    mut sprite_query: Query<&Parent, (With<HugeSlimeSprite>, Without<HugeSlime>, Without<Player>)>,
-    mut se_writer: EventWriter<SEEvent>,
-) {
-    for parent in sprite_query.iter_mut() {
-        let (mut huge_slime, transform) = huge_slime_query.get_mut(parent.get()).unwrap();
-        if let HugeSlimeState::Promote = huge_slime.state {
-            if huge_slime.animation == 120 {
-                se_writer.send(SEEvent::pos(SE::Growl, transform.translation.truncate()));
-            } else if 300 <= huge_slime.animation {
-                huge_slime.state = HugeSlimeState::Approach;
-                huge_slime.animation = 0;
+                if 120 <= huge_slime.animation {
+                    huge_slime.animation = 0;
+                    huge_slime.state = HugeSlimeState::Approach;
+                }
             }
         }
     }
 }
 
-fn promote(mut huge_slime_query: Query<(&mut HugeSlime, &Life), Without<Player>>) {
-    for (mut huge_slime, life) in huge_slime_query.iter_mut() {
-        if !huge_slime.promoted && life.life < 600 {
+/// フェイズが進んだら咆哮して本気を出します
+fn promote(mut reader: EventReader<BossPhaseChanged>, mut huge_slime_query: Query<&mut HugeSlime>) {
+    for event in reader.read() {
+        if let Ok(mut huge_slime) = huge_slime_query.get_mut(event.boss) {
             huge_slime.state = HugeSlimeState::Promote;
             huge_slime.animation = 0;
-            huge_slime.promoted = true;
-        }
-    }
-}
-
-#[derive(Component)]
-pub struct DespawnHugeSlime;
-
-fn despown(
-    mut commands: Commands,
-    query: Query<(Entity, &Life), With<DespawnHugeSlime>>,
-    mut bgm: ResMut<NextBGM>,
-    assets: Res<GameAssets>,
-) {
-    for (entity, life) in query.iter() {
-        if life.life <= 0 {
-            commands.entity(entity).despawn_recursive();
-            bgm.0 = Some(assets.dokutsu.clone());
         }
     }
 }
@@ -334,15 +276,7 @@ impl Plugin for HugeSlimePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             FixedUpdate,
-            (
-                update_huge_slime,
-                update_huge_slime_growl,
-                update_huge_slime_approach,
-                update_huge_slime_summon,
-                update_huge_slime_promote,
-                promote,
-                despown,
-            )
+            (update_huge_slime, promote, update_huge_slime_state)
                 .run_if(in_state(GameState::InGame))
                 .before(PhysicsSet::SyncBackend),
         );
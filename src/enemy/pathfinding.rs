@@ -0,0 +1,241 @@
+use crate::constant::*;
+use crate::enemy::ai::EnemyAi;
+use crate::entity::book_shelf::Bookshelf;
+use crate::entity::chest::Chest;
+use crate::level::map::LevelChunk;
+use crate::states::GameState;
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+use bevy_rapier2d::plugin::PhysicsSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// 経路探索で調べるタイルの最大数
+/// 到達できない目的地を探索し続けないように打ち切ります
+const MAX_SEARCH_NODES: usize = 2048;
+
+/// チェストなどの障害物の位置を調べ直す間隔
+const BLOCKER_UPDATE_INTERVAL: u32 = 30;
+
+/// チェストと本棚の衝突形状の半分の大きさ
+const BLOCKER_HALF_EXTENTS: Vec2 = Vec2::new(16.0, 8.0);
+
+/// 直進と斜め移動のコスト
+const STRAIGHT_COST: u32 = 10;
+
+const DIAGONAL_COST: u32 = 14;
+
+/// 敵の経路探索に使う、レベルの通行可能なタイルの一覧です
+/// レベルが切り替わったときに LevelChunk から作り直し、
+/// チェストや本棚のある位置は一定間隔で通行不可として更新します
+#[derive(Resource, Default)]
+pub struct NavigationGrid {
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+    walkable: Vec<bool>,
+    blocked: HashSet<(i32, i32)>,
+}
+
+/// 経路のデバッグ表示を切り替えます
+#[derive(Resource, Default)]
+pub struct NavigationDebug(pub bool);
+
+/// ワールド座標からタイルの座標に変換します
+pub fn to_tile(position: Vec2) -> (i32, i32) {
+    (
+        (position.x / TILE_SIZE).floor() as i32,
+        (-position.y / TILE_SIZE).floor() as i32,
+    )
+}
+
+/// タイルの中心のワールド座標を返します
+pub fn tile_center(tile: (i32, i32)) -> Vec2 {
+    Vec2::new(
+        TILE_SIZE * tile.0 as f32 + TILE_HALF,
+        -TILE_SIZE * tile.1 as f32 - TILE_HALF,
+    )
+}
+
+impl NavigationGrid {
+    pub fn from_chunk(chunk: &LevelChunk) -> Self {
+        let mut walkable = Vec::new();
+        for y in chunk.min_y..chunk.max_y {
+            for x in chunk.min_x..chunk.max_x {
+                walkable.push(chunk.is_empty(x, y));
+            }
+        }
+        Self {
+            min_x: chunk.min_x,
+            min_y: chunk.min_y,
+            max_x: chunk.max_x,
+            max_y: chunk.max_y,
+            walkable,
+            blocked: HashSet::new(),
+        }
+    }
+
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        self.is_floor(x, y) && !self.blocked.contains(&(x, y))
+    }
+
+    /// 宝箱などの障害物を無視して、床のタイルかどうかを返します
+    fn is_floor(&self, x: i32, y: i32) -> bool {
+        if x < self.min_x || x >= self.max_x || y < self.min_y || y >= self.max_y {
+            return false;
+        }
+        let w = self.max_x - self.min_x;
+        let i = ((y - self.min_y) * w + (x - self.min_x)) as usize;
+        self.walkable[i]
+    }
+
+    /// A* で from から to までの経路を探索し、通過するタイルの中心の一覧を返します
+    /// 出発地点のタイルは含みません
+    /// 目的地に到達できない場合は None を返します
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let start = to_tile(from);
+        let goal = to_tile(to);
+        if start == goal {
+            return Some(Vec::new());
+        }
+
+        // 目的地のタイルは障害物があっても通行できるものとして扱いますが、壁の中には到達できません
+        if !self.is_floor(goal.0, goal.1) {
+            return None;
+        }
+        let passable = |tile: (i32, i32)| tile == goal || self.is_walkable(tile.0, tile.1);
+
+        let heuristic = |tile: (i32, i32)| {
+            let dx = (tile.0 - goal.0).unsigned_abs();
+            let dy = (tile.1 - goal.1).unsigned_abs();
+            STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+        };
+
+        let mut open = BinaryHeap::new();
+        let mut costs: HashMap<(i32, i32), u32> = HashMap::new();
+        let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+        open.push(Reverse((heuristic(start), start)));
+        costs.insert(start, 0);
+
+        let mut searched = 0;
+        while let Some(Reverse((_, current))) = open.pop() {
+            if current == goal {
+                let mut path = vec![tile_center(goal)];
+                let mut tile = goal;
+                while let Some(previous) = came_from.get(&tile) {
+                    if *previous == start {
+                        break;
+                    }
+                    path.push(tile_center(*previous));
+                    tile = *previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            searched += 1;
+            if MAX_SEARCH_NODES < searched {
+                return None;
+            }
+
+            let cost = costs[&current];
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    let next = (current.0 + dx, current.1 + dy);
+                    if !passable(next) {
+                        continue;
+                    }
+                    // 壁の角をすり抜けないよう、斜め移動は両隣が通行できる場合だけ許可します
+                    let diagonal = dx != 0 && dy != 0;
+                    if diagonal
+                        && !(passable((current.0 + dx, current.1))
+                            && passable((current.0, current.1 + dy)))
+                    {
+                        continue;
+                    }
+                    let step = if diagonal {
+                        DIAGONAL_COST
+                    } else {
+                        STRAIGHT_COST
+                    };
+                    let next_cost = cost + step;
+                    if costs.get(&next).map_or(true, |c| next_cost < *c) {
+                        costs.insert(next, next_cost);
+                        came_from.insert(next, current);
+                        open.push(Reverse((next_cost + heuristic(next), next)));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn update_navigation_grid(
+    chunk: Option<Res<LevelChunk>>,
+    mut grid: ResMut<NavigationGrid>,
+    blocker_query: Query<&Transform, Or<(With<Chest>, With<Bookshelf>)>>,
+    frame_count: Res<FrameCount>,
+) {
+    if let Some(chunk) = chunk {
+        if chunk.is_changed() {
+            *grid = NavigationGrid::from_chunk(&chunk);
+        }
+    }
+
+    if frame_count.0 % BLOCKER_UPDATE_INTERVAL == 0 {
+        grid.blocked.clear();
+        for transform in blocker_query.iter() {
+            let position = transform.translation.truncate();
+            let (min_x, max_y) = to_tile(position - BLOCKER_HALF_EXTENTS + 1.0);
+            let (max_x, min_y) = to_tile(position + BLOCKER_HALF_EXTENTS - 1.0);
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    grid.blocked.insert((x, y));
+                }
+            }
+        }
+    }
+}
+
+/// 各敵が追いかけている経路を線で表示します
+fn draw_navigation_paths(
+    mut gizmos: Gizmos,
+    debug: Res<NavigationDebug>,
+    query: Query<(&Transform, &EnemyAi)>,
+) {
+    if !debug.0 {
+        return;
+    }
+    for (transform, ai) in query.iter() {
+        let mut from = transform.translation.truncate();
+        for point in ai.path().iter() {
+            gizmos.line_2d(from, *point, Color::srgb(0.2, 1.0, 0.4));
+            from = *point;
+        }
+    }
+}
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavigationGrid>();
+        app.init_resource::<NavigationDebug>();
+        app.add_systems(
+            FixedUpdate,
+            update_navigation_grid
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+        app.add_systems(
+            Update,
+            draw_navigation_paths.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
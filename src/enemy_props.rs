@@ -0,0 +1,96 @@
+use crate::asset::GameAssets;
+use crate::constant::TILE_SIZE;
+use crate::enemy::ai::Behavior;
+use crate::entity::actor::ActorGroup;
+use crate::spell::SpellType;
+use bevy::prelude::*;
+use bevy_aseprite_ultra::prelude::Aseprite;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EnemyType {
+    Slime,
+    Eyeball,
+}
+
+/// 敵の種類ごとの基礎情報
+pub struct EnemyProps {
+    pub name: &'static str,
+
+    /// 所属するグループに応じたスプライトを返します
+    pub aseprite: fn(&GameAssets, ActorGroup) -> Handle<Aseprite>,
+
+    pub life: i32,
+
+    /// 杖に入れておく呪文
+    pub spell: SpellType,
+
+    pub move_force: f32,
+
+    /// 倒したときに落とすゴールド
+    pub gold: u32,
+
+    pub vision: f32,
+
+    pub hearing: f32,
+
+    pub behaviors: &'static [Behavior],
+
+    /// スポーンテーブルで消費する難易度の予算
+    pub cost: u32,
+}
+
+const SLIME: EnemyProps = EnemyProps {
+    name: "slime",
+    aseprite: |assets, group| match group {
+        ActorGroup::Player => assets.friend_slime.clone(),
+        ActorGroup::Enemy => assets.slime.clone(),
+    },
+    life: 15,
+    spell: SpellType::SlimeCharge,
+    move_force: 100000.0,
+    gold: 2,
+    // スライムは目が悪いぶん、物音によく反応します
+    vision: TILE_SIZE * 7.0,
+    hearing: TILE_SIZE * 8.0,
+    // 1マス以内まで接近し、体当たりします
+    behaviors: &[
+        Behavior::Cast {
+            range: TILE_SIZE * 1.0,
+        },
+        Behavior::Chase {
+            range: TILE_SIZE * 1.0,
+        },
+    ],
+    cost: 1,
+};
+
+const EYEBALL: EnemyProps = EnemyProps {
+    name: "eyeball",
+    aseprite: |assets, _| assets.eyeball.clone(),
+    life: 15,
+    spell: SpellType::PurpleBolt,
+    move_force: 100000.0,
+    gold: 3,
+    // アイボールは遠くまで見通せますが、耳は良くありません
+    vision: TILE_SIZE * 12.0,
+    hearing: TILE_SIZE * 5.0,
+    // 8マス以内まで接近し、遠くから魔法を撃ちます
+    behaviors: &[
+        Behavior::Cast {
+            range: TILE_SIZE * 8.0,
+        },
+        Behavior::Chase {
+            range: TILE_SIZE * 8.0,
+        },
+    ],
+    cost: 2,
+};
+
+impl EnemyType {
+    pub fn to_props(&self) -> EnemyProps {
+        match self {
+            EnemyType::Slime => SLIME,
+            EnemyType::Eyeball => EYEBALL,
+        }
+    }
+}
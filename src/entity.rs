@@ -1,4 +1,5 @@
 pub mod actor;
+pub mod bomb;
 pub mod book_shelf;
 pub mod broken_magic_circle;
 pub mod bullet;
@@ -13,8 +14,10 @@ pub mod magic_circle;
 pub mod rabbit;
 pub mod shop;
 pub mod slime_seed;
+pub mod stash;
 pub mod stone_lantern;
 pub mod witch;
+pub mod workbench;
 
 use crate::{
     constant::{ENTITY_LAYER_Z, Z_ORDER_SCALE},
@@ -39,6 +42,7 @@ pub enum GameEntity {
     Spell,
     Crate,
     HugeSlime,
+    Buer,
     Rabbit,
     Witch,
     ShopDoor,
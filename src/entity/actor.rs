@@ -1,10 +1,11 @@
-use crate::cast::cast_spell;
+use crate::cast::cast_wand;
 use crate::constant::{MAX_ITEMS_IN_EQUIPMENT, MAX_WANDS};
-use crate::controller::player::Equipment;
+use crate::controller::network_simulator::OutgoingMessage;
+use crate::controller::player::{Equipment, Player};
+use crate::controller::remote::RemotePlayer;
 use crate::entity::life::Life;
 use crate::entity::life::LifeBeingSprite;
 use crate::entity::slime_seed::SpawnSlimeSeed;
-use crate::equipment::EquipmentType;
 use crate::inventory::Inventory;
 use crate::ui::floating::FloatingContent;
 use crate::wand::{Wand, WandSpell};
@@ -13,7 +14,7 @@ use bevy::prelude::*;
 use bevy_light_2d::light::PointLight2d;
 use bevy_rapier2d::plugin::PhysicsSet;
 use bevy_rapier2d::prelude::{ExternalForce, ExternalImpulse};
-use bevy_simple_websocket::{ClientMessage, ReadyState, WebSocketState};
+use bevy_simple_websocket::{ReadyState, WebSocketState};
 use std::f32::consts::PI;
 use uuid::Uuid;
 
@@ -35,8 +36,33 @@ pub enum ActorState {
     Run,
 }
 
+/// 装備を含めたアクターの能力値です
+/// update_actor_stats によって毎フレーム Actor の装備から集計されます
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+pub struct ActorStats {
+    /// 装備を含めた移動力の合計
+    /// ただし魔法発射中のペナルティは含まれません
+    pub move_force: f32,
+
+    pub light_intensity: f32,
+
+    /// カメラの拡大率の対数
+    pub scale_factor: f32,
+
+    /// 装備による最大ライフの増加量
+    /// Life の max_life にはこの値が加算済みです
+    pub max_life: i32,
+
+    pub cast_delay: i32,
+
+    pub bullet_speed: f32,
+
+    pub damage: i32,
+}
+
 /// ライフを持ち、弾丸のダメージの対象となるエンティティを表します
 #[derive(Component, Reflect)]
+#[require(ActorStats)]
 pub struct Actor {
     pub uuid: Uuid,
 
@@ -93,11 +119,12 @@ impl Actor {
                 self.inventory.get(index).map(|i| i.item_type.get_icon())
             }
             FloatingContent::Equipment(index) => {
-                self.equipments[index].map(|i| i.equipment_type.to_props().icon)
+                self.equipments[index].map(|i| i.item.equipment_type.to_props().icon)
             }
             FloatingContent::Wand(index) => self.wands[index]
                 .as_ref()
-                .map(|i| i.wand_type.to_props().icon),
+                .map(|i| i.item.wand_type.to_props().icon),
+            FloatingContent::Stash(..) => None,
             FloatingContent::WandSpell(w, s) => self.wands[w]
                 .as_ref()
                 .and_then(|wand| wand.slots[s].map(|spell| spell.spell_type.to_props().icon)),
@@ -152,15 +179,20 @@ impl Actor {
         }
 
         self.golds -= dept as i32;
+        self.clear_prices();
+        true
+    }
 
+    /// 所持しているすべての商品の価格を0にして、自分のものにします
+    pub fn clear_prices(&mut self) {
         for item in self.inventory.0.iter_mut() {
             if let Some(item) = item {
                 item.price = 0;
             }
         }
 
-        for e in self.equipments {
-            if let Some(mut equipment) = e {
+        for e in self.equipments.iter_mut() {
+            if let Some(ref mut equipment) = e {
                 equipment.price = 0;
             }
         }
@@ -169,48 +201,34 @@ impl Actor {
             if let Some(ref mut wand) = w {
                 wand.price = 0;
                 for s in wand.slots.iter_mut() {
-                    if let Some(mut spell) = s {
+                    if let Some(ref mut spell) = s {
                         spell.price = 0;
                     }
                 }
             }
         }
-
-        true
     }
 
-    /// 装備を含めた移動力の合計を返します
-    /// ただし魔法発射中のペナルティは含まれません
-    fn get_total_move_force(&self) -> f32 {
-        let mut force = self.move_force;
-        for equipment in self.equipments {
-            force += match equipment {
-                Some(Equipment {
-                    equipment_type: EquipmentType::SpikeBoots,
-                    ..
-                }) => 40000.0,
-                _ => 0.0,
-            }
+    /// 装備による変化を合計して、アクターの能力値を求めます
+    /// 能力値を参照する処理は、アクターに直接問い合わせず ActorStats を参照してください
+    pub fn aggregate_stats(&self) -> ActorStats {
+        let mut stats = ActorStats {
+            move_force: self.move_force,
+            scale_factor: -1.0,
+            ..default()
+        };
+        for equipment in self.equipments.iter().flatten() {
+            let modifiers = equipment.item.stats();
+            stats.move_force += modifiers.move_force;
+            stats.light_intensity += modifiers.light_intensity;
+            stats.scale_factor += modifiers.zoom;
+            stats.max_life += modifiers.max_life;
+            stats.cast_delay += modifiers.cast_delay;
+            stats.bullet_speed += modifiers.bullet_speed;
+            stats.damage += modifiers.damage;
         }
-        force
-    }
-
-    pub fn get_total_scale_factor(&self) -> f32 {
-        let mut scale_factor: f32 = -1.0;
-        for equipment in self.equipments {
-            scale_factor += match equipment {
-                Some(Equipment {
-                    equipment_type: EquipmentType::Telescope,
-                    ..
-                }) => 0.5,
-                Some(Equipment {
-                    equipment_type: EquipmentType::Magnifier,
-                    ..
-                }) => -0.5,
-                _ => 0.0,
-            }
-        }
-        scale_factor.max(-2.0).min(1.0)
+        stats.scale_factor = stats.scale_factor.max(-2.0).min(1.0);
+        stats
     }
 }
 
@@ -298,6 +316,7 @@ fn fire_bullet(
         (
             Entity,
             &mut Actor,
+            &ActorStats,
             &mut Life,
             &mut Transform,
             &mut ExternalImpulse,
@@ -306,26 +325,27 @@ fn fire_bullet(
     >,
     mut commands: Commands,
     assets: Res<GameAssets>,
-    mut writer: EventWriter<ClientMessage>,
+    mut writer: EventWriter<OutgoingMessage>,
     mut se_writer: EventWriter<SEEvent>,
     websocket: Res<WebSocketState>,
     mut slime_writer: EventWriter<SpawnSlimeSeed>,
 ) {
     let online = websocket.ready_state == ReadyState::OPEN;
 
-    for (actor_entity, mut actor, mut actor_life, actor_transform, mut actor_impulse) in
+    for (actor_entity, mut actor, stats, mut actor_life, actor_transform, mut actor_impulse) in
         actor_query.iter_mut()
     {
         if actor.fire_state == ActorFireState::Fire {
             let current_wand = actor.current_wand;
             while actor.spell_delay == 0 {
-                let delay = cast_spell(
+                let delay = cast_wand(
                     &mut commands,
                     &assets,
                     &mut writer,
                     &mut se_writer,
                     actor_entity,
                     &mut actor,
+                    stats,
                     &mut actor_life,
                     &actor_transform,
                     &mut actor_impulse,
@@ -333,19 +353,20 @@ fn fire_bullet(
                     &mut slime_writer,
                     current_wand,
                 );
-                actor.spell_delay += delay.max(1);
+                actor.spell_delay += apply_cast_delay(delay, stats);
             }
         }
 
         if actor.fire_state_secondary == ActorFireState::Fire {
             while actor.spell_delay_secondary == 0 {
-                let delay = cast_spell(
+                let delay = cast_wand(
                     &mut commands,
                     &assets,
                     &mut writer,
                     &mut se_writer,
                     actor_entity,
                     &mut actor,
+                    stats,
                     &mut actor_life,
                     &actor_transform,
                     &mut actor_impulse,
@@ -354,7 +375,7 @@ fn fire_bullet(
                     MAX_WANDS - 1,
                 );
 
-                actor.spell_delay_secondary += delay.max(1);
+                actor.spell_delay_secondary += apply_cast_delay(delay, stats);
             }
         }
 
@@ -363,12 +384,52 @@ fn fire_bullet(
     }
 }
 
+/// 詠唱遅延に装備による増減を加えます
+/// 何も詠唱しなかった場合の遅延には加えません
+fn apply_cast_delay(delay: i32, stats: &ActorStats) -> i32 {
+    if 0 < delay {
+        (delay + stats.cast_delay).max(1)
+    } else {
+        1
+    }
+}
+
+/// アクターの装備から能力値を集計します
+/// 最大ライフは前回の集計との差分だけ Life に反映します
+/// リモートプレイヤーのライフと光の強さは受信したデータに従います
+/// 装備による光の強さはプレイヤーだけに反映し、店主やエリートなどは生成時の光の強さのままにします
+fn update_actor_stats(
+    mut query: Query<(
+        &mut Actor,
+        &mut ActorStats,
+        &mut Life,
+        Has<RemotePlayer>,
+        Has<Player>,
+    )>,
+) {
+    for (mut actor, mut stats, mut life, remote, player) in query.iter_mut() {
+        let next = actor.aggregate_stats();
+        if !remote {
+            if next.max_life != stats.max_life {
+                life.max_life = (life.max_life + next.max_life - stats.max_life).max(1);
+                life.life = life.life.min(life.max_life);
+            }
+            if player && actor.intensity != next.light_intensity {
+                actor.intensity = next.light_intensity;
+            }
+        }
+        if *stats != next {
+            *stats = next;
+        }
+    }
+}
+
 /// actor.move_direction の値に従って、アクターに外力を適用します
 /// 魔法の発射中は移動速度が低下します
-fn apply_external_force(mut player_query: Query<(&Actor, &mut ExternalForce)>) {
-    for (actor, mut force) in player_query.iter_mut() {
+fn apply_external_force(mut player_query: Query<(&Actor, &ActorStats, &mut ExternalForce)>) {
+    for (actor, stats, mut force) in player_query.iter_mut() {
         force.force = actor.move_direction
-            * actor.get_total_move_force()
+            * stats.move_force
             * if actor.fire_state == ActorFireState::Fire
                 || actor.fire_state_secondary == ActorFireState::Fire
             {
@@ -379,6 +440,20 @@ fn apply_external_force(mut player_query: Query<(&Actor, &mut ExternalForce)>) {
     }
 }
 
+/// ダッシュした直後のアクターに付与され、残りのフレーム数が尽きると取り除かれます
+#[derive(Component)]
+pub struct Dashing(pub u32);
+
+fn update_dashing(mut commands: Commands, mut query: Query<(Entity, &mut Dashing)>) {
+    for (entity, mut dashing) in query.iter_mut() {
+        if 0 < dashing.0 {
+            dashing.0 -= 1;
+        } else {
+            commands.entity(entity).remove::<Dashing>();
+        }
+    }
+}
+
 fn update_actor_state(mut witch_query: Query<(&Actor, &mut ActorState)>) {
     for (actor, mut state) in witch_query.iter_mut() {
         if actor.move_direction.length() < 0.01 {
@@ -398,6 +473,7 @@ pub struct ActorPlugin;
 impl Plugin for ActorPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Actor>();
+        app.register_type::<ActorStats>();
         app.add_systems(
             Update,
             (update_sprite_flip, update_actor_light, update_actor_state)
@@ -405,7 +481,13 @@ impl Plugin for ActorPlugin {
         );
         app.add_systems(
             FixedUpdate,
-            (apply_external_force, fire_bullet)
+            (
+                update_actor_stats,
+                apply_external_force,
+                fire_bullet,
+                update_dashing,
+            )
+                .chain()
                 .run_if(in_state(GameState::InGame))
                 .before(PhysicsSet::SyncBackend),
         );
@@ -0,0 +1,147 @@
+use crate::asset::GameAssets;
+use crate::constant::*;
+use crate::controller::remote::RemotePlayer;
+use crate::enemy::elite::Elite;
+use crate::entity::actor::Actor;
+use crate::entity::bullet_particle::{spawn_particle_system, BulletParticleResource};
+use crate::entity::damege::spawn_damage_number;
+use crate::entity::life::{DamageEvent, Life};
+use crate::entity::EntityDepth;
+use crate::se::{SEEvent, SE};
+use crate::states::GameState;
+use bevy::prelude::*;
+use bevy_aseprite_ultra::prelude::*;
+use bevy_rapier2d::prelude::*;
+use uuid::Uuid;
+
+/// 投げてから爆発するまでのフレーム数
+const BOMB_FUSE: u32 = 90;
+
+/// 爆発に巻き込まれる範囲
+const BOMB_RADIUS: f32 = TILE_SIZE * 2.0;
+
+const BOMB_DAMAGE: i32 = 30;
+
+const BOMB_IMPULSE: f32 = 20000.0;
+
+/// 投げられた爆弾です
+/// 一定時間後に爆発し、範囲内のライフを持つものすべてにダメージを与えます
+/// 投げた本人も巻き込まれます
+#[derive(Component)]
+pub struct Bomb {
+    fuse: u32,
+    owner: Uuid,
+}
+
+pub fn spawn_bomb(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    position: Vec2,
+    velocity: Vec2,
+    owner: Uuid,
+) {
+    commands
+        .spawn((
+            Name::new("bomb"),
+            StateScoped(GameState::InGame),
+            Bomb {
+                fuse: BOMB_FUSE,
+                owner,
+            },
+            EntityDepth,
+            Transform::from_translation(position.extend(0.0)),
+            GlobalTransform::default(),
+            InheritedVisibility::default(),
+            RigidBody::Dynamic,
+            LockedAxes::ROTATION_LOCKED,
+            Damping {
+                linear_damping: 3.0,
+                angular_damping: 0.0,
+            },
+            Collider::ball(4.0),
+            CollisionGroups::new(ENTITY_GROUP, WALL_GROUP),
+            Velocity::linear(velocity),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Sprite {
+                    color: Color::srgb(1.0, 0.4, 0.3),
+                    ..default()
+                },
+                AseSpriteSlice {
+                    aseprite: assets.atlas.clone(),
+                    name: "slime_seed".into(),
+                },
+            ));
+        });
+}
+
+/// 導火線が尽きた爆弾を爆発させます
+/// リモートプレイヤーへのダメージはリモート側で処理されるため、ここでは扱いません
+pub fn explode_bomb(
+    mut commands: Commands,
+    mut bomb_query: Query<(Entity, &mut Bomb, &Transform)>,
+    mut life_query: Query<
+        (
+            Entity,
+            &mut Life,
+            &Transform,
+            Option<&mut ExternalImpulse>,
+            Option<&mut Elite>,
+            Has<Actor>,
+        ),
+        (Without<Bomb>, Without<RemotePlayer>),
+    >,
+    mut se: EventWriter<SEEvent>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    resource: Res<BulletParticleResource>,
+) {
+    for (bomb_entity, mut bomb, bomb_transform) in bomb_query.iter_mut() {
+        if 0 < bomb.fuse {
+            bomb.fuse -= 1;
+            continue;
+        }
+
+        let position = bomb_transform.translation.truncate();
+        commands.entity(bomb_entity).despawn_recursive();
+        spawn_particle_system(&mut commands, position, &resource);
+        se.send(SEEvent::pos(SE::Break, position));
+
+        for (entity, mut life, transform, impulse, elite, is_actor) in life_query.iter_mut() {
+            let diff = transform.translation.truncate() - position;
+            if BOMB_RADIUS < diff.length() {
+                continue;
+            }
+            let damage = match elite {
+                Some(mut elite) => elite.absorb(BOMB_DAMAGE),
+                None => BOMB_DAMAGE,
+            };
+            life.life = (life.life - damage).max(0);
+            life.amplitude = 6.0;
+            if let Some(mut impulse) = impulse {
+                impulse.impulse += diff.normalize_or_zero() * BOMB_IMPULSE;
+            }
+            spawn_damage_number(&mut commands, damage, transform.translation.truncate());
+            if is_actor {
+                damage_writer.send(DamageEvent {
+                    target: entity,
+                    attacker: Some(bomb.owner),
+                    damage,
+                });
+            }
+        }
+    }
+}
+
+pub struct BombPlugin;
+
+impl Plugin for BombPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            explode_bomb
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use crate::asset::GameAssets;
+use crate::controller::replication::RemoteControlled;
 use crate::entity::life::{Life, LifeBeingSprite};
 use crate::entity::EntityDepth;
+use crate::level::CurrentLevel;
+use crate::loot::{level_depth, spawn_loot, LootSource};
 use crate::se::{SEEvent, SE};
 use crate::{constant::*, states::GameState};
 use bevy::prelude::*;
@@ -15,7 +19,12 @@ pub struct Bookshelf;
 
 /// 指定した位置に本棚を生成します
 /// 指定する位置はスプライトの左上ではなく、重心のピクセル座標です
-pub fn spawn_book_shelf(commands: &mut Commands, aseprite: Handle<Aseprite>, x: f32, y: f32) {
+pub fn spawn_book_shelf(
+    commands: &mut Commands,
+    aseprite: Handle<Aseprite>,
+    x: f32,
+    y: f32,
+) -> Entity {
     let aseprite_clone = aseprite.clone();
 
     let mut parent = commands.spawn((
@@ -48,20 +57,29 @@ pub fn spawn_book_shelf(commands: &mut Commands, aseprite: Handle<Aseprite>, x:
             },
         ));
     });
+
+    parent.id()
 }
 
 fn break_book_shelf(
     mut commands: Commands,
-    query: Query<(Entity, &Life, &Transform), With<Bookshelf>>,
+    query: Query<(Entity, &Life, &Transform), (With<Bookshelf>, Without<RemoteControlled>)>,
+    assets: Res<GameAssets>,
+    current: Res<CurrentLevel>,
     mut writer: EventWriter<SEEvent>,
 ) {
+    let depth = current.level.map(level_depth).unwrap_or(0);
     for (entity, breakabke, transform) in query.iter() {
         if breakabke.life <= 0 {
             commands.entity(entity).despawn_recursive();
-            writer.send(SEEvent::pos(
-                SE::Break,
+            writer.send(SEEvent::pos(SE::Break, transform.translation.truncate()));
+            spawn_loot(
+                &mut commands,
+                &assets,
+                LootSource::BookShelf,
+                depth,
                 transform.translation.truncate(),
-            ));
+            );
         }
     }
 }
@@ -1,8 +1,10 @@
+use crate::constant::ENEMY_BULLET_GROUP;
 use crate::controller::remote::RemotePlayer;
-use crate::entity::actor::Actor;
+use crate::enemy::elite::Elite;
+use crate::entity::actor::{Actor, ActorGroup};
 use crate::entity::bullet_particle::BulletParticleResource;
 use crate::entity::damege::spawn_damage_number;
-use crate::entity::life::Life;
+use crate::entity::life::{DamageEvent, Life};
 use crate::entity::EntityDepth;
 use crate::level::wall::WallCollider;
 use crate::se::SE;
@@ -33,6 +35,9 @@ pub struct Bullet {
     impulse: f32,
     owner: Option<Uuid>,
     homing: f32,
+
+    /// 発射したアクターのグループ
+    group: ActorGroup,
 }
 
 #[derive(Bundle)]
@@ -82,7 +87,14 @@ pub fn spawn_bullet(
     writer: &mut EventWriter<SEEvent>,
     spawn: &SpawnBullet,
 ) {
-    writer.send(SEEvent::pos(SE::Fire, spawn.position));
+    // 衝突グループから発射したアクターのグループを求めます
+    let group = if spawn.group == ENEMY_BULLET_GROUP {
+        ActorGroup::Enemy
+    } else {
+        ActorGroup::Player
+    };
+
+    writer.send(SEEvent::pos(SE::Fire, spawn.position).with_source(group));
 
     let mut entity = commands.spawn((
         Name::new("bullet"),
@@ -93,6 +105,7 @@ pub fn spawn_bullet(
             impulse: spawn.impulse,
             owner: spawn.sender,
             homing: spawn.homing,
+            group,
         },
         EntityDepth,
         Transform::from_xyz(spawn.position.x, spawn.position.y, BULLET_Z)
@@ -189,17 +202,23 @@ fn bullet_homing(
     }
 }
 
-fn bullet_collision(
+pub fn bullet_collision(
     mut commands: Commands,
     mut bullet_query: Query<(Entity, &mut Bullet, &Transform, &Velocity)>,
     mut actor_query: Query<
-        (&mut Actor, Option<&mut ExternalImpulse>, &mut Life),
+        (
+            &mut Actor,
+            Option<&mut ExternalImpulse>,
+            &mut Life,
+            Option<&mut Elite>,
+        ),
         Without<RemotePlayer>,
     >,
     mut lifebeing_query: Query<(&mut Life, Option<&mut ExternalImpulse>), Without<Actor>>,
     mut collision_events: EventReader<CollisionEvent>,
     wall_collider_query: Query<Entity, With<WallCollider>>,
     mut writer: EventWriter<SEEvent>,
+    mut damage_writer: EventWriter<DamageEvent>,
     resource: Res<BulletParticleResource>,
 ) {
     // 弾丸が壁の角に当たった場合、衝突イベントが同時に複数回発生するため、
@@ -221,6 +240,7 @@ fn bullet_collision(
                     &b,
                     &wall_collider_query,
                     &mut writer,
+                    &mut damage_writer,
                     &resource,
                 ) {
                     process_bullet_event(
@@ -233,6 +253,7 @@ fn bullet_collision(
                         &a,
                         &wall_collider_query,
                         &mut writer,
+                        &mut damage_writer,
                         &resource,
                     );
                 }
@@ -246,7 +267,12 @@ fn process_bullet_event(
     mut commands: &mut Commands,
     query: &Query<(Entity, &mut Bullet, &Transform, &Velocity)>,
     actors: &mut Query<
-        (&mut Actor, Option<&mut ExternalImpulse>, &mut Life),
+        (
+            &mut Actor,
+            Option<&mut ExternalImpulse>,
+            &mut Life,
+            Option<&mut Elite>,
+        ),
         Without<RemotePlayer>,
     >,
     breakabke_query: &mut Query<(&mut Life, Option<&mut ExternalImpulse>), Without<Actor>>,
@@ -255,13 +281,14 @@ fn process_bullet_event(
     b: &Entity,
     wall_collider_query: &Query<Entity, With<WallCollider>>,
     writer: &mut EventWriter<SEEvent>,
+    damage_writer: &mut EventWriter<DamageEvent>,
     resource: &Res<BulletParticleResource>,
 ) -> bool {
     if let Ok((bullet_entity, bullet, bullet_transform, bullet_velocity)) = query.get(*a) {
         let bullet_position = bullet_transform.translation.truncate();
 
         if !despownings.contains(&bullet_entity) {
-            if let Ok((actor, impilse, mut lifebeing)) = actors.get_mut(*b) {
+            if let Ok((actor, impilse, mut lifebeing, elite)) = actors.get_mut(*b) {
                 trace!("bullet hit actor: {:?}", actor.uuid);
 
                 // 弾丸がアクターに衝突したとき
@@ -269,7 +296,12 @@ fn process_bullet_event(
                 // 弾丸の詠唱者自身に命中した場合はダメージやノックバックはなし
                 // リモートプレイヤーのダメージやノックバックはリモートで処理されるため、ここでは処理しない
                 if bullet.owner == None || Some(actor.uuid) != bullet.owner {
-                    lifebeing.life = (lifebeing.life - bullet.damage).max(0);
+                    // エリートのシールドはライフより先にダメージを受けます
+                    let damage = match elite {
+                        Some(mut elite) => elite.absorb(bullet.damage),
+                        None => bullet.damage,
+                    };
+                    lifebeing.life = (lifebeing.life - damage).max(0);
                     lifebeing.amplitude = 6.0;
                     if let Some(mut impilse) = impilse {
                         impilse.impulse +=
@@ -278,8 +310,14 @@ fn process_bullet_event(
                     despownings.insert(bullet_entity.clone());
                     commands.entity(bullet_entity).despawn_recursive();
                     spawn_particle_system(&mut commands, bullet_position, resource);
-                    spawn_damage_number(&mut commands, bullet.damage, bullet_position);
-                    writer.send(SEEvent::pos(SE::Damage, bullet_position));
+                    spawn_damage_number(&mut commands, damage, bullet_position);
+                    writer
+                        .send(SEEvent::pos(SE::Damage, bullet_position).with_source(bullet.group));
+                    damage_writer.send(DamageEvent {
+                        target: *b,
+                        attacker: bullet.owner,
+                        damage,
+                    });
                 }
             } else if let Ok((mut breakabke, impulse_optional)) = breakabke_query.get_mut(*b) {
                 trace!("bullet hit: {:?}", b);
@@ -289,7 +327,7 @@ fn process_bullet_event(
                 commands.entity(bullet_entity).despawn_recursive();
                 spawn_particle_system(&mut commands, bullet_position, resource);
                 spawn_damage_number(&mut commands, bullet.damage, bullet_position);
-                writer.send(SEEvent::pos(SE::Damage, bullet_position));
+                writer.send(SEEvent::pos(SE::Damage, bullet_position).with_source(bullet.group));
 
                 if let Some(mut impilse) = impulse_optional {
                     impilse.impulse += bullet_velocity.linvel.normalize_or_zero() * bullet.impulse;
@@ -1,7 +1,9 @@
+use crate::controller::replication::RemoteControlled;
+use crate::level::CurrentLevel;
+use crate::loot::{level_depth, spawn_loot, LootSource};
 use crate::{asset::GameAssets, constant::*, se::SEEvent, states::GameState};
 use crate::{
     entity::{
-        gold::spawn_gold,
         life::{Life, LifeBeingSprite},
         EntityDepth,
     },
@@ -10,7 +12,6 @@ use crate::{
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::*;
 use bevy_rapier2d::prelude::*;
-use rand::random;
 
 const ENTITY_WIDTH: f32 = 8.0;
 
@@ -24,9 +25,8 @@ pub enum ChestType {
 }
 
 #[derive(Default, Component, Reflect)]
-struct Chest {
+pub struct Chest {
     pub chest_type: ChestType,
-    pub golds: i32,
 }
 
 /// チェストを生成します
@@ -37,7 +37,7 @@ pub fn spawn_chest(
     x: f32,
     y: f32,
     chest_type: ChestType,
-) {
+) -> Entity {
     let tx = x + ENTITY_WIDTH - TILE_SIZE / 2.0;
     let ty = y - ENTITY_HEIGHT + TILE_SIZE / 2.0;
     commands
@@ -49,13 +49,7 @@ pub fn spawn_chest(
                 max_life: 30,
                 amplitude: 0.0,
             },
-            Chest {
-                chest_type,
-                golds: match chest_type {
-                    ChestType::Chest => 10,
-                    ChestType::Crate => 0,
-                },
-            },
+            Chest { chest_type },
             EntityDepth,
             Transform::from_translation(Vec3::new(tx, ty, 0.0)),
             GlobalTransform::default(),
@@ -92,30 +86,34 @@ pub fn spawn_chest(
                     .into(),
                 },
             ));
-        });
+        })
+        .id()
 }
 
 fn break_chest(
     mut commands: Commands,
-    query: Query<(Entity, &Life, &Transform, &Chest)>,
+    query: Query<(Entity, &Life, &Transform, &Chest), Without<RemoteControlled>>,
     assets: Res<GameAssets>,
+    current: Res<CurrentLevel>,
     mut writer: EventWriter<SEEvent>,
 ) {
+    let depth = current.level.map(level_depth).unwrap_or(0);
     for (entity, breakabke, transform, chest) in query.iter() {
         if breakabke.life <= 0 {
             commands.entity(entity).despawn_recursive();
             writer.send(SEEvent::pos(SE::Break, transform.translation.truncate()));
 
-            if chest.chest_type == ChestType::Chest {
-                for _ in 0..(3 + random::<i32>().abs() % 10) {
-                    spawn_gold(
-                        &mut commands,
-                        &assets,
-                        transform.translation.x,
-                        transform.translation.y,
-                    );
-                }
-            }
+            let source = match chest.chest_type {
+                ChestType::Chest => LootSource::Chest,
+                ChestType::Crate => LootSource::Crate,
+            };
+            spawn_loot(
+                &mut commands,
+                &assets,
+                source,
+                depth,
+                transform.translation.truncate(),
+            );
         }
     }
 }
@@ -15,9 +15,14 @@ use super::life::Life;
 
 #[derive(Component)]
 pub struct DroppedItemEntity {
-    item: InventoryItem,
+    pub item: InventoryItem,
 }
 
+/// プレイヤーがインベントリから地面に置いたアイテムを表します
+/// ショップの店員はこのアイテムだけを買い取ります
+#[derive(Component)]
+pub struct DroppedByPlayer;
+
 #[derive(Component)]
 struct SpellSprites {
     swing: f32,
@@ -29,23 +34,26 @@ pub fn spawn_dropped_item(
     assets: &Res<GameAssets>,
     position: Vec2,
     item: InventoryItem,
-) {
+) -> Entity {
     let item_type = item.item_type;
     let icon = match item_type {
         InventoryItemType::Spell(spell) => spell.to_props().icon,
-        InventoryItemType::Wand(wand) => wand.to_props().icon,
-        InventoryItemType::Equipment(equipment) => equipment.to_props().icon,
+        InventoryItemType::Wand(wand) => wand.wand_type.to_props().icon,
+        InventoryItemType::Equipment(equipment) => equipment.equipment_type.to_props().icon,
+        InventoryItemType::Consumable(consumable) => consumable.to_props().icon,
     };
     let name = match item_type {
         InventoryItemType::Spell(spell) => spell.to_props().name.en,
-        InventoryItemType::Wand(wand) => wand.to_props().name.en,
-        InventoryItemType::Equipment(equipment) => equipment.to_props().name.en,
+        InventoryItemType::Wand(wand) => wand.wand_type.to_props().name.en,
+        InventoryItemType::Equipment(equipment) => equipment.equipment_type.to_props().name.en,
+        InventoryItemType::Consumable(consumable) => consumable.to_props().name.en,
     };
     let frame_slice = match item_type {
         InventoryItemType::Wand(_) => "empty", //"wand_frame",
         InventoryItemType::Spell(_) if 0 < item.price => "spell_frame_yellow",
         InventoryItemType::Spell(_) => "spell_frame",
         InventoryItemType::Equipment(_) => "empty",
+        InventoryItemType::Consumable(_) => "empty",
     };
     let collider_width = match item_type {
         InventoryItemType::Wand(_) => 16.0,
@@ -55,6 +63,7 @@ pub fn spawn_dropped_item(
         InventoryItemType::Spell(_) => 2.0,
         InventoryItemType::Wand(_) => 0.0,
         InventoryItemType::Equipment(_) => 0.0,
+        InventoryItemType::Consumable(_) => 2.0,
     };
     commands
         .spawn((
@@ -134,7 +143,8 @@ pub fn spawn_dropped_item(
                         Transform::from_xyz(0.0, 0.0, 0.0001),
                     ));
                 });
-        });
+        })
+        .id()
 }
 
 fn swing(mut query: Query<(&mut Transform, &SpellSprites)>, frame_count: Res<FrameCount>) {
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use uuid::Uuid;
 
 use crate::states::GameState;
 
@@ -16,6 +17,15 @@ pub struct Life {
     pub amplitude: f32,
 }
 
+/// アクターが弾丸によってダメージを受けたときに送られるイベントです
+/// attacker は弾丸を発射したアクターのUUIDです
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub attacker: Option<Uuid>,
+    pub damage: i32,
+}
+
 /// ダメージを受けた時に振動するスプライト
 #[derive(Default, Component, Reflect)]
 pub struct LifeBeingSprite;
@@ -39,6 +49,7 @@ pub struct LifePlugin;
 
 impl Plugin for LifePlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>();
         app.add_systems(
             Update,
             vibrate_breakabke_sprite.run_if(in_state(GameState::InGame)),
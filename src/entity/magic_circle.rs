@@ -1,7 +1,7 @@
 use crate::{
     asset::GameAssets,
     constant::*,
-    controller::player::Player,
+    controller::{player::Player, remote::RemotePlayer},
     hud::overlay::OverlayEvent,
     level::{CurrentLevel, GameLevel},
     player_state::PlayerState,
@@ -16,7 +16,10 @@ use bevy_rapier2d::{
     prelude::{ActiveEvents, Collider, CollisionEvent, CollisionGroups, Sensor},
 };
 
-use super::{actor::Actor, life::Life};
+use super::{
+    actor::{Actor, ActorGroup},
+    life::Life,
+};
 
 const MAX_POWER: i32 = 360;
 const MIN_RADIUS_ON: f32 = 100.0;
@@ -107,8 +110,12 @@ pub fn spawn_magic_circle(
     ));
 }
 
+/// 魔法陣に乗ることができるのは、プレイヤー自身と協力プレイの仲間です
+/// アリーナの対戦相手は敵のグループなので、魔法陣のセンサーには反応しません
+type WalkerFilter = Or<(With<Player>, With<RemotePlayer>)>;
+
 fn power_on_circle(
-    player_query: Query<&Player>,
+    player_query: Query<(), WalkerFilter>,
     mut circle_query: Query<&mut MagicCircle>,
     mut events: EventReader<CollisionEvent>,
     mut writer: EventWriter<SEEvent>,
@@ -133,14 +140,21 @@ fn power_on_circle(
 fn warp(
     mut commands: Commands,
     mut player_query: Query<(Entity, &Player, &Actor, &Life)>,
+    ally_query: Query<&Actor, (With<RemotePlayer>, Without<Player>)>,
     mut circle_query: Query<(&mut MagicCircle, &Transform)>,
     mut next: ResMut<CurrentLevel>,
     mut writer: EventWriter<SEEvent>,
     mut overlay_event_writer: EventWriter<OverlayEvent>,
 ) {
+    // 協力プレイでは、同じレベルにいる仲間全員が魔法陣に乗っている場合だけワープします
+    let required = 1 + ally_query
+        .iter()
+        .filter(|actor| actor.actor_group == ActorGroup::Player)
+        .count() as i32;
+
     for (mut circle, transform) in circle_query.iter_mut() {
         if circle.step < MAX_POWER {
-            if 0 < circle.players {
+            if required <= circle.players {
                 circle.step = (circle.step + 1).min(MAX_POWER);
             } else {
                 circle.step = (circle.step - 1).max(0);
@@ -150,14 +164,7 @@ fn warp(
                 writer.send(SEEvent::pos(SE::Warp, transform.translation.truncate()));
                 commands.entity(entity).despawn_recursive();
 
-                let player_state = PlayerState {
-                    name: player.name.clone(),
-                    life: actor_life.life,
-                    max_life: actor_life.max_life,
-                    inventory: actor.inventory.clone(),
-                    equipments: actor.equipments.clone(),
-                    wands: actor.wands.clone(),
-                };
+                let player_state = PlayerState::new(player, actor, actor_life);
 
                 match circle.destination {
                     MagicCircleDestination::NextLevel => {
@@ -212,7 +219,7 @@ fn update_circle_color(
 fn process_collision_start_event(
     a: &Entity,
     b: &Entity,
-    players: &Query<&Player>,
+    players: &Query<(), WalkerFilter>,
     circle_query: &mut Query<&mut MagicCircle>,
 ) -> bool {
     if players.contains(*a) {
@@ -227,7 +234,7 @@ fn process_collision_start_event(
 fn process_collision_end_event(
     a: &Entity,
     b: &Entity,
-    players: &Query<&Player>,
+    players: &Query<(), WalkerFilter>,
     circle_query: &mut Query<&mut MagicCircle>,
 ) -> bool {
     if players.contains(*a) {
@@ -1,20 +1,46 @@
 use crate::asset::GameAssets;
 use crate::config::GameConfig;
 use crate::constant::*;
+use crate::controller::despawn_with_gold::DespawnWithGold;
 use crate::controller::player::Player;
+use crate::enemy::ai::{Behavior, EnemyAi};
 use crate::entity::actor::{Actor, ActorFireState};
 use crate::entity::actor::{ActorGroup, ActorState};
+use crate::entity::dropped_item::{DroppedByPlayer, DroppedItemEntity};
 use crate::entity::life::Life;
+use crate::entity::shop::ShopliftEvent;
 use crate::entity::EntityChildrenAutoDepth;
 use crate::inventory::Inventory;
 use crate::language::Dict;
 use crate::se::{SEEvent, SE};
 use crate::speech_bubble::SpeechEvent;
+use crate::spell::SpellType;
 use crate::states::GameState;
+use crate::wand::{Wand, WandItem, WandSpell, WandType};
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::{AseSpriteAnimation, AseSpriteSlice};
 use bevy_rapier2d::prelude::*;
 
+/// 店主がアイテムを買い取るときの、基本価格に対する割合
+const SELL_RATE: f32 = 0.5;
+
+/// 店主のそばのこの距離以内に置かれたアイテムを買い取ります
+const BUY_RANGE: f32 = 24.0;
+
+/// 商品を持ち逃げされて怒った店主が標的を見つけられる範囲
+const HOSTILE_VISION_RANGE: f32 = TILE_SIZE * 16.0;
+
+const HOSTILE_HEARING_RANGE: f32 = TILE_SIZE * 16.0;
+
+const HOSTILE_MOVE_FORCE: f32 = 40000.0;
+
+/// 怒った店主のライフ
+/// 怒るまでは倒されないようにライフを大きくしてあり、怒ったときにこの値にします
+const HOSTILE_LIFE: i32 = 400;
+
+/// 怒った店主を倒したときに落とす金塊の数
+const HOSTILE_GOLD: u32 = 30;
+
 #[derive(Component)]
 pub struct Rabbit;
 
@@ -114,7 +140,7 @@ pub fn spawn_rabbit(commands: &mut Commands, assets: &Res<GameAssets>, position:
 fn collision_inner_sensor(
     mut collision_events: EventReader<CollisionEvent>,
     sensor_query: Query<&RabbitSensor>,
-    mut player_query: Query<&mut Actor, With<Player>>,
+    mut player_query: Query<(&mut Actor, &mut Player)>,
     mut speech_writer: EventWriter<SpeechEvent>,
     mut se: EventWriter<SEEvent>,
     config: Res<GameConfig>,
@@ -152,38 +178,68 @@ fn chat_start(
     a: &Entity,
     b: &Entity,
     sensor_query: &Query<&RabbitSensor>,
-    player_query: &mut Query<&mut Actor, With<Player>>,
+    player_query: &mut Query<(&mut Actor, &mut Player)>,
     speech_writer: &mut EventWriter<SpeechEvent>,
     se: &mut EventWriter<SEEvent>,
     config: &Res<GameConfig>,
 ) -> bool {
     if sensor_query.contains(*a) {
-        if let Ok(mut actor) = player_query.get_mut(*b) {
+        if let Ok((mut actor, mut player)) = player_query.get_mut(*b) {
             let dept = actor.dept();
-            if 0 < dept {
-                if actor.liquidate() {
+            let debt = player.debt;
+            let total = dept + debt;
+            if 0 < total {
+                // 借金がある場合は、今回の買い物と合わせて払ってもらいます
+                if total as i32 <= actor.golds {
+                    actor.golds -= debt as i32;
+                    player.debt = 0;
+                    actor.liquidate();
                     se.send(SEEvent::new(SE::Register));
+                    if 0 < debt {
+                        speech_writer.send(SpeechEvent::Speech(config.language.m17n(
+                            format!(
+                                "ツケの{}ゴールドも含めて\n合計{}ゴールドだね\n確かに受け取ったよ",
+                                debt, total
+                            ),
+                            format!(
+                                "Including your {} Golds tab\nyour total is {} Golds\nThank you",
+                                debt, total
+                            ),
+                        )));
+                    } else {
+                        speech_writer.send(SpeechEvent::Speech(config.language.m17n(
+                            format!("合計{}ゴールドのお買い上げ！\nありがとう", dept).to_string(),
+                            format!("Your total is {} Golds\nThank you", dept).to_string(),
+                        )));
+                    }
+                } else if 0 < debt && dept == 0 {
                     speech_writer.send(SpeechEvent::Speech(config.language.m17n(
-                        format!("合計{}ゴールドのお買い上げ！\nありがとう", dept).to_string(),
-                        format!("Your total is {} Golds\nThank you", dept).to_string(),
+                        format!(
+                            "噂は聞いてるよ\nよその店のツケが{}ゴールド\n残っているそうじゃないか",
+                            debt
+                        ),
+                        format!(
+                            "I've heard about you\nYou still owe {} Golds\nto another shop",
+                            debt
+                        ),
                     )));
                 } else {
                     speech_writer.send(SpeechEvent::Speech(config.language.m17n(
                         format!(
                             "おいおい\n{}ゴールド足りないよ\n買わない商品は\n戻しておいてね",
-                            dept as i32 - actor.golds
+                            total as i32 - actor.golds
                         ),
                         format!(
                             "Hey, hey!\nYou are {} Golds short!\nPut it back that you woun't buy",
-                            dept as i32 - actor.golds
+                            total as i32 - actor.golds
                         ),
                     )));
                 }
             } else {
                 speech_writer.send(SpeechEvent::Speech(
                     (Dict {
-                        ja: "やあ\nなにか買っていくかい？\n欲しい商品があったら\n持ってきて",
-                        en: "Hello\nIs there anything you want?\nIf you have something you want\nbring it here",
+                        ja: "やあ\nなにか買っていくかい？\n欲しい商品があったら\n持ってきて\nいらない物はそばに置けば\n買い取るよ",
+                        en: "Hello\nIs there anything you want?\nIf you have something you want\nbring it here\nI'll also buy what you\nput down next to me",
                     })
                     .get(config.language).to_string(),
                 ));
@@ -198,7 +254,7 @@ fn chat_end(
     a: &Entity,
     b: &Entity,
     sensor_query: &Query<&RabbitSensor>,
-    player_query: &Query<&mut Actor, With<Player>>,
+    player_query: &Query<(&mut Actor, &mut Player)>,
     speech_writer: &mut EventWriter<SpeechEvent>,
 ) -> bool {
     if sensor_query.contains(*a) && player_query.contains(*b) {
@@ -213,33 +269,52 @@ fn collision_outer_sensor(
     sensor_query: Query<&RabbitOuterSensor>,
     player_query: Query<&Actor, With<Player>>,
     mut speech_writer: EventWriter<SpeechEvent>,
+    config: Res<GameConfig>,
 ) {
     for collision_event in collision_events.read() {
         match collision_event {
             CollisionEvent::Started(..) => {}
             CollisionEvent::Stopped(a, b, _option) => {
-                let _ = out_sensor(a, b, &sensor_query, &player_query, &mut speech_writer)
-                    || out_sensor(b, a, &sensor_query, &player_query, &mut speech_writer);
+                let _ = out_sensor(
+                    a,
+                    b,
+                    &sensor_query,
+                    &player_query,
+                    &mut speech_writer,
+                    &config,
+                ) || out_sensor(
+                    b,
+                    a,
+                    &sensor_query,
+                    &player_query,
+                    &mut speech_writer,
+                    &config,
+                );
             }
         }
     }
 }
 
+/// 会計をせずに店主から離れようとしたプレイヤーを呼び止めます
 fn out_sensor(
     a: &Entity,
     b: &Entity,
     sensor_query: &Query<&RabbitOuterSensor>,
     player_query: &Query<&Actor, With<Player>>,
-    _speech_writer: &mut EventWriter<SpeechEvent>,
+    speech_writer: &mut EventWriter<SpeechEvent>,
+    config: &Res<GameConfig>,
 ) -> bool {
     if sensor_query.contains(*a) {
         if let Ok(actor) = player_query.get(*b) {
             if 0 < actor.dept() {
-                // WIP
-                // speech_writer.send(SpeechEvent::Speech(
-                //     format!("おいおいおいおい\n冗談はよしてくれ\nまだ会計をしてないのに\nどこに行く気だい？")
-                //         .to_string(),
-                // ));
+                speech_writer.send(SpeechEvent::Speech(
+                    (Dict {
+                        ja: "おいおいおいおい\n冗談はよしてくれ\nまだ会計をしてないのに\nどこに行く気だい？",
+                        en: "Whoa, whoa, whoa!\nYou must be joking\nYou haven't paid yet\nWhere do you think you're going?",
+                    })
+                    .get(config.language)
+                    .to_string(),
+                ));
                 return true;
             }
         }
@@ -247,13 +322,122 @@ fn out_sensor(
     return false;
 }
 
+/// 商品を持ち逃げされた店主は怒って魔法で襲いかかってきます
+/// 会話や買い取りには応じなくなり、倒されると金塊を残して消滅します
+fn become_hostile(
+    mut commands: Commands,
+    mut reader: EventReader<ShopliftEvent>,
+    mut rabbit_query: Query<(&mut Actor, &mut Life, &Children), (With<Rabbit>, Without<EnemyAi>)>,
+    sensor_query: Query<Entity, Or<(With<RabbitSensor>, With<RabbitOuterSensor>)>>,
+    mut speech_writer: EventWriter<SpeechEvent>,
+    mut se: EventWriter<SEEvent>,
+    config: Res<GameConfig>,
+) {
+    for event in reader.read() {
+        if let Ok((mut actor, mut life, children)) = rabbit_query.get_mut(event.rabbit) {
+            let mut slots = [None; MAX_SPELLS_IN_WAND];
+            slots[0] = Some(WandSpell {
+                spell_type: SpellType::MagicBolt,
+                price: 0,
+            });
+            actor.wands[0] = Some(Wand {
+                item: WandItem::new(WandType::CypressWand),
+                price: 0,
+                slots,
+                index: 0,
+            });
+            actor.actor_group = ActorGroup::Enemy;
+            actor.move_force = HOSTILE_MOVE_FORCE;
+            life.life = HOSTILE_LIFE;
+            life.max_life = HOSTILE_LIFE;
+
+            commands.entity(event.rabbit).insert((
+                EnemyAi::new(
+                    HOSTILE_VISION_RANGE,
+                    HOSTILE_HEARING_RANGE,
+                    vec![
+                        Behavior::Cast {
+                            range: TILE_SIZE * 10.0,
+                        },
+                        Behavior::Chase {
+                            range: TILE_SIZE * 3.0,
+                        },
+                    ],
+                ),
+                DespawnWithGold { gold: HOSTILE_GOLD },
+                LockedAxes::ROTATION_LOCKED,
+                CollisionGroups::new(
+                    ENEMY_GROUP,
+                    ENTITY_GROUP | WALL_GROUP | WITCH_GROUP | ENEMY_GROUP | WITCH_BULLET_GROUP,
+                ),
+            ));
+
+            for child in children.iter() {
+                if sensor_query.contains(*child) {
+                    commands.entity(*child).despawn_recursive();
+                }
+            }
+
+            se.send(SEEvent::new(SE::Growl));
+            speech_writer.send(SpeechEvent::Speech(config.language.m17n(
+                format!(
+                    "泥棒！\n{}ゴールド分の商品を\n返してもらうよ！",
+                    event.amount
+                ),
+                format!(
+                    "Thief!\nI want my {} Golds\nworth of goods back!",
+                    event.amount
+                ),
+            )));
+        }
+    }
+}
+
+/// プレイヤーが店主のそばに置いたアイテムを割り引いた価格で買い取ります
+/// 未清算の商品は店の品物なので買い取りません
+fn buy_dropped_items(
+    mut commands: Commands,
+    rabbit_query: Query<&Transform, (With<Rabbit>, Without<EnemyAi>)>,
+    item_query: Query<(Entity, &DroppedItemEntity, &Transform), With<DroppedByPlayer>>,
+    mut player_query: Query<&mut Actor, With<Player>>,
+    mut speech_writer: EventWriter<SpeechEvent>,
+    mut se: EventWriter<SEEvent>,
+    config: Res<GameConfig>,
+) {
+    if let Ok(mut actor) = player_query.get_single_mut() {
+        for rabbit_transform in rabbit_query.iter() {
+            let rabbit_position = rabbit_transform.translation.truncate();
+            for (item_entity, item, item_transform) in item_query.iter() {
+                let distance = rabbit_position.distance(item_transform.translation.truncate());
+                if 0 < item.item.price || BUY_RANGE < distance {
+                    continue;
+                }
+                let golds = (item.item.item_type.get_price() as f32 * SELL_RATE) as i32
+                    * item.item.count as i32;
+                actor.golds += golds;
+                commands.entity(item_entity).despawn_recursive();
+                se.send(SEEvent::new(SE::Register));
+                speech_writer.send(SpeechEvent::Speech(config.language.m17n(
+                    format!("{}ゴールドで\n買い取るよ", golds),
+                    format!("I'll buy it\nfor {} Golds", golds),
+                )));
+            }
+        }
+    }
+}
+
 pub struct RabbitPlugin;
 
 impl Plugin for RabbitPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             FixedUpdate,
-            (collision_inner_sensor, collision_outer_sensor)
+            (
+                collision_inner_sensor,
+                collision_outer_sensor,
+                buy_dropped_items,
+                become_hostile,
+            )
                 .run_if(in_state(GameState::InGame))
                 .before(PhysicsSet::SyncBackend),
         );
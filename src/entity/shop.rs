@@ -1,26 +1,112 @@
 use crate::{
     config::GameConfig,
-    constant::{EMPTY_GROUP, TILE_SIZE, WALL_GROUP, WITCH_GROUP},
+    constant::{EMPTY_GROUP, SENSOR_GROUP, TILE_SIZE, WALL_GROUP, WITCH_GROUP},
+    consumable::CONSUMABLES,
     controller::player::Player,
+    equipment::{EquipmentItem, EQUIPMENTS},
+    inventory::InventoryItem,
+    inventory_item::InventoryItemType,
     speech_bubble::SpeechEvent,
+    spell::SPELL_TYPES,
     states::GameState,
 };
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use super::actor::Actor;
+use super::actor::{Actor, Dashing};
+use super::rabbit::Rabbit;
 
 #[derive(Component)]
 struct ShopDoor;
 
+/// 扉を通り抜けたプレイヤーを検出するセンサー
+#[derive(Component)]
+struct ShopDoorSensor;
+
+/// 未清算の商品を持ったまま店から逃げ出したときに送られます
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ShopliftEvent {
+    /// 商品を持ち逃げされた店の店主
+    pub rabbit: Entity,
+
+    /// 持ち逃げした商品の合計金額
+    pub amount: u32,
+}
+
+/// 装備が棚に並ぶ確率
+const EQUIPMENT_RATE: f32 = 0.3;
+
+/// 消費アイテムが棚に並ぶ確率
+const CONSUMABLE_RATE: f32 = 0.2;
+
+/// ショップの棚に並ぶ商品の一覧です
+/// レベルに入るたびに作り直されるので、次のレベルのショップでは品揃えが入れ替わります
+#[derive(Debug, Clone, Default)]
+pub struct ShopInventory {
+    pub items: Vec<InventoryItem>,
+}
+
+impl ShopInventory {
+    /// 呪文、装備、消費アイテムの中からランダムに商品を選んで、棚の数だけ品揃えを作ります
+    /// 同じ呪文はなるべく重複しないように選びます
+    pub fn generate(shelves: usize, depth: i32) -> Self {
+        let mut spells = Vec::from(SPELL_TYPES);
+        let mut items = Vec::new();
+        for _ in 0..shelves {
+            let value = rand::random::<f32>();
+            let item_type = if value < EQUIPMENT_RATE {
+                InventoryItemType::Equipment(EquipmentItem::random(
+                    EQUIPMENTS[rand::random::<usize>() % EQUIPMENTS.len()],
+                    depth,
+                ))
+            } else if value < EQUIPMENT_RATE + CONSUMABLE_RATE {
+                InventoryItemType::Consumable(
+                    CONSUMABLES[rand::random::<usize>() % CONSUMABLES.len()],
+                )
+            } else {
+                if spells.is_empty() {
+                    spells = Vec::from(SPELL_TYPES);
+                }
+                InventoryItemType::Spell(spells.remove(rand::random::<usize>() % spells.len()))
+            };
+            items.push(InventoryItem {
+                item_type,
+                price: item_type.get_price(),
+                count: 1,
+            });
+        }
+        Self { items }
+    }
+
+    /// 次に棚に並べる商品を取り出します
+    pub fn pop(&mut self) -> Option<InventoryItem> {
+        self.items.pop()
+    }
+}
+
+/// 未清算の商品を持っているプレイヤーを通さない扉です
+/// ただしダッシュしている間は通り抜けることができます
 pub fn spawn_shop_door(commands: &mut Commands, position: Vec2) {
-    commands.spawn((
-        ShopDoor,
-        RigidBody::Fixed,
-        Collider::cuboid(TILE_SIZE * 3.0, TILE_SIZE * 2.0),
-        Transform::from_translation(position.extend(0.0)),
-        CollisionGroups::new(WALL_GROUP, WITCH_GROUP),
-    ));
+    commands
+        .spawn((
+            ShopDoor,
+            RigidBody::Fixed,
+            Collider::cuboid(TILE_SIZE * 3.0, TILE_SIZE * 2.0),
+            Transform::from_translation(position.extend(0.0)),
+            GlobalTransform::default(),
+            CollisionGroups::new(WALL_GROUP, WITCH_GROUP),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ShopDoorSensor,
+                Collider::cuboid(TILE_SIZE * 3.0, TILE_SIZE * 2.0),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                Transform::default(),
+                GlobalTransform::default(),
+                CollisionGroups::new(SENSOR_GROUP, WITCH_GROUP),
+            ));
+        });
 }
 
 fn sensor(
@@ -88,15 +174,66 @@ fn enter(
 }
 
 fn update_door_collision(
-    player_query: Query<&mut Actor, (With<Player>, Changed<Actor>)>,
+    player_query: Query<(&Actor, Has<Dashing>), With<Player>>,
     mut door_query: Query<&mut CollisionGroups, With<ShopDoor>>,
 ) {
-    if let Ok(actor) = player_query.get_single() {
+    if let Ok((actor, dashing)) = player_query.get_single() {
+        let memberships = if actor.dept() == 0 || dashing {
+            EMPTY_GROUP
+        } else {
+            WALL_GROUP
+        };
         for mut door in door_query.iter_mut() {
-            if actor.dept() == 0 {
-                door.memberships = EMPTY_GROUP;
+            if door.memberships != memberships {
+                door.memberships = memberships;
+            }
+        }
+    }
+}
+
+/// 未清算の商品を持ったまま扉を抜けて店の外に出た場合は、持ち逃げした商品の代金を借金にします
+/// 扉から見て店主と反対側に抜けた場合に、店の外に出たとみなします
+fn escape(
+    mut collision_events: EventReader<CollisionEvent>,
+    sensor_query: Query<&GlobalTransform, With<ShopDoorSensor>>,
+    rabbit_query: Query<(Entity, &Transform), With<Rabbit>>,
+    mut player_query: Query<(&mut Player, &mut Actor, &Transform)>,
+    mut writer: EventWriter<ShopliftEvent>,
+) {
+    for collision_event in collision_events.read() {
+        if let CollisionEvent::Stopped(a, b, _option) = collision_event {
+            let (sensor, player) = if sensor_query.contains(*a) {
+                (*a, *b)
             } else {
-                door.memberships = WALL_GROUP;
+                (*b, *a)
+            };
+            if let (Ok(sensor_transform), Ok((mut player, mut actor, player_transform))) =
+                (sensor_query.get(sensor), player_query.get_mut(player))
+            {
+                let amount = actor.dept();
+                if amount == 0 {
+                    continue;
+                }
+                // レベルに店主が複数いる場合は、扉に最も近い店主をこの店の店主とみなします
+                let door = sensor_transform.translation().truncate();
+                let nearest = rabbit_query.iter().min_by(|(_, a), (_, b)| {
+                    let a = a.translation.truncate().distance(door);
+                    let b = b.translation.truncate().distance(door);
+                    a.total_cmp(&b)
+                });
+                if let Some((rabbit_entity, rabbit_transform)) = nearest {
+                    let rabbit = rabbit_transform.translation.truncate();
+                    let position = player_transform.translation.truncate();
+                    if 0.0 < (position - door).dot(door - rabbit) {
+                        info!("Shoplifted {} golds", amount);
+                        player.debt += amount;
+                        actor.clear_prices();
+                        writer.send(ShopliftEvent {
+                            rabbit: rabbit_entity,
+                            amount,
+                        });
+                    }
+                }
             }
         }
     }
@@ -106,9 +243,10 @@ pub struct ShopPlugin;
 
 impl Plugin for ShopPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<ShopliftEvent>();
         app.add_systems(
             FixedUpdate,
-            (sensor, update_door_collision)
+            (sensor, update_door_collision, escape)
                 .run_if(in_state(GameState::InGame))
                 .before(PhysicsSet::SyncBackend),
         );
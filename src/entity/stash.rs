@@ -0,0 +1,43 @@
+use crate::asset::GameAssets;
+use crate::constant::*;
+use crate::entity::EntityDepth;
+use crate::states::GameState;
+use bevy::prelude::*;
+use bevy_aseprite_ultra::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// プレイヤーがこの距離以内にいるときにインベントリを開くと、保管箱の中身も表示されます
+pub const STASH_RANGE: f32 = TILE_SIZE * 2.0;
+
+/// ホームに置かれた保管箱です
+/// 中身は Stash リソースに保存されています
+#[derive(Component)]
+pub struct StashChest;
+
+pub fn spawn_stash_chest(commands: &mut Commands, assets: &Res<GameAssets>, position: Vec2) {
+    commands
+        .spawn((
+            Name::new("stash"),
+            StateScoped(GameState::InGame),
+            StashChest,
+            EntityDepth,
+            Transform::from_translation(position.extend(0.0)),
+            GlobalTransform::default(),
+            InheritedVisibility::default(),
+            RigidBody::Fixed,
+            Collider::cuboid(8.0, 8.0),
+            CollisionGroups::new(ENTITY_GROUP, ENTITY_GROUP | WITCH_GROUP | ENEMY_GROUP),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Sprite {
+                    color: Color::srgb(0.6, 0.8, 1.0),
+                    ..default()
+                },
+                AseSpriteSlice {
+                    aseprite: assets.atlas.clone(),
+                    name: "chest".into(),
+                },
+            ));
+        });
+}
@@ -30,6 +30,10 @@ pub struct WitchAnimationSprite;
 #[derive(Component)]
 pub struct Witch;
 
+/// 魔女の頭上に表示される名札です
+#[derive(Component)]
+pub struct WitchNamePlate;
+
 pub fn spawn_witch<T: Component>(
     commands: &mut Commands,
     assets: &Res<GameAssets>,
@@ -148,6 +152,7 @@ pub fn spawn_witch<T: Component>(
 
         spawn_children.spawn((
             WitchWandSprite,
+            Sprite::default(),
             AseSpriteSlice {
                 aseprite: assets.atlas.clone(),
                 name: "wand_cypress".into(),
@@ -159,6 +164,7 @@ pub fn spawn_witch<T: Component>(
         // 自分のプレイヤーキャラクターは名前を表示しません
         if let Some(name) = name_plate {
             spawn_children.spawn((
+                WitchNamePlate,
                 Transform::from_xyz(0.0, 20.0, 100.0),
                 Text2d::new(name),
                 TextColor(Color::hsla(120.0, 1.0, 0.5, 0.3)),
@@ -313,12 +319,12 @@ fn update_witch_animation(
 fn update_wand(
     actor_query: Query<&Actor>,
     mut query: Query<
-        (&Parent, &mut Transform, &mut AseSpriteSlice),
+        (&Parent, &mut Transform, &mut AseSpriteSlice, &mut Sprite),
         (With<WitchWandSprite>, Without<Actor>),
     >,
     assets: Res<GameAssets>,
 ) {
-    for (parent, mut transform, mut slice) in query.iter_mut() {
+    for (parent, mut transform, mut slice, mut sprite) in query.iter_mut() {
         if let Ok(actor) = actor_query.get(parent.get()) {
             let direction = actor.pointer;
             let angle = direction.to_angle();
@@ -334,9 +340,10 @@ fn update_wand(
 
             if let Some(wand) = &actor.wands[actor.current_wand] {
                 *slice = AseSpriteSlice {
-                    name: wand.wand_type.to_props().slice.to_string(),
+                    name: wand.item.wand_type.to_props().slice.to_string(),
                     aseprite: assets.atlas.clone(),
                 };
+                sprite.color = wand.item.color();
             } else {
                 *slice = AseSpriteSlice {
                     name: "empty".to_string(),
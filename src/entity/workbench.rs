@@ -0,0 +1,43 @@
+use crate::asset::GameAssets;
+use crate::constant::*;
+use crate::entity::EntityDepth;
+use crate::states::GameState;
+use bevy::prelude::*;
+use bevy_aseprite_ultra::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// プレイヤーがこの距離以内にいるときにインベントリを開くと、作業台の画面も表示されます
+pub const WORKBENCH_RANGE: f32 = TILE_SIZE * 2.0;
+
+/// ホームに置かれた作業台です
+/// 杖を合成したり、ゴールドを払ってスロットを増やしたりできます
+#[derive(Component)]
+pub struct Workbench;
+
+pub fn spawn_workbench(commands: &mut Commands, assets: &Res<GameAssets>, position: Vec2) {
+    commands
+        .spawn((
+            Name::new("workbench"),
+            StateScoped(GameState::InGame),
+            Workbench,
+            EntityDepth,
+            Transform::from_translation(position.extend(0.0)),
+            GlobalTransform::default(),
+            InheritedVisibility::default(),
+            RigidBody::Fixed,
+            Collider::cuboid(16.0, 8.0),
+            CollisionGroups::new(ENTITY_GROUP, ENTITY_GROUP | WITCH_GROUP | ENEMY_GROUP),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Sprite {
+                    color: Color::srgb(1.0, 0.8, 0.6),
+                    ..default()
+                },
+                AseSpriteSlice {
+                    aseprite: assets.atlas.clone(),
+                    name: "book_shelf".into(),
+                },
+            ));
+        });
+}
@@ -1,8 +1,10 @@
+use bevy::color::Color;
 use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
 
-use crate::language::Dict;
+use crate::language::{Dict, Languages};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
 pub enum EquipmentType {
     Lantern,
     SpikeBoots,
@@ -10,7 +12,12 @@ pub enum EquipmentType {
     Magnifier,
 }
 
-pub const EQUIPMENTS: [EquipmentType; 1] = [EquipmentType::Lantern];
+pub const EQUIPMENTS: [EquipmentType; 4] = [
+    EquipmentType::Lantern,
+    EquipmentType::SpikeBoots,
+    EquipmentType::Telescope,
+    EquipmentType::Magnifier,
+];
 
 impl EquipmentType {
     pub fn to_props(&self) -> EquipmentProps {
@@ -26,6 +33,10 @@ impl EquipmentType {
                     ja: "暗闇を照らすランタン",
                     en: "A lantern that illuminates the darkness",
                 },
+                stats: StatModifiers {
+                    light_intensity: 3.0,
+                    ..NO_MODIFIERS
+                },
             },
             EquipmentType::SpikeBoots => EquipmentProps {
                 icon: "boots",
@@ -38,6 +49,10 @@ impl EquipmentType {
                     ja: "滑り止めのついた革靴。移動速度が上昇する。",
                     en: "Leather shoes with anti-slip. Movement speed increases.",
                 },
+                stats: StatModifiers {
+                    move_force: 40000.0,
+                    ..NO_MODIFIERS
+                },
             },
             EquipmentType::Telescope => EquipmentProps {
                 icon: "zoom_out",
@@ -50,6 +65,10 @@ impl EquipmentType {
                     ja: "遠くまで見えるようになる。",
                     en: "You can see far away.",
                 },
+                stats: StatModifiers {
+                    zoom: 0.5,
+                    ..NO_MODIFIERS
+                },
             },
             EquipmentType::Magnifier => EquipmentProps {
                 icon: "zoom_in",
@@ -62,6 +81,10 @@ impl EquipmentType {
                     ja: "手元が見やすくなる。",
                     en: "It is easier to see the details.",
                 },
+                stats: StatModifiers {
+                    zoom: -0.5,
+                    ..NO_MODIFIERS
+                },
             },
         }
     }
@@ -72,4 +95,299 @@ pub struct EquipmentProps {
     pub name: Dict,
     pub price: u32,
     pub description: Dict,
+
+    /// 装備したときに変化する能力値
+    pub stats: StatModifiers,
+}
+
+/// 装備による能力値の変化量です
+/// 装備しているすべての装備の変化量を合計したものがアクターの能力値に加算されます
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatModifiers {
+    /// 移動力
+    pub move_force: f32,
+
+    /// アクターの周囲を照らす光の強さ
+    pub light_intensity: f32,
+
+    /// カメラの拡大率の対数
+    /// 正の値で遠くまで見えるようになります
+    pub zoom: f32,
+
+    /// 最大ライフ
+    pub max_life: i32,
+
+    /// 呪文の詠唱遅延のフレーム数
+    /// 負の値で連射が速くなります
+    pub cast_delay: i32,
+
+    /// 弾丸の速度の倍率への加算値
+    pub bullet_speed: f32,
+
+    /// 弾丸のダメージ
+    pub damage: i32,
+}
+
+pub const NO_MODIFIERS: StatModifiers = StatModifiers {
+    move_force: 0.0,
+    light_intensity: 0.0,
+    zoom: 0.0,
+    max_life: 0,
+    cast_delay: 0,
+    bullet_speed: 0.0,
+    damage: 0,
+};
+
+/// ひとつの装備が持てる特性の最大数
+pub const MAX_EQUIPMENT_AFFIXES: usize = 3;
+
+/// 装備のレア度
+/// レア度が高いほど多くの特性を持ち、価格も高くなります
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
+pub enum Rarity {
+    Common,
+    Rare,
+    Epic,
+}
+
+impl Rarity {
+    pub fn name(&self) -> Dict {
+        match self {
+            Rarity::Common => Dict {
+                ja: "コモン",
+                en: "Common",
+            },
+            Rarity::Rare => Dict {
+                ja: "レア",
+                en: "Rare",
+            },
+            Rarity::Epic => Dict {
+                ja: "エピック",
+                en: "Epic",
+            },
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Rarity::Common => Color::WHITE,
+            Rarity::Rare => Color::srgb(0.3, 0.6, 1.0),
+            Rarity::Epic => Color::srgb(0.8, 0.4, 1.0),
+        }
+    }
+
+    /// 生成時に付与される特性の数
+    pub fn affix_count(&self) -> usize {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Rare => 2,
+            Rarity::Epic => 3,
+        }
+    }
+
+    /// 基本価格に対する倍率
+    pub fn price_multiplier(&self) -> u32 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Rare => 2,
+            Rarity::Epic => 4,
+        }
+    }
+
+    /// 深い階層ほどレア度の高い装備が出やすくなります
+    fn random(depth: i32) -> Rarity {
+        let bonus = depth.max(0) as f32 * 0.05;
+        let value = rand::random::<f32>();
+        if value < 0.1 + bonus {
+            Rarity::Epic
+        } else if value < 0.35 + bonus * 2.0 {
+            Rarity::Rare
+        } else {
+            Rarity::Common
+        }
+    }
+}
+
+/// 装備に付与されるランダムな特性です
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
+pub enum EquipmentAffix {
+    Swift,
+    Bright,
+    Sturdy,
+    Quick,
+    Velocity,
+    Power,
+}
+
+const EQUIPMENT_AFFIXES: [EquipmentAffix; 6] = [
+    EquipmentAffix::Swift,
+    EquipmentAffix::Bright,
+    EquipmentAffix::Sturdy,
+    EquipmentAffix::Quick,
+    EquipmentAffix::Velocity,
+    EquipmentAffix::Power,
+];
+
+pub struct EquipmentAffixProps {
+    pub name: Dict,
+    pub stats: StatModifiers,
+}
+
+impl EquipmentAffix {
+    pub fn to_props(&self) -> EquipmentAffixProps {
+        match self {
+            EquipmentAffix::Swift => EquipmentAffixProps {
+                name: Dict {
+                    ja: "移動速度上昇",
+                    en: "Movement speed up",
+                },
+                stats: StatModifiers {
+                    move_force: 15000.0,
+                    ..NO_MODIFIERS
+                },
+            },
+            EquipmentAffix::Bright => EquipmentAffixProps {
+                name: Dict {
+                    ja: "周囲が明るくなる",
+                    en: "Brighter light",
+                },
+                stats: StatModifiers {
+                    light_intensity: 1.0,
+                    ..NO_MODIFIERS
+                },
+            },
+            EquipmentAffix::Sturdy => EquipmentAffixProps {
+                name: Dict {
+                    ja: "最大ライフ+10",
+                    en: "Max life +10",
+                },
+                stats: StatModifiers {
+                    max_life: 10,
+                    ..NO_MODIFIERS
+                },
+            },
+            EquipmentAffix::Quick => EquipmentAffixProps {
+                name: Dict {
+                    ja: "詠唱遅延-2",
+                    en: "Cast delay -2",
+                },
+                stats: StatModifiers {
+                    cast_delay: -2,
+                    ..NO_MODIFIERS
+                },
+            },
+            EquipmentAffix::Velocity => EquipmentAffixProps {
+                name: Dict {
+                    ja: "弾速上昇",
+                    en: "Bullet speed up",
+                },
+                stats: StatModifiers {
+                    bullet_speed: 0.2,
+                    ..NO_MODIFIERS
+                },
+            },
+            EquipmentAffix::Power => EquipmentAffixProps {
+                name: Dict {
+                    ja: "ダメージ+2",
+                    en: "Damage +2",
+                },
+                stats: StatModifiers {
+                    damage: 2,
+                    ..NO_MODIFIERS
+                },
+            },
+        }
+    }
+}
+
+impl StatModifiers {
+    fn add(&self, other: &StatModifiers) -> StatModifiers {
+        StatModifiers {
+            move_force: self.move_force + other.move_force,
+            light_intensity: self.light_intensity + other.light_intensity,
+            zoom: self.zoom + other.zoom,
+            max_life: self.max_life + other.max_life,
+            cast_delay: self.cast_delay + other.cast_delay,
+            bullet_speed: self.bullet_speed + other.bullet_speed,
+            damage: self.damage + other.damage,
+        }
+    }
+}
+
+/// 生成された装備のひとつひとつを表します
+/// 同じ種類の装備でも、レア度や特性が異なります
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
+pub struct EquipmentItem {
+    pub equipment_type: EquipmentType,
+    pub rarity: Rarity,
+    pub affixes: [Option<EquipmentAffix>; MAX_EQUIPMENT_AFFIXES],
+}
+
+impl EquipmentItem {
+    /// 特性を持たないコモンの装備を作成します
+    pub fn new(equipment_type: EquipmentType) -> Self {
+        Self {
+            equipment_type,
+            rarity: Rarity::Common,
+            affixes: [None; MAX_EQUIPMENT_AFFIXES],
+        }
+    }
+
+    /// レベルの深さに応じてランダムなレア度を選び、レア度に応じた数の特性を重複なく付与します
+    pub fn random(equipment_type: EquipmentType, depth: i32) -> Self {
+        let rarity = Rarity::random(depth);
+        let mut candidates = EQUIPMENT_AFFIXES.to_vec();
+        let mut affixes = [None; MAX_EQUIPMENT_AFFIXES];
+        for affix in affixes.iter_mut().take(rarity.affix_count()) {
+            *affix = Some(candidates.remove(rand::random::<usize>() % candidates.len()));
+        }
+        Self {
+            equipment_type,
+            rarity,
+            affixes,
+        }
+    }
+
+    /// 装備の種類による変化と特性による変化を合計した能力値の変化量
+    pub fn stats(&self) -> StatModifiers {
+        self.affixes
+            .iter()
+            .flatten()
+            .fold(self.equipment_type.to_props().stats, |stats, affix| {
+                stats.add(&affix.to_props().stats)
+            })
+    }
+
+    /// レア度に応じた価格
+    pub fn price(&self) -> u32 {
+        self.equipment_type.to_props().price * self.rarity.price_multiplier()
+    }
+
+    /// レア度を付けた名前
+    pub fn name(&self, language: Languages) -> String {
+        let name = self
+            .equipment_type
+            .to_props()
+            .name
+            .get(language)
+            .to_string();
+        match self.rarity {
+            Rarity::Common => name,
+            rarity => format!("{} {}", rarity.name().get(language), name),
+        }
+    }
+
+    /// 説明文と特性の一覧
+    pub fn description(&self, language: Languages) -> String {
+        let mut description = self
+            .equipment_type
+            .to_props()
+            .description
+            .get(language)
+            .to_string();
+        for affix in self.affixes.iter().flatten() {
+            description += &format!("\n+ {}", affix.to_props().name.get(language));
+        }
+        description
+    }
 }
@@ -3,14 +3,24 @@ use crate::audio::GameAudioPlugin;
 use crate::camera::*;
 use crate::config::GameConfigPlugin;
 use crate::constant::*;
+use crate::consumable::ConsumablePlugin;
+use crate::controller::arena_match::ArenaMatchPlugin;
+use crate::controller::connection::ConnectionPlugin;
 use crate::controller::despawn_with_gold::DespawnWithGoldPlugin;
+use crate::controller::network_simulator::NetworkSimulatorPlugin;
 use crate::controller::player::PlayerPlugin;
 use crate::controller::remote::RemotePlayerPlugin;
+use crate::controller::replication::ReplicationPlugin;
 use crate::debug::DebugCommandPlugin;
-use crate::enemy::eyeball::EyeballControlPlugin;
+use crate::enemy::ai::EnemyAiPlugin;
+use crate::enemy::boss::BossPlugin;
+use crate::enemy::buer::BuerPlugin;
+use crate::enemy::elite::ElitePlugin;
 use crate::enemy::huge_slime::HugeSlimePlugin;
+use crate::enemy::pathfinding::NavigationPlugin;
 use crate::enemy::slime::SlimeControlPlugin;
 use crate::entity::actor::ActorPlugin;
+use crate::entity::bomb::BombPlugin;
 use crate::entity::book_shelf::BookshelfPlugin;
 use crate::entity::bullet::BulletPlugin;
 use crate::entity::bullet_particle::BulletParticlePlugin;
@@ -28,9 +38,15 @@ use crate::entity::stone_lantern::StoneLanternPlugin;
 use crate::entity::witch::WitchPlugin;
 use crate::entity::EntityPlugin;
 use crate::footsteps::FootStepsPlugin;
+use crate::hud::chat::ChatPlugin;
+use crate::hud::connection_banner::ConnectionBannerPlugin;
+use crate::hud::debug_overlay::DebugOverlayPlugin;
+use crate::hud::emote_wheel::EmoteWheelPlugin;
 use crate::hud::life_bar::LifeBarPlugin;
 use crate::hud::overlay::*;
 use crate::hud::pointer::PointerPlugin;
+use crate::hud::scoreboard::ScoreboardPlugin;
+use crate::hud::spectator::SpectatorPlugin;
 use crate::hud::*;
 use crate::input::GameInputPlugin;
 use crate::level::*;
@@ -42,6 +58,7 @@ use crate::page::warp::WarpPagePlugin;
 use crate::physics::GamePhysicsPlugin;
 use crate::se::SECommandPlugin;
 use crate::speech_bubble::SpeechBubblePlugin;
+use crate::stash::StashPlugin;
 use crate::states::*;
 use crate::ui::bar::StatusBarPlugin;
 use crate::ui::boss_hitpoint_bar::BossHitpointBarPlugin;
@@ -58,10 +75,13 @@ use crate::ui::menu_left::MenuLeftPlugin;
 use crate::ui::on_press::OnPressPlugin;
 use crate::ui::pause_menu::GameMenuPlugin;
 use crate::ui::player_list::PlayerListPlugin;
+use crate::ui::quick_use::QuickUsePlugin;
 use crate::ui::spell_in_wand::SpellInWandPlugin;
+use crate::ui::stash::StashGridPlugin;
 use crate::ui::wand_editor::WandEditorPlugin;
 use crate::ui::wand_list::WandListPlugin;
 use crate::ui::wand_sprite::WandSpritePlugin;
+use crate::ui::workbench::WorkbenchPanelPlugin;
 use bevy::log::*;
 use bevy::prelude::*;
 use bevy::window::WindowMode;
@@ -152,19 +172,30 @@ pub fn run_game() {
         //
         .add_systems(Update, toggle_fullscreen)
         .add_plugins(ActorPlugin)
+        .add_plugins(ArenaMatchPlugin)
+        .add_plugins(BombPlugin)
         .add_plugins(BookshelfPlugin)
         .add_plugins(BossHitpointBarPlugin)
+        .add_plugins(BossPlugin)
         .add_plugins(LifePlugin)
+        .add_plugins(BuerPlugin)
         .add_plugins(BulletPlugin)
         .add_plugins(BulletParticlePlugin)
         .add_plugins(CameraPlugin)
+        .add_plugins(ChatPlugin)
         .add_plugins(ChestPlugin)
         .add_plugins(CommandButtonPlugin)
+        .add_plugins(ConnectionBannerPlugin)
+        .add_plugins(ConnectionPlugin)
+        .add_plugins(ConsumablePlugin)
         .add_plugins(DamagePlugin)
         .add_plugins(DebugCommandPlugin)
+        .add_plugins(DebugOverlayPlugin)
         .add_plugins(DespawnWithGoldPlugin)
+        .add_plugins(ElitePlugin)
+        .add_plugins(EmoteWheelPlugin)
         .add_plugins(EndingPlugin)
-        .add_plugins(EyeballControlPlugin)
+        .add_plugins(EnemyAiPlugin)
         .add_plugins(EntityPlugin)
         .add_plugins(EquipmentListPlugin)
         .add_plugins(FootStepsPlugin)
@@ -190,21 +221,29 @@ pub fn run_game() {
         .add_plugins(MenuButtonPlugin)
         .add_plugins(MenuLeftPlugin)
         .add_plugins(NameInputPagePlugin)
+        .add_plugins(NavigationPlugin)
+        .add_plugins(NetworkSimulatorPlugin)
         .add_plugins(OnPressPlugin)
         .add_plugins(OverlayPlugin)
         .add_plugins(PlayerListPlugin)
         .add_plugins(PlayerPlugin)
         .add_plugins(PointerPlugin)
+        .add_plugins(QuickUsePlugin)
         .add_plugins(RabbitPlugin)
         .add_plugins(RemotePlayerPlugin)
+        .add_plugins(ReplicationPlugin)
+        .add_plugins(ScoreboardPlugin)
         .add_plugins(SetupPlugin)
         .add_plugins(ShopPlugin)
         .add_plugins(SlimeControlPlugin)
         .add_plugins(SlimeSeedPlugin)
+        .add_plugins(SpectatorPlugin)
         .add_plugins(SpeechBubblePlugin)
         .add_plugins(SpellInformationPlugin)
         .add_plugins(SpellEntityPlugin)
         .add_plugins(SpellInWandPlugin)
+        .add_plugins(StashGridPlugin)
+        .add_plugins(StashPlugin)
         .add_plugins(StatusBarPlugin)
         .add_plugins(StoneLanternPlugin)
         .add_plugins(WallPlugin)
@@ -214,6 +253,7 @@ pub fn run_game() {
         .add_plugins(WarpPagePlugin)
         .add_plugins(WebSocketPlugin)
         .add_plugins(WitchPlugin)
+        .add_plugins(WorkbenchPanelPlugin)
         .add_plugins(WorldPlugin)
         //
         // メインメニューやゲームプレイ画面などのシーンを定義するstate
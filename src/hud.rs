@@ -1,7 +1,13 @@
+pub mod chat;
+pub mod connection_banner;
+pub mod debug_overlay;
+pub mod emote_wheel;
 pub mod gameover;
 pub mod life_bar;
 pub mod overlay;
 pub mod pointer;
+pub mod scoreboard;
+pub mod spectator;
 
 use crate::asset::GameAssets;
 use crate::config::GameConfig;
@@ -16,8 +22,11 @@ use crate::ui::bar::{spawn_status_bar, StatusBar};
 use crate::ui::boss_hitpoint_bar::spawn_boss_hitpoint_bar;
 use crate::ui::equipment_list::spawn_equipment_list;
 use crate::ui::floating::{spawn_inventory_floating, Floating};
+use crate::ui::quick_use::spawn_quick_use_slot;
+use crate::ui::stash::spawn_stash;
 use crate::ui::wand_editor::spawn_wand_editor;
 use crate::ui::wand_list::spawn_wand_list;
+use crate::ui::workbench::spawn_workbench_panel;
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::AseUiSlice;
 
@@ -30,6 +39,10 @@ pub struct PlayerLifeBar;
 #[derive(Component)]
 pub struct PlayerGold;
 
+/// 店から持ち逃げした商品の借金
+#[derive(Component)]
+pub struct PlayerDebt;
+
 fn setup_hud(
     mut commands: Commands,
     assets: Res<GameAssets>,
@@ -92,6 +105,8 @@ fn setup_hud(
                             spawn_wand_list(&mut parent, &assets);
 
                             spawn_equipment_list(&mut parent, &assets);
+
+                            spawn_quick_use_slot(&mut parent, &assets);
                         });
 
                     // 右下
@@ -115,6 +130,10 @@ fn setup_hud(
 
             spawn_wand_editor(&mut parent, &assets);
 
+            spawn_stash(&mut parent, &assets);
+
+            spawn_workbench_panel(&mut parent, &assets);
+
             spawn_inventory_floating(&mut parent, &assets);
 
             spawn_boss_hitpoint_bar(&mut parent);
@@ -228,23 +247,50 @@ fn spawn_status_bars(parent: &mut ChildBuilder, assets: &Res<GameAssets>) {
                         },
                         HUD,
                     ));
+
+                    parent.spawn((
+                        PlayerDebt,
+                        Name::new("debt"),
+                        GlobalZIndex(HUD_Z_INDEX),
+                        Text::new(""),
+                        TextColor(Color::hsla(0.0, 1.0, 0.6, 0.8)),
+                        TextFont {
+                            font: assets.dotgothic.clone(),
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        HUD,
+                    ));
                 });
         });
 }
 
 fn update_hud(
-    player_query: Query<(&Actor, &Life), (With<Player>, Without<Camera2d>)>,
+    player_query: Query<(&Player, &Actor, &Life), Without<Camera2d>>,
     mut player_life_query: Query<&mut StatusBar, With<PlayerLifeBar>>,
-    mut player_gold_query: Query<&mut Text, (With<PlayerGold>,)>,
+    mut player_gold_query: Query<&mut Text, (With<PlayerGold>, Without<PlayerDebt>)>,
+    mut player_debt_query: Query<&mut Text, (With<PlayerDebt>, Without<PlayerGold>)>,
+    config: Res<GameConfig>,
 ) {
-    if let Ok((actor, actor_life)) = player_query.get_single() {
+    if let Ok((player, actor, actor_life)) = player_query.get_single() {
         let mut player_life = player_life_query.single_mut();
         let mut player_gold = player_gold_query.single_mut();
+        let mut player_debt = player_debt_query.single_mut();
 
         player_life.value = actor_life.life;
         player_life.max_value = actor_life.max_life;
 
         player_gold.0 = format!("{}", actor.golds);
+
+        // 借金は払い終えるまで表示します
+        player_debt.0 = if 0 < player.debt {
+            config.language.m17n(
+                format!("借金 {}", player.debt),
+                format!("Debt {}", player.debt),
+            )
+        } else {
+            "".to_string()
+        };
     }
 }
 
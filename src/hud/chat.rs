@@ -0,0 +1,483 @@
+use crate::asset::GameAssets;
+use crate::config::GameConfig;
+use crate::constant::HUD_Z_INDEX;
+use crate::controller::network_simulator::OutgoingMessage;
+use crate::controller::player::Player;
+use crate::controller::remote::{
+    is_online_level, send_remote_message, RemoteMessage, RemotePlayer,
+};
+use crate::entity::actor::Actor;
+use crate::hud::emote_wheel::Emote;
+use crate::language::{Dict, Languages};
+use crate::level::CurrentLevel;
+use crate::speech_bubble::typing_text;
+use crate::states::{GameMenuState, GameState};
+use bevy::{core::FrameCount, prelude::*, utils::HashMap};
+use bevy_simple_text_input::{
+    TextInput, TextInputInactive, TextInputSettings, TextInputSubmitEvent, TextInputTextColor,
+    TextInputTextFont, TextInputValue,
+};
+use bevy_simple_websocket::{ReadyState, WebSocketState};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// 1つのメッセージの最大文字数
+/// これを超える部分は送信時と受信時に切り捨てられます
+pub const MAX_CHAT_LENGTH: usize = 64;
+
+/// CHAT_RATE_WINDOW 秒の間に送信できるメッセージの数
+/// 他のプレイヤーからこれを超えて届いたメッセージは表示しません
+const CHAT_RATE_LIMIT: usize = 3;
+
+const CHAT_RATE_WINDOW: f32 = 5.0;
+
+/// チャットのログに表示する行数
+const CHAT_LOG_LINES: usize = 6;
+
+/// 入力欄を閉じているとき、ログを表示し続ける時間(秒)
+const CHAT_LOG_SECONDS: f32 = 10.0;
+
+/// 吹き出しの文字をすべて表示したあと、吹き出しを表示し続ける時間(秒)
+const CHAT_BUBBLE_SECONDS: f32 = 4.0;
+
+/// 吹き出しの幅の上限
+const CHAT_BUBBLE_WIDTH: f32 = 240.0;
+
+const TOO_FAST: Dict = Dict {
+    ja: "メッセージの送信が多すぎます",
+    en: "You are sending messages too fast",
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ChatMessage {
+    Text(String),
+    Emote(Emote),
+}
+
+impl ChatMessage {
+    fn to_text(&self, language: Languages) -> String {
+        match self {
+            ChatMessage::Text(text) => text.chars().take(MAX_CHAT_LENGTH).collect(),
+            ChatMessage::Emote(emote) => emote.text().get(language).to_string(),
+        }
+    }
+}
+
+/// 自分または他のプレイヤーがチャットを送信したときに送られるイベントです
+#[derive(Event, Clone, Debug)]
+pub struct ChatEvent {
+    pub sender: Uuid,
+    pub message: ChatMessage,
+}
+
+/// 一定時間内のメッセージの数を制限します
+#[derive(Default)]
+struct RateLimiter {
+    times: VecDeque<f32>,
+}
+
+impl RateLimiter {
+    fn try_acquire(&mut self, now: f32) -> bool {
+        while let Some(time) = self.times.front() {
+            if CHAT_RATE_WINDOW < now - time {
+                self.times.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.times.len() < CHAT_RATE_LIMIT {
+            self.times.push_back(now);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ChatState {
+    sent: RateLimiter,
+    received: HashMap<Uuid, RateLimiter>,
+
+    /// 表示したメッセージと、その時刻
+    log: VecDeque<(f32, String)>,
+
+    /// 入力欄を開いたフレーム
+    opened_at: u32,
+}
+
+impl ChatState {
+    /// 送信できる場合は true を返します
+    /// 送信しすぎている場合は、ログに警告を表示して false を返します
+    pub fn try_send(&mut self, now: f32, language: Languages) -> bool {
+        if self.sent.try_acquire(now) {
+            true
+        } else {
+            self.push_log(now, TOO_FAST.get(language).to_string());
+            false
+        }
+    }
+
+    fn push_log(&mut self, now: f32, line: String) {
+        self.log.push_back((now, line));
+        while CHAT_LOG_LINES < self.log.len() {
+            self.log.pop_front();
+        }
+    }
+}
+
+/// チャットを他のプレイヤーに送信し、自分の画面にも表示します
+pub fn send_chat(
+    writer: &mut EventWriter<OutgoingMessage>,
+    chat_writer: &mut EventWriter<ChatEvent>,
+    online: bool,
+    sender: Uuid,
+    message: ChatMessage,
+) {
+    send_remote_message(
+        writer,
+        online,
+        &RemoteMessage::Chat {
+            sender,
+            message: message.clone(),
+        },
+    );
+    chat_writer.send(ChatEvent { sender, message });
+}
+
+#[derive(Component)]
+struct ChatLog;
+
+#[derive(Component)]
+struct ChatInput;
+
+/// 魔女の頭上に表示する吹き出しです
+#[derive(Component)]
+struct ChatBubble {
+    speaker: Entity,
+    text: String,
+    count: usize,
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct ChatBubbleText;
+
+fn spawn_chat(mut commands: Commands, assets: Res<GameAssets>, mut chat: ResMut<ChatState>) {
+    *chat = ChatState::default();
+
+    commands
+        .spawn((
+            Name::new("chat_root"),
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX + 1),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(8.0),
+                bottom: Val::Px(160.0),
+                width: Val::Px(480.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ChatLog,
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+            ));
+
+            parent.spawn((
+                ChatInput,
+                Node {
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                TextInput,
+                TextInputInactive(true),
+                TextInputTextFont(TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 16.0,
+                    ..default()
+                }),
+                TextInputTextColor(Color::WHITE.into()),
+                TextInputSettings {
+                    retain_on_submit: false,
+                    ..default()
+                },
+                TextInputValue::default(),
+            ));
+        });
+}
+
+/// オンラインのときに Enter キーでチャットの入力欄を開きます
+fn open_chat(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameMenuState>>,
+    mut next: ResMut<NextState<GameMenuState>>,
+    player_query: Query<&Player>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    frame_count: Res<FrameCount>,
+    mut chat: ResMut<ChatState>,
+) {
+    if *state.get() == GameMenuState::Closed
+        && keys.just_pressed(KeyCode::Enter)
+        && is_online_level(current.level, &config)
+        && !player_query.is_empty()
+    {
+        next.set(GameMenuState::ChatOpen);
+        chat.opened_at = frame_count.0;
+    }
+}
+
+/// 入力欄の表示とフォーカスを、メニューの状態に合わせます
+/// Escape キーで閉じた場合は、入力中の文字列を破棄します
+fn sync_chat_input(
+    state: Res<State<GameMenuState>>,
+    mut query: Query<(&mut Node, &mut TextInputInactive, &mut TextInputValue), With<ChatInput>>,
+) {
+    let open = *state.get() == GameMenuState::ChatOpen;
+    if let Ok((mut node, mut inactive, mut value)) = query.get_single_mut() {
+        node.display = if open { Display::Flex } else { Display::None };
+        if inactive.0 == open {
+            inactive.0 = !open;
+        }
+        if !open && !value.0.is_empty() {
+            value.0.clear();
+        }
+    }
+}
+
+fn submit_chat(
+    mut reader: EventReader<TextInputSubmitEvent>,
+    input_query: Query<Entity, With<ChatInput>>,
+    player_query: Query<&Actor, With<Player>>,
+    state: Res<State<GameMenuState>>,
+    mut next: ResMut<NextState<GameMenuState>>,
+    mut writer: EventWriter<OutgoingMessage>,
+    mut chat_writer: EventWriter<ChatEvent>,
+    mut chat: ResMut<ChatState>,
+    websocket: Res<WebSocketState>,
+    config: Res<GameConfig>,
+    frame_count: Res<FrameCount>,
+    time: Res<Time>,
+) {
+    for event in reader.read() {
+        if *state.get() != GameMenuState::ChatOpen || input_query.get(event.entity).is_err() {
+            continue;
+        }
+
+        let text: String = event.value.trim().chars().take(MAX_CHAT_LENGTH).collect();
+
+        // 入力欄を開いたときの Enter キーで空のまま送信されることがあるため、無視します
+        if text.is_empty() && frame_count.0.wrapping_sub(chat.opened_at) < 3 {
+            continue;
+        }
+
+        if let Ok(actor) = player_query.get_single() {
+            if !text.is_empty() && chat.try_send(time.elapsed_secs(), config.language) {
+                send_chat(
+                    &mut writer,
+                    &mut chat_writer,
+                    websocket.ready_state == ReadyState::OPEN,
+                    actor.uuid,
+                    ChatMessage::Text(text),
+                );
+            }
+        }
+        next.set(GameMenuState::Closed);
+    }
+}
+
+/// 届いたチャットをログに追加し、発言した魔女の頭上に吹き出しを表示します
+fn receive_chat(
+    mut commands: Commands,
+    mut reader: EventReader<ChatEvent>,
+    witches: Query<(Entity, &Actor, Option<&Player>, Option<&RemotePlayer>)>,
+    bubbles: Query<(Entity, &ChatBubble)>,
+    mut chat: ResMut<ChatState>,
+    assets: Res<GameAssets>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_secs();
+    for event in reader.read() {
+        let speaker = witches.iter().find(|(_, actor, player, remote)| {
+            actor.uuid == event.sender && (player.is_some() || remote.is_some())
+        });
+
+        let (speaker_entity, name, is_remote) = match speaker {
+            Some((entity, _, Some(player), _)) => (Some(entity), player.name.clone(), false),
+            Some((entity, _, None, Some(remote))) => (Some(entity), remote.name.clone(), true),
+            _ => (None, String::new(), true),
+        };
+
+        // 他のプレイヤーが送信しすぎている場合は表示しません
+        if is_remote
+            && !chat
+                .received
+                .entry(event.sender)
+                .or_default()
+                .try_acquire(now)
+        {
+            continue;
+        }
+
+        let text = event.message.to_text(config.language);
+        let name = if name.is_empty() {
+            "(anonymous)".to_string()
+        } else {
+            name
+        };
+        chat.push_log(now, format!("{}: {}", name, text));
+
+        if let Some(speaker_entity) = speaker_entity {
+            for (bubble_entity, bubble) in bubbles.iter() {
+                if bubble.speaker == speaker_entity {
+                    commands.entity(bubble_entity).despawn_recursive();
+                }
+            }
+
+            commands
+                .spawn((
+                    Name::new("chat_bubble"),
+                    StateScoped(GameState::InGame),
+                    ChatBubble {
+                        speaker: speaker_entity,
+                        text,
+                        count: 0,
+                        remaining: CHAT_BUBBLE_SECONDS,
+                    },
+                    GlobalZIndex(HUD_Z_INDEX - 1),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(CHAT_BUBBLE_WIDTH),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        ChatBubbleText,
+                        Node {
+                            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        BackgroundColor(Color::hsva(0.0, 0.0, 1.0, 0.9)),
+                        Text::new(""),
+                        TextColor(Color::hsva(0.0, 0.0, 0.1, 1.0)),
+                        TextFont {
+                            font: assets.dotgothic.clone(),
+                            font_size: 16.0,
+                            ..default()
+                        },
+                    ));
+                });
+        }
+    }
+}
+
+/// 吹き出しを魔女の頭上に移動し、文字を1文字ずつ表示します
+fn update_chat_bubbles(
+    mut commands: Commands,
+    mut bubbles: Query<(Entity, &mut ChatBubble, &mut Node, &Children)>,
+    mut text_query: Query<&mut Text, With<ChatBubbleText>>,
+    speakers: Query<&GlobalTransform>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    time: Res<Time>,
+) {
+    for (entity, mut bubble, mut node, children) in bubbles.iter_mut() {
+        let speaker = match speakers.get(bubble.speaker) {
+            Ok(speaker) => speaker,
+            Err(_) => {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        };
+
+        if let Ok((camera, camera_transform)) = camera_query.get_single() {
+            if let Ok(p) = camera.world_to_viewport(
+                camera_transform,
+                speaker.translation() + Vec3::new(0.0, 28.0, 0.0),
+            ) {
+                node.left = Val::Px(p.x - CHAT_BUBBLE_WIDTH * 0.5);
+                node.bottom = Val::Auto;
+                node.top = Val::Px(p.y - 32.0);
+            }
+        }
+
+        let (str, typing) = typing_text(&bubble.text, bubble.count);
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                if text.0 != str {
+                    text.0 = str.clone();
+                }
+            }
+        }
+
+        if typing {
+            bubble.count += 1;
+        } else {
+            bubble.remaining -= time.delta_secs();
+            if bubble.remaining <= 0.0 {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn update_chat_log(
+    mut query: Query<&mut Text, With<ChatLog>>,
+    chat: Res<ChatState>,
+    state: Res<State<GameMenuState>>,
+    time: Res<Time>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        let open = *state.get() == GameMenuState::ChatOpen;
+        let now = time.elapsed_secs();
+        let lines: Vec<&str> = chat
+            .log
+            .iter()
+            .filter(|(t, _)| open || now - t < CHAT_LOG_SECONDS)
+            .map(|(_, line)| line.as_str())
+            .collect();
+        let log = lines.join("\n");
+        if text.0 != log {
+            text.0 = log;
+        }
+    }
+}
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChatEvent>();
+        app.init_resource::<ChatState>();
+        app.add_systems(OnEnter(GameState::InGame), spawn_chat);
+        app.add_systems(
+            Update,
+            (
+                open_chat,
+                sync_chat_input,
+                submit_chat,
+                receive_chat,
+                update_chat_bubbles,
+                update_chat_log,
+            )
+                .chain()
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::asset::GameAssets;
+use crate::config::GameConfig;
+use crate::constant::HUD_Z_INDEX;
+use crate::controller::connection::{Connection, ConnectionStatus, MAX_CONNECT_ATTEMPTS};
+use crate::controller::remote::is_online_level;
+use crate::language::Dict;
+use crate::level::CurrentLevel;
+use crate::states::GameState;
+use bevy::{core::FrameCount, prelude::*};
+
+/// 拠点に戻ったあと、説明を表示し続けるフレーム数
+const FALLBACK_NOTICE_FRAMES: u32 = 600;
+
+const CONNECTING: Dict = Dict {
+    ja: "サーバーに接続しています...",
+    en: "Connecting to server...",
+};
+
+const RECONNECTING: Dict = Dict {
+    ja: "接続が切れました。再接続しています...",
+    en: "Connection lost. Reconnecting...",
+};
+
+const OFFLINE: Dict = Dict {
+    ja: "オフライン",
+    en: "Offline",
+};
+
+const FALLBACK: Dict = Dict {
+    ja: "サーバーに接続できなかったため、拠点に戻りました",
+    en: "Could not reach the server, so you were returned home",
+};
+
+/// 接続中・再接続中・オフラインを知らせるバナーです
+#[derive(Component)]
+struct ConnectionBanner;
+
+fn spawn_connection_banner(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Name::new("connection_banner"),
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX + 1),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ConnectionBanner,
+                Node {
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(4.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn update_connection_banner(
+    mut query: Query<(&mut Node, &mut Text), With<ConnectionBanner>>,
+    connection: Res<Connection>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    frame_count: Res<FrameCount>,
+) {
+    if let Ok((mut node, mut text)) = query.get_single_mut() {
+        let message = if is_online_level(current.level, &config) {
+            match connection.status {
+                ConnectionStatus::Connecting if connection.attempts == 0 => {
+                    Some(CONNECTING.get(config.language).to_string())
+                }
+                ConnectionStatus::Connecting | ConnectionStatus::Reconnecting => Some(format!(
+                    "{} ({}/{})",
+                    RECONNECTING.get(config.language),
+                    connection.attempts,
+                    MAX_CONNECT_ATTEMPTS
+                )),
+                ConnectionStatus::Offline | ConnectionStatus::Failed => {
+                    Some(OFFLINE.get(config.language).to_string())
+                }
+                ConnectionStatus::Open => None,
+            }
+        } else {
+            match connection.fallback_frame {
+                Some(frame) if frame_count.0.wrapping_sub(frame.0) < FALLBACK_NOTICE_FRAMES => {
+                    Some(FALLBACK.get(config.language).to_string())
+                }
+                _ => None,
+            }
+        };
+
+        match message {
+            Some(message) => {
+                node.display = Display::Flex;
+                if text.0 != message {
+                    text.0 = message;
+                }
+            }
+            None => {
+                node.display = Display::None;
+            }
+        }
+    }
+}
+
+pub struct ConnectionBannerPlugin;
+
+impl Plugin for ConnectionBannerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_connection_banner);
+        app.add_systems(
+            Update,
+            update_connection_banner.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use crate::asset::GameAssets;
+use crate::config::GameConfig;
+use crate::constant::OVERLAY_Z_INDEX;
+use crate::controller::network_simulator::NetworkConditions;
+use crate::controller::remote::{is_online_level, NetworkStats, RemotePlayer};
+use crate::entity::actor::Actor;
+use crate::level::CurrentLevel;
+use crate::states::GameState;
+use bevy::prelude::*;
+
+/// デバッグ用に通信量などを表示するオーバーレイです
+/// debug フィーチャーが無効のときは表示されません
+#[derive(Component)]
+struct DebugOverlayText;
+
+fn spawn_debug_overlay(mut commands: Commands, assets: Res<GameAssets>) {
+    commands.spawn((
+        Name::new("debug_overlay"),
+        DebugOverlayText,
+        StateScoped(GameState::InGame),
+        GlobalZIndex(OVERLAY_Z_INDEX),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            right: Val::Px(8.0),
+            display: if cfg!(feature = "debug") {
+                Display::Flex
+            } else {
+                Display::None
+            },
+            ..default()
+        },
+        Text::new(""),
+        TextColor(Color::WHITE),
+        TextFont {
+            font: assets.dotgothic.clone(),
+            font_size: 16.0,
+            ..default()
+        },
+    ));
+}
+
+fn update_debug_overlay(
+    mut query: Query<&mut Text, With<DebugOverlayText>>,
+    remotes: Query<(&Actor, &RemotePlayer)>,
+    stats: Res<NetworkStats>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    conditions: Res<NetworkConditions>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        if !is_online_level(current.level, &config) {
+            text.0 = String::new();
+            return;
+        }
+
+        let mut lines = vec![format!("up: {} B/s", stats.sent_per_second)];
+        for (actor, remote) in remotes.iter() {
+            let received = stats
+                .received_per_second
+                .get(&actor.uuid)
+                .cloned()
+                .unwrap_or(0);
+            let name = if remote.name.is_empty() {
+                "(anonymous)"
+            } else {
+                remote.name.as_str()
+            };
+            lines.push(format!("down {}: {} B/s", name, received));
+        }
+        if conditions.is_active() {
+            lines.push(format!(
+                "sim: {}ms ±{}ms loss {:.0}% dup {:.0}% reorder {:.0}%",
+                conditions.latency_ms,
+                conditions.jitter_ms,
+                conditions.loss * 100.0,
+                conditions.duplication * 100.0,
+                conditions.reorder * 100.0
+            ));
+        }
+        text.0 = lines.join("\n");
+    }
+}
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_debug_overlay);
+        app.add_systems(
+            Update,
+            update_debug_overlay.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
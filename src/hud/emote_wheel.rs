@@ -0,0 +1,234 @@
+use crate::asset::GameAssets;
+use crate::config::GameConfig;
+use crate::constant::HUD_Z_INDEX;
+use crate::controller::network_simulator::OutgoingMessage;
+use crate::controller::player::Player;
+use crate::controller::remote::is_online_level;
+use crate::entity::actor::Actor;
+use crate::hud::chat::{send_chat, ChatEvent, ChatMessage, ChatState};
+use crate::language::Dict;
+use crate::level::CurrentLevel;
+use crate::states::{GameMenuState, GameState};
+use bevy::prelude::*;
+use bevy_simple_websocket::{ReadyState, WebSocketState};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// 項目を並べる円の半径
+const WHEEL_RADIUS: f32 = 120.0;
+
+/// カーソルが中心からこの距離以上離れているときだけ項目を選択します
+const WHEEL_DEAD_ZONE: f32 = 40.0;
+
+const ITEM_WIDTH: f32 = 120.0;
+
+const ITEM_HEIGHT: f32 = 32.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Emote {
+    Hello,
+    Thanks,
+    Sorry,
+    Nice,
+    Help,
+    ThisWay,
+    GoodGame,
+    Laugh,
+}
+
+/// エモートホイールに並べる順番です
+/// 右から反時計回りに並びます
+const EMOTES: [Emote; 8] = [
+    Emote::Hello,
+    Emote::Thanks,
+    Emote::ThisWay,
+    Emote::Help,
+    Emote::Sorry,
+    Emote::Laugh,
+    Emote::GoodGame,
+    Emote::Nice,
+];
+
+impl Emote {
+    pub fn text(&self) -> Dict {
+        match self {
+            Emote::Hello => Dict {
+                ja: "こんにちは！",
+                en: "Hello!",
+            },
+            Emote::Thanks => Dict {
+                ja: "ありがとう！",
+                en: "Thanks!",
+            },
+            Emote::Sorry => Dict {
+                ja: "ごめん！",
+                en: "Sorry!",
+            },
+            Emote::Nice => Dict {
+                ja: "ナイス！",
+                en: "Nice!",
+            },
+            Emote::Help => Dict {
+                ja: "助けて！",
+                en: "Help!",
+            },
+            Emote::ThisWay => Dict {
+                ja: "こっち！",
+                en: "This way!",
+            },
+            Emote::GoodGame => Dict {
+                ja: "おつかれ！",
+                en: "GG!",
+            },
+            Emote::Laugh => Dict {
+                ja: "ｗｗｗ",
+                en: "lol",
+            },
+        }
+    }
+}
+
+/// E キーを押している間に表示するエモートの選択肢です
+/// カーソルの方向で選び、キーを離すと送信します
+#[derive(Component)]
+struct EmoteWheelRoot;
+
+#[derive(Component)]
+struct EmoteWheelItem(usize);
+
+#[derive(Component)]
+struct EmoteWheelItemText(usize);
+
+fn spawn_emote_wheel(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Name::new("emote_wheel"),
+            EmoteWheelRoot,
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX + 1),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            // 画面の中心に置いた大きさのない要素を基準に、項目を円形に並べます
+            parent.spawn(Node::default()).with_children(|parent| {
+                for (i, _) in EMOTES.iter().enumerate() {
+                    let angle = TAU * i as f32 / EMOTES.len() as f32;
+                    parent
+                        .spawn((
+                            EmoteWheelItem(i),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(WHEEL_RADIUS * angle.cos() - ITEM_WIDTH * 0.5),
+                                top: Val::Px(-WHEEL_RADIUS * angle.sin() - ITEM_HEIGHT * 0.5),
+                                width: Val::Px(ITEM_WIDTH),
+                                height: Val::Px(ITEM_HEIGHT),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                        ))
+                        .with_child((
+                            EmoteWheelItemText(i),
+                            Text::new(""),
+                            TextColor(Color::WHITE),
+                            TextFont {
+                                font: assets.dotgothic.clone(),
+                                font_size: 16.0,
+                                ..default()
+                            },
+                        ));
+                }
+            });
+        });
+}
+
+/// カーソルの方向にある項目の番号を返します
+fn selected_emote(window: &Window) -> Option<usize> {
+    let cursor = window.cursor_position()?;
+    let center = Vec2::new(window.width(), window.height()) * 0.5;
+    let offset = Vec2::new(cursor.x - center.x, center.y - cursor.y);
+    if offset.length() < WHEEL_DEAD_ZONE {
+        return None;
+    }
+    let step = TAU / EMOTES.len() as f32;
+    let index = (offset.to_angle().rem_euclid(TAU) / step).round() as usize % EMOTES.len();
+    Some(index)
+}
+
+fn update_emote_wheel(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameMenuState>>,
+    window_query: Query<&Window>,
+    player_query: Query<&Actor, With<Player>>,
+    mut root_query: Query<&mut Node, With<EmoteWheelRoot>>,
+    mut item_query: Query<(&EmoteWheelItem, &mut BackgroundColor)>,
+    mut text_query: Query<(&EmoteWheelItemText, &mut Text)>,
+    mut writer: EventWriter<OutgoingMessage>,
+    mut chat_writer: EventWriter<ChatEvent>,
+    mut chat: ResMut<ChatState>,
+    websocket: Res<WebSocketState>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let available = *state.get() == GameMenuState::Closed
+        && is_online_level(current.level, &config)
+        && !player_query.is_empty();
+    let open = available && keys.pressed(KeyCode::KeyE);
+    let selected = window_query.get_single().ok().and_then(selected_emote);
+
+    if let Ok(mut root) = root_query.get_single_mut() {
+        root.display = if open { Display::Flex } else { Display::None };
+    }
+
+    if open {
+        for (item, mut background) in item_query.iter_mut() {
+            background.0 = if Some(item.0) == selected {
+                Color::srgba(0.4, 0.2, 0.6, 0.8)
+            } else {
+                Color::srgba(0.0, 0.0, 0.0, 0.6)
+            };
+        }
+        for (item, mut text) in text_query.iter_mut() {
+            let label = EMOTES[item.0].text().get(config.language);
+            if text.0 != label {
+                text.0 = label.to_string();
+            }
+        }
+    }
+
+    if available && keys.just_released(KeyCode::KeyE) {
+        if let (Some(index), Ok(actor)) = (selected, player_query.get_single()) {
+            if chat.try_send(time.elapsed_secs(), config.language) {
+                send_chat(
+                    &mut writer,
+                    &mut chat_writer,
+                    websocket.ready_state == ReadyState::OPEN,
+                    actor.uuid,
+                    ChatMessage::Emote(EMOTES[index]),
+                );
+            }
+        }
+    }
+}
+
+pub struct EmoteWheelPlugin;
+
+impl Plugin for EmoteWheelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_emote_wheel);
+        app.add_systems(
+            Update,
+            update_emote_wheel.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
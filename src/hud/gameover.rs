@@ -3,6 +3,7 @@ use bevy::prelude::*;
 use crate::{controller::player::Player, states::GameState};
 
 use super::overlay::OverlayEvent;
+use super::spectator::Spectator;
 
 #[derive(Resource, Default)]
 struct GameOver {
@@ -17,8 +18,9 @@ fn gameover(
     player_query: Query<&Player>,
     mut gameover: ResMut<GameOver>,
     mut overlay_event_writer: EventWriter<OverlayEvent>,
+    spectator: Res<Spectator>,
 ) {
-    if player_query.is_empty() {
+    if player_query.is_empty() && !spectator.is_spectating() {
         if gameover.animation == 300 {
             overlay_event_writer.send(OverlayEvent::Close(GameState::MainMenu));
         }
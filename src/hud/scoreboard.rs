@@ -0,0 +1,244 @@
+use crate::asset::GameAssets;
+use crate::config::GameConfig;
+use crate::constant::HUD_Z_INDEX;
+use crate::controller::arena_match::{ArenaMatch, MatchMode, MatchPhase, DEATHMATCH_KILLS};
+use crate::language::Dict;
+use crate::level::{CurrentLevel, GameLevel};
+use crate::states::{GameMenuState, GameState};
+use bevy::prelude::*;
+use uuid::Uuid;
+
+const WAITING: Dict = Dict {
+    ja: "対戦相手を待っています",
+    en: "Waiting for opponents",
+};
+
+const ROUND: Dict = Dict {
+    ja: "ラウンド",
+    en: "Round",
+};
+
+const KILLS: Dict = Dict {
+    ja: "キル",
+    en: "Kills",
+};
+
+const DEATHS: Dict = Dict {
+    ja: "デス",
+    en: "Deaths",
+};
+
+const DAMAGE: Dict = Dict {
+    ja: "ダメージ",
+    en: "Damage",
+};
+
+const WINNER: Dict = Dict {
+    ja: "勝者",
+    en: "Winner",
+};
+
+const NEXT_ROUND: Dict = Dict {
+    ja: "次のラウンドまで",
+    en: "Next round in",
+};
+
+/// 画面上部に表示する試合の状況です
+#[derive(Component)]
+struct MatchStatusLabel;
+
+/// 試合の終了時、または Q キーを押している間に表示する成績表です
+#[derive(Component)]
+struct ScoreboardRoot;
+
+#[derive(Component)]
+struct ScoreboardText;
+
+fn spawn_scoreboard(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Name::new("match_status"),
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MatchStatusLabel,
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+
+    commands
+        .spawn((
+            Name::new("scoreboard_root"),
+            ScoreboardRoot,
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX + 1),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ScoreboardText,
+                Node {
+                    padding: UiRect::all(Val::Px(24.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn display_name(arena: &ArenaMatch, uuid: &Uuid) -> String {
+    match arena.names.get(uuid) {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => "(anonymous)".to_string(),
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds.ceil() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn update_match_status(
+    mut query: Query<&mut Text, With<MatchStatusLabel>>,
+    arena: Res<ArenaMatch>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+) {
+    if let Ok(mut text) = query.get_single_mut() {
+        let status = if current.level != Some(GameLevel::MultiPlayArena) {
+            String::new()
+        } else if arena.phase == MatchPhase::Waiting {
+            WAITING.get(config.language).to_string()
+        } else {
+            let rule = match arena.mode {
+                MatchMode::Deathmatch => {
+                    format!("{} {}", DEATHMATCH_KILLS, KILLS.get(config.language))
+                }
+                MatchMode::LastWitchStanding => String::new(),
+                MatchMode::TimedRound => format_time(arena.remaining),
+            };
+            format!(
+                "{} {} - {}  {}",
+                ROUND.get(config.language),
+                arena.round,
+                arena.mode.name().get(config.language),
+                rule
+            )
+        };
+        if text.0 != status {
+            text.0 = status;
+        }
+    }
+}
+
+fn update_scoreboard(
+    mut root_query: Query<&mut Node, With<ScoreboardRoot>>,
+    mut text_query: Query<&mut Text, With<ScoreboardText>>,
+    arena: Res<ArenaMatch>,
+    current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    keys: Res<ButtonInput<KeyCode>>,
+    menu: Res<State<GameMenuState>>,
+) {
+    let holding = *menu.get() != GameMenuState::ChatOpen && keys.pressed(KeyCode::KeyQ);
+    let visible = current.level == Some(GameLevel::MultiPlayArena)
+        && arena.phase != MatchPhase::Waiting
+        && (arena.phase == MatchPhase::Finished || holding);
+
+    if let Ok(mut root) = root_query.get_single_mut() {
+        root.display = if visible {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    if !visible {
+        return;
+    }
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        let ranking = arena.ranking();
+        let mut lines = Vec::new();
+
+        if arena.phase == MatchPhase::Finished {
+            if let Some((winner, _)) = ranking.first() {
+                lines.push(format!(
+                    "{}: {}",
+                    WINNER.get(config.language),
+                    display_name(&arena, winner)
+                ));
+            }
+            lines.push(format!(
+                "{} {}",
+                NEXT_ROUND.get(config.language),
+                format_time(arena.remaining)
+            ));
+            lines.push(String::new());
+        }
+
+        lines.push(format!(
+            "{:<16} {:>6} {:>6} {:>8}",
+            "",
+            KILLS.get(config.language),
+            DEATHS.get(config.language),
+            DAMAGE.get(config.language)
+        ));
+        for (uuid, score) in ranking.iter() {
+            lines.push(format!(
+                "{:<16} {:>6} {:>6} {:>8}",
+                display_name(&arena, uuid),
+                score.kills,
+                score.deaths,
+                score.damage
+            ));
+        }
+
+        let scoreboard = lines.join("\n");
+        if text.0 != scoreboard {
+            text.0 = scoreboard;
+        }
+    }
+}
+
+pub struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::InGame), spawn_scoreboard);
+        app.add_systems(
+            Update,
+            (update_match_status, update_scoreboard).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use crate::asset::GameAssets;
+use crate::camera::GameCamera;
+use crate::config::GameConfig;
+use crate::constant::{CAMERA_SPEED, HUD_Z_INDEX, TILE_HALF, TILE_SIZE};
+use crate::controller::arena_match::{ArenaMatch, MatchPhase};
+use crate::controller::player::Player;
+use crate::controller::remote::{PositionSync, RemotePlayer};
+use crate::entity::actor::{Actor, ActorGroup};
+use crate::entity::life::Life;
+use crate::entity::witch::spawn_witch;
+use crate::hud::life_bar::LifeBarResource;
+use crate::hud::overlay::OverlayEvent;
+use crate::language::Dict;
+use crate::level::{CurrentLevel, GameLevel};
+use crate::player_state::PlayerState;
+use crate::random::random_select;
+use crate::se::{SEEvent, SE};
+use crate::states::{GameMenuState, GameState};
+use crate::ui::menu_button::menu_button;
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+use bevy_rapier2d::plugin::PhysicsSet;
+use uuid::Uuid;
+
+/// 試合中に倒れてから自動的に復活するまでの時間(秒)
+const AUTO_RESPAWN_SECONDS: f32 = 3.0;
+
+const SPECTATING: Dict = Dict {
+    ja: "観戦中",
+    en: "Spectating",
+};
+
+const NO_PLAYERS: Dict = Dict {
+    ja: "観戦できるプレイヤーがいません",
+    en: "No players to watch",
+};
+
+const PREV: Dict = Dict {
+    ja: "前へ",
+    en: "Prev",
+};
+
+const NEXT: Dict = Dict {
+    ja: "次へ",
+    en: "Next",
+};
+
+const RESPAWN: Dict = Dict {
+    ja: "復活",
+    en: "Respawn",
+};
+
+const LEAVE: Dict = Dict {
+    ja: "退出",
+    en: "Leave",
+};
+
+/// 倒れた魔女の状態です
+/// 復活や退出のときにこの状態から魔女を生成します
+struct FallenWitch {
+    uuid: Uuid,
+    golds: i32,
+    position: Vec2,
+    state: PlayerState,
+}
+
+/// アリーナで倒れたあと、他のプレイヤーを観戦している状態です
+/// 観戦中もサーバーとの接続は維持されます
+#[derive(Resource, Default)]
+pub struct Spectator {
+    fallen: Option<FallenWitch>,
+
+    /// 観戦しているリモートプレイヤーのUUID
+    target: Option<Uuid>,
+}
+
+impl Spectator {
+    pub fn begin(&mut self, uuid: Uuid, golds: i32, position: Vec2, state: PlayerState) {
+        self.fallen = Some(FallenWitch {
+            uuid,
+            golds,
+            position,
+            state,
+        });
+        self.target = None;
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.fallen.is_some()
+    }
+
+    /// 観戦対象を UUID 順に step だけ切り替えます
+    fn cycle(&mut self, remotes: &Query<&Actor, With<RemotePlayer>>, step: i32) {
+        let mut uuids: Vec<Uuid> = remotes.iter().map(|actor| actor.uuid).collect();
+        if uuids.is_empty() {
+            self.target = None;
+            return;
+        }
+        uuids.sort();
+        let len = uuids.len() as i32;
+        let index = match self.target.and_then(|t| uuids.iter().position(|u| *u == t)) {
+            Some(index) => (index as i32 + step).rem_euclid(len),
+            None => 0,
+        };
+        self.target = Some(uuids[index as usize]);
+    }
+}
+
+#[derive(Resource)]
+struct SpectatorButtons {
+    prev: SystemId,
+    next: SystemId,
+    respawn: SystemId,
+    leave: SystemId,
+}
+
+impl FromWorld for SpectatorButtons {
+    fn from_world(world: &mut World) -> Self {
+        SpectatorButtons {
+            prev: world.register_system(prev),
+            next: world.register_system(next),
+            respawn: world.register_system(respawn),
+            leave: world.register_system(leave),
+        }
+    }
+}
+
+#[derive(Component)]
+struct SpectatorRoot;
+
+#[derive(Component)]
+struct SpectatorLabel;
+
+fn prev(
+    mut spectator: ResMut<Spectator>,
+    remotes: Query<&Actor, With<RemotePlayer>>,
+    mut writer: EventWriter<SEEvent>,
+) {
+    spectator.cycle(&remotes, -1);
+    writer.send(SEEvent::new(SE::Click));
+}
+
+fn next(
+    mut spectator: ResMut<Spectator>,
+    remotes: Query<&Actor, With<RemotePlayer>>,
+    mut writer: EventWriter<SEEvent>,
+) {
+    spectator.cycle(&remotes, 1);
+    writer.send(SEEvent::new(SE::Click));
+}
+
+/// アリーナのいずれかの入り口で、体力を回復して復活します
+/// 同じUUIDのまま復活するため、他のプレイヤーからは同じ魔女として扱われます
+fn respawn(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    life_bar_res: Res<LifeBarResource>,
+    current: Res<CurrentLevel>,
+    mut spectator: ResMut<Spectator>,
+    mut sync: ResMut<PositionSync>,
+    mut writer: EventWriter<SEEvent>,
+    arena: Res<ArenaMatch>,
+) {
+    // サバイバルの試合中は、次のラウンドまで復活できません
+    if !arena.can_respawn() {
+        writer.send(SEEvent::new(SE::NoDamage));
+        return;
+    }
+
+    if let Some(fallen) = spectator.fallen.take() {
+        let position = match current.chunk {
+            Some(ref chunk) if !chunk.entry_points.is_empty() => {
+                let entry_point = random_select(&chunk.entry_points);
+                Vec2::new(
+                    TILE_SIZE * entry_point.x + TILE_HALF,
+                    -TILE_SIZE * entry_point.y - TILE_HALF,
+                )
+            }
+            _ => fallen.position,
+        };
+
+        let state = fallen.state;
+        spawn_witch(
+            &mut commands,
+            &assets,
+            position,
+            0.0,
+            fallen.uuid,
+            None,
+            state.max_life,
+            state.max_life,
+            &life_bar_res,
+            false,
+            3.0,
+            fallen.golds,
+            state.wands,
+            state.inventory,
+            state.equipments,
+            Player {
+                name: state.name,
+                debt: state.debt,
+            },
+            ActorGroup::Player,
+        );
+
+        // 他のプレイヤーは倒れた時点でこの魔女を削除しているため、キーフレームから送り直します
+        *sync = PositionSync::default();
+
+        spectator.target = None;
+        writer.send(SEEvent::new(SE::Click));
+    }
+}
+
+/// アリーナを出て拠点に戻ります
+fn leave(
+    mut spectator: ResMut<Spectator>,
+    mut current: ResMut<CurrentLevel>,
+    mut overlay_writer: EventWriter<OverlayEvent>,
+    mut writer: EventWriter<SEEvent>,
+) {
+    if let Some(fallen) = spectator.fallen.take() {
+        let mut state = fallen.state;
+        state.life = state.max_life;
+        current.next_level = GameLevel::Level(0);
+        current.next_state = state;
+        overlay_writer.send(OverlayEvent::Close(GameState::Warp));
+        writer.send(SEEvent::new(SE::Click));
+    }
+}
+
+fn setup_spectator(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    buttons: Res<SpectatorButtons>,
+    mut spectator: ResMut<Spectator>,
+) {
+    *spectator = Spectator::default();
+
+    commands
+        .spawn((
+            Name::new("spectator_root"),
+            SpectatorRoot,
+            StateScoped(GameState::InGame),
+            GlobalZIndex(HUD_Z_INDEX + 1),
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpectatorLabel,
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+            ));
+
+            parent
+                .spawn((
+                    Node {
+                        column_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                ))
+                .with_children(|parent| {
+                    menu_button(parent, &assets, buttons.prev, 160.0, 48.0, PREV);
+                    menu_button(parent, &assets, buttons.next, 160.0, 48.0, NEXT);
+                    menu_button(parent, &assets, buttons.respawn, 160.0, 48.0, RESPAWN);
+                    menu_button(parent, &assets, buttons.leave, 160.0, 48.0, LEAVE);
+                });
+        });
+}
+
+/// 観戦対象がいなくなった場合は次のプレイヤーに切り替えます
+/// A / D キーでも観戦対象を切り替えられます
+/// チャットの入力中はキーで切り替えません
+fn update_spectator_target(
+    mut spectator: ResMut<Spectator>,
+    remotes: Query<&Actor, With<RemotePlayer>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    menu: Res<State<GameMenuState>>,
+) {
+    if !spectator.is_spectating() {
+        return;
+    }
+
+    let chatting = *menu.get() == GameMenuState::ChatOpen;
+
+    if !chatting && keys.just_pressed(KeyCode::KeyA) {
+        spectator.cycle(&remotes, -1);
+    } else if !chatting && keys.just_pressed(KeyCode::KeyD) {
+        spectator.cycle(&remotes, 1);
+    } else if !remotes
+        .iter()
+        .any(|actor| Some(actor.uuid) == spectator.target)
+    {
+        spectator.cycle(&remotes, 0);
+    }
+}
+
+/// 試合中は倒れてから一定時間で自動的に復活します
+/// 新しいラウンドが始まったときはすぐに復活します
+fn auto_respawn(
+    mut commands: Commands,
+    spectator: Res<Spectator>,
+    arena: Res<ArenaMatch>,
+    buttons: Res<SpectatorButtons>,
+    time: Res<Time>,
+    mut waiting: Local<f32>,
+    mut round: Local<u32>,
+) {
+    let new_round = *round != arena.round;
+    *round = arena.round;
+
+    if !spectator.is_spectating() {
+        *waiting = 0.0;
+        return;
+    }
+
+    *waiting += time.delta_secs();
+    if arena.phase == MatchPhase::Playing
+        && arena.can_respawn()
+        && (new_round || AUTO_RESPAWN_SECONDS <= *waiting)
+    {
+        commands.run_system(buttons.respawn);
+        *waiting = 0.0;
+    }
+}
+
+fn follow_spectator_target(
+    spectator: Res<Spectator>,
+    remotes: Query<(&Actor, &Transform), With<RemotePlayer>>,
+    mut camera_query: Query<
+        (&mut Transform, &mut GameCamera),
+        (With<Camera2d>, Without<RemotePlayer>),
+    >,
+) {
+    if !spectator.is_spectating() {
+        return;
+    }
+
+    if let Some((_, target)) = remotes
+        .iter()
+        .find(|(actor, _)| Some(actor.uuid) == spectator.target)
+    {
+        if let Ok((mut camera, mut game_camera)) = camera_query.get_single_mut() {
+            game_camera.x += (target.translation.x - game_camera.x) * CAMERA_SPEED;
+            game_camera.y += (target.translation.y - game_camera.y) * CAMERA_SPEED;
+            camera.translation.x = game_camera.x;
+            camera.translation.y = game_camera.y;
+        }
+    }
+}
+
+fn update_spectator_ui(
+    spectator: Res<Spectator>,
+    remotes: Query<(&Actor, &Life, &RemotePlayer)>,
+    mut root_query: Query<&mut Node, With<SpectatorRoot>>,
+    mut label_query: Query<&mut Text, With<SpectatorLabel>>,
+    config: Res<GameConfig>,
+) {
+    if let Ok(mut root) = root_query.get_single_mut() {
+        root.display = if spectator.is_spectating() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    if let Ok(mut label) = label_query.get_single_mut() {
+        let target = remotes
+            .iter()
+            .find(|(actor, _, _)| Some(actor.uuid) == spectator.target);
+        label.0 = match target {
+            Some((_, life, remote)) => format!(
+                "{}: {} ({}/{})",
+                SPECTATING.get(config.language),
+                if remote.name.is_empty() {
+                    "(anonymous)"
+                } else {
+                    remote.name.as_str()
+                },
+                life.life.max(0),
+                life.max_life
+            ),
+            None => NO_PLAYERS.get(config.language).to_string(),
+        };
+    }
+}
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Spectator>();
+        app.init_resource::<SpectatorButtons>();
+        app.add_systems(OnEnter(GameState::InGame), setup_spectator);
+        app.add_systems(
+            Update,
+            (update_spectator_target, update_spectator_ui, auto_respawn)
+                .run_if(in_state(GameState::InGame)),
+        );
+        app.add_systems(
+            FixedUpdate,
+            follow_spectator_target
+                .run_if(in_state(GameState::InGame))
+                .before(PhysicsSet::SyncBackend),
+        );
+    }
+}
@@ -1,14 +1,34 @@
 use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     constant::{MAX_ITEMS_IN_INVENTORY, MAX_ITEMS_IN_INVENTORY_COLUMN, MAX_ITEMS_IN_INVENTORY_ROW},
     inventory_item::InventoryItemType,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Reflect)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Reflect, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub item_type: InventoryItemType,
     pub price: u32,
+
+    /// 重ねて持っている個数
+    /// 重ねられないアイテムは常に1です
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+impl InventoryItem {
+    /// このアイテムを other に重ねられるかどうかを返します
+    /// 種類と価格が同じで、重ねられる種類のアイテムである必要があります
+    pub fn is_stackable_with(&self, other: &InventoryItem) -> bool {
+        1 < self.item_type.get_max_stack()
+            && self.item_type == other.item_type
+            && self.price == other.price
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Reflect)]
@@ -29,6 +49,15 @@ impl Inventory {
         inventory[index] = item;
     }
 
+    /// そのスロットにアイテムがあるか、左にある幅のあるアイテムがはみ出しているかを返します
+    fn is_occupied(&self, index: usize) -> bool {
+        let row_start = index - index % MAX_ITEMS_IN_INVENTORY_ROW;
+        (row_start..=index).any(|i| match self.0[i] {
+            Some(item) => index < i + item.item_type.get_width(),
+            None => false,
+        })
+    }
+
     pub fn is_settable(&self, index: usize, item: InventoryItem) -> bool {
         let x = index % MAX_ITEMS_IN_INVENTORY_ROW;
         let y = index / MAX_ITEMS_IN_INVENTORY_ROW;
@@ -39,7 +68,7 @@ impl Inventory {
             if MAX_ITEMS_IN_INVENTORY_ROW <= x + i {
                 return false;
             }
-            if self.0[index + i].is_some() {
+            if self.is_occupied(index + i) {
                 return false;
             }
         }
@@ -53,58 +82,89 @@ impl Inventory {
         return true;
     }
 
+    /// アイテムをインベントリの空いている位置に追加します
+    /// 重ねられるアイテムは、まず同じ種類で同じ価格のアイテムに上限まで重ね、
+    /// 残りを空いている位置に置きます
+    /// すべてを追加できない場合はインベントリを変更せずに false を返します
     pub fn insert(&mut self, item: InventoryItem) -> bool {
-        let Inventory(ref mut inventory) = *self;
-        let mut i = 0;
-        while i < MAX_ITEMS_IN_INVENTORY {
-            match inventory[i] {
-                None => {
-                    inventory[i] = Some(item);
-                    return true;
+        let mut inventory = self.clone();
+        let max_stack = item.item_type.get_max_stack();
+        let mut rest = item.count;
+
+        for slot in inventory.0.iter_mut() {
+            if rest == 0 {
+                break;
+            }
+            if let Some(ref mut stack) = slot {
+                if item.is_stackable_with(stack) && stack.count < max_stack {
+                    let moved = rest.min(max_stack - stack.count);
+                    stack.count += moved;
+                    rest -= moved;
                 }
-                Some(item) => {
-                    i += item.item_type.get_width();
+            }
+        }
+
+        while 0 < rest {
+            let count = rest.min(max_stack);
+            let placed = InventoryItem { count, ..item };
+            match (0..MAX_ITEMS_IN_INVENTORY).find(|i| inventory.is_settable(*i, placed)) {
+                Some(index) => {
+                    inventory.set(index, Some(placed));
+                    rest -= count;
                 }
+                None => return false,
             }
         }
-        return false;
+
+        *self = inventory;
+        return true;
     }
 
     pub fn insert_free(&mut self, item_type: InventoryItemType) -> bool {
         self.insert(InventoryItem {
             item_type,
             price: 0,
+            count: 1,
         })
     }
 
+    /// アイテムを種類ごとに並べ替えます
+    /// 重ねられるアイテムはまとめ、幅のあるアイテムは行をまたがないように隙間なく詰めます
     pub fn sort(&mut self) {
-        let mut cloned = self.0.clone();
-        cloned.sort_by(|a, b| {
-            if a.is_none() {
-                return std::cmp::Ordering::Greater;
-            }
-            if b.is_none() {
-                return std::cmp::Ordering::Less;
-            }
-            match (a.unwrap(), b.unwrap()) {
-                (a, b) => a.item_type.cmp(&b.item_type),
-            }
-        });
-        let mut i = 0;
-        for item in cloned {
-            if MAX_ITEMS_IN_INVENTORY <= i {
-                break;
+        let mut items: Vec<InventoryItem> = self.0.iter().filter_map(|item| *item).collect();
+        items.sort_by(|a, b| a.item_type.cmp(&b.item_type).then(a.price.cmp(&b.price)));
+
+        let mut sorted = Inventory::new();
+        let mut last: Option<usize> = None;
+        for mut item in items {
+            // 直前に置いたものと同じ種類であれば上限まで重ねます
+            if let Some(index) = last {
+                if let Some(ref mut stack) = sorted.0[index] {
+                    if item.is_stackable_with(stack) {
+                        let max_stack = item.item_type.get_max_stack();
+                        let moved = item.count.min(max_stack.saturating_sub(stack.count));
+                        stack.count += moved;
+                        item.count -= moved;
+                        if item.count == 0 {
+                            continue;
+                        }
+                    }
+                }
             }
-            let width = match item {
-                Some(item) => item.item_type.get_width(),
-                None => 1,
-            };
-            self.0[i] = item;
-            for j in 1..width {
-                self.0[i + j] = None;
+
+            // 行末に収まらない杖は次の行に送り、空いた隙間には後の小さなアイテムを詰めます
+            match (0..MAX_ITEMS_IN_INVENTORY).find(|i| sorted.is_settable(*i, item)) {
+                Some(index) => {
+                    sorted.set(index, Some(item));
+                    last = Some(index);
+                }
+                None => {
+                    // 詰め直せない場合は並べ替えを諦めます
+                    return;
+                }
             }
-            i += width;
         }
+        *self = sorted;
     }
 
     // 現在所持している有料呪文の合計金額を返します
@@ -112,9 +172,148 @@ impl Inventory {
         let mut total = 0;
         for item in self.0 {
             if let Some(item) = item {
-                total += item.price;
+                total += item.price * item.count;
             }
         }
         total
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consumable::ConsumableType;
+    use crate::spell::SpellType;
+    use crate::wand::{WandItem, WandType};
+
+    fn spell() -> InventoryItem {
+        InventoryItem {
+            item_type: InventoryItemType::Spell(SpellType::MagicBolt),
+            price: 0,
+            count: 1,
+        }
+    }
+
+    fn wand() -> InventoryItem {
+        InventoryItem {
+            item_type: InventoryItemType::Wand(WandItem::new(WandType::CypressWand)),
+            price: 0,
+            count: 1,
+        }
+    }
+
+    fn bombs(count: u32) -> InventoryItem {
+        InventoryItem {
+            item_type: InventoryItemType::Consumable(ConsumableType::Bomb),
+            price: 0,
+            count,
+        }
+    }
+
+    #[test]
+    fn wand_does_not_fit_at_row_end() {
+        let inventory = Inventory::new();
+        assert!(inventory.is_settable(MAX_ITEMS_IN_INVENTORY_ROW - 2, wand()));
+        assert!(!inventory.is_settable(MAX_ITEMS_IN_INVENTORY_ROW - 1, wand()));
+    }
+
+    #[test]
+    fn nothing_fits_outside_inventory() {
+        let inventory = Inventory::new();
+        assert!(!inventory.is_settable(MAX_ITEMS_IN_INVENTORY, spell()));
+    }
+
+    #[test]
+    fn wand_does_not_overlap_right_neighbor() {
+        let mut inventory = Inventory::new();
+        inventory.set(1, Some(spell()));
+        assert!(!inventory.is_settable(0, wand()));
+    }
+
+    #[test]
+    fn slot_covered_by_wand_is_occupied() {
+        let mut inventory = Inventory::new();
+        inventory.set(0, Some(wand()));
+        assert!(!inventory.is_settable(1, spell()));
+        assert!(inventory.is_settable(2, spell()));
+    }
+
+    #[test]
+    fn insert_stacks_same_items() {
+        let mut inventory = Inventory::new();
+        assert!(inventory.insert(spell()));
+        assert!(inventory.insert(spell()));
+        assert_eq!(inventory.get(0).map(|item| item.count), Some(2));
+        assert_eq!(inventory.get(1), None);
+    }
+
+    #[test]
+    fn insert_does_not_stack_different_prices() {
+        let mut inventory = Inventory::new();
+        assert!(inventory.insert(spell()));
+        assert!(inventory.insert(InventoryItem {
+            price: 10,
+            ..spell()
+        }));
+        assert_eq!(inventory.get(0).map(|item| item.count), Some(1));
+        assert_eq!(inventory.get(1).map(|item| item.price), Some(10));
+    }
+
+    #[test]
+    fn insert_overflows_into_new_slot() {
+        let max_stack = bombs(1).item_type.get_max_stack();
+        let mut inventory = Inventory::new();
+        assert!(inventory.insert(bombs(max_stack - 1)));
+        assert!(inventory.insert(bombs(3)));
+        assert_eq!(inventory.get(0).map(|item| item.count), Some(max_stack));
+        assert_eq!(inventory.get(1).map(|item| item.count), Some(2));
+    }
+
+    #[test]
+    fn insert_skips_row_end_for_wand() {
+        let mut inventory = Inventory::new();
+        for i in 0..MAX_ITEMS_IN_INVENTORY_ROW - 1 {
+            inventory.set(
+                i,
+                Some(InventoryItem {
+                    price: i as u32,
+                    ..spell()
+                }),
+            );
+        }
+        assert!(inventory.insert(wand()));
+        assert_eq!(inventory.get(MAX_ITEMS_IN_INVENTORY_ROW - 1), None);
+        assert_eq!(inventory.get(MAX_ITEMS_IN_INVENTORY_ROW), Some(wand()));
+    }
+
+    #[test]
+    fn insert_into_full_inventory_leaves_it_unchanged() {
+        let mut inventory = Inventory::new();
+        for i in 0..MAX_ITEMS_IN_INVENTORY {
+            inventory.set(
+                i,
+                Some(InventoryItem {
+                    price: i as u32,
+                    ..spell()
+                }),
+            );
+        }
+        let before = inventory;
+        assert!(!inventory.insert(wand()));
+        assert_eq!(inventory, before);
+    }
+
+    #[test]
+    fn sort_packs_wands_and_merges_stacks() {
+        let mut inventory = Inventory::new();
+        inventory.set(1, Some(spell()));
+        inventory.set(3, Some(wand()));
+        inventory.set(7, Some(wand()));
+        inventory.set(20, Some(spell()));
+        inventory.sort();
+        assert_eq!(inventory.get(0), Some(wand()));
+        assert_eq!(inventory.get(2), Some(wand()));
+        assert_eq!(inventory.get(4).map(|item| item.count), Some(2));
+        assert_eq!(inventory.get(5), None);
+    }
+}
@@ -1,17 +1,23 @@
 use crate::{
-    equipment::EquipmentType,
+    consumable::ConsumableType,
+    equipment::EquipmentItem,
     language::{Dict, Languages},
     spell::SpellType,
     spell_props::get_spell_appendix,
-    wand::WandType,
+    wand::WandItem,
 };
 use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect)]
+/// インベントリのひとつのスロットに重ねられる呪文の数
+const MAX_SPELL_STACK: u32 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
 pub enum InventoryItemType {
-    Wand(WandType),
+    Wand(WandItem),
     Spell(SpellType),
-    Equipment(EquipmentType),
+    Equipment(EquipmentItem),
+    Consumable(ConsumableType),
 }
 
 impl InventoryItemType {
@@ -20,14 +26,35 @@ impl InventoryItemType {
             InventoryItemType::Spell(_) => 1,
             InventoryItemType::Wand(_) => 2,
             InventoryItemType::Equipment(_) => 1,
+            InventoryItemType::Consumable(_) => 1,
+        }
+    }
+
+    /// インベントリのひとつのスロットに重ねられる数
+    pub fn get_max_stack(&self) -> u32 {
+        match self {
+            InventoryItemType::Spell(..) => MAX_SPELL_STACK,
+            InventoryItemType::Consumable(consumable) => consumable.to_props().max_stack,
+            _ => 1,
         }
     }
 
     pub fn get_icon(&self) -> &'static str {
         match self {
             InventoryItemType::Spell(spell) => spell.to_props().icon,
-            InventoryItemType::Wand(wand) => wand.to_props().icon,
-            InventoryItemType::Equipment(equipment) => equipment.to_props().icon,
+            InventoryItemType::Wand(wand) => wand.wand_type.to_props().icon,
+            InventoryItemType::Equipment(equipment) => equipment.equipment_type.to_props().icon,
+            InventoryItemType::Consumable(consumable) => consumable.to_props().icon,
+        }
+    }
+
+    /// ショップでの基本価格
+    pub fn get_price(&self) -> u32 {
+        match self {
+            InventoryItemType::Spell(spell) => spell.to_props().price,
+            InventoryItemType::Wand(wand) => wand.price(),
+            InventoryItemType::Equipment(equipment) => equipment.price(),
+            InventoryItemType::Consumable(consumable) => consumable.to_props().price,
         }
     }
 
@@ -36,6 +63,7 @@ impl InventoryItemType {
             InventoryItemType::Spell(..) => 32.0,
             InventoryItemType::Wand(..) => 64.0,
             InventoryItemType::Equipment(..) => 32.0,
+            InventoryItemType::Consumable(..) => 32.0,
         }
     }
 }
@@ -57,7 +85,7 @@ pub fn inventory_item_to_props(item: InventoryItemType) -> InventoryItemProps {
             }
         }
         InventoryItemType::Wand(wand) => {
-            let props = wand.to_props();
+            let props = wand.wand_type.to_props();
             InventoryItemProps {
                 icon: props.icon,
                 name: props.name,
@@ -65,7 +93,15 @@ pub fn inventory_item_to_props(item: InventoryItemType) -> InventoryItemProps {
             }
         }
         InventoryItemType::Equipment(equipment) => {
-            let props = equipment.to_props();
+            let props = equipment.equipment_type.to_props();
+            InventoryItemProps {
+                icon: props.icon,
+                name: props.name,
+                description: props.description,
+            }
+        }
+        InventoryItemType::Consumable(consumable) => {
+            let props = consumable.to_props();
             InventoryItemProps {
                 icon: props.icon,
                 name: props.name,
@@ -96,9 +132,58 @@ pub fn get_inventory_item_description(item: InventoryItemType, language: Languag
                 appendix
             );
         }
-        other => inventory_item_to_props(other)
-            .description
-            .get(language)
-            .to_string(),
+        InventoryItemType::Wand(wand) => {
+            return wand_description(&wand, language);
+        }
+        InventoryItemType::Equipment(equipment) => {
+            return equipment.description(language);
+        }
+        InventoryItemType::Consumable(consumable) => {
+            let props = consumable.to_props();
+            return format!(
+                "{}\n{}",
+                props.description.get(language),
+                Dict {
+                    ja: "右クリックまたはFキーで使用",
+                    en: "Right click or press F to use"
+                }
+                .get(language)
+            );
+        }
     }
 }
+
+pub fn wand_description(wand: &WandItem, language: Languages) -> String {
+    let props = wand.wand_type.to_props();
+    let mut description = format!(
+        "{}\n{}",
+        props.description.get(language),
+        wand_stats(wand, language)
+    );
+    if let Some(spell) = wand.always_cast {
+        description += &format!(
+            "\n{}:{}",
+            Dict {
+                ja: "常時詠唱",
+                en: "Always Cast"
+            }
+            .get(language),
+            spell.to_props().name.get(language)
+        );
+    }
+    description
+}
+
+/// 杖の個体ごとの性能を一行で表します
+pub fn wand_stats(wand: &WandItem, language: Languages) -> String {
+    language.m17n(
+        format!(
+            "スロット:{} 詠唱遅延:{:+} リロード:{} 拡散:{}",
+            wand.capacity, wand.cast_delay, wand.reload, wand.spread
+        ),
+        format!(
+            "Slots:{} Delay:{:+} Reload:{} Spread:{}",
+            wand.capacity, wand.cast_delay, wand.reload, wand.spread
+        ),
+    )
+}
@@ -1,5 +1,6 @@
 pub mod ceil;
 pub mod map;
+pub mod spawn_table;
 pub mod tile;
 pub mod wall;
 
@@ -9,9 +10,9 @@ use crate::camera::GameCamera;
 use crate::config::GameConfig;
 use crate::constant::*;
 use crate::controller::player::Player;
-use crate::enemy::eyeball::spawn_eyeball;
+use crate::controller::replication::SpawnTile;
+use crate::enemy::buer::spawn_buer;
 use crate::enemy::huge_slime::spawn_huge_slime;
-use crate::enemy::slime::spawn_slime;
 use crate::entity::actor::ActorGroup;
 use crate::entity::book_shelf::spawn_book_shelf;
 use crate::entity::broken_magic_circle::spawn_broken_magic_circle;
@@ -21,12 +22,13 @@ use crate::entity::dropped_item::spawn_dropped_item;
 use crate::entity::magic_circle::spawn_magic_circle;
 use crate::entity::magic_circle::MagicCircleDestination;
 use crate::entity::rabbit::spawn_rabbit;
-use crate::entity::shop::spawn_shop_door;
+use crate::entity::shop::{spawn_shop_door, ShopInventory};
+use crate::entity::stash::spawn_stash_chest;
 use crate::entity::stone_lantern::spawn_stone_lantern;
 use crate::entity::witch::spawn_enemy_witch;
 use crate::entity::witch::spawn_witch;
+use crate::entity::workbench::spawn_workbench;
 use crate::entity::GameEntity;
-use crate::equipment::EQUIPMENTS;
 use crate::hud::life_bar::LifeBarResource;
 use crate::inventory::InventoryItem;
 use crate::inventory_item::InventoryItemType;
@@ -34,23 +36,25 @@ use crate::language::Dict;
 use crate::level::ceil::spawn_roof_tiles;
 use crate::level::map::image_to_tilemap;
 use crate::level::map::LevelChunk;
+use crate::level::spawn_table::spawn_enemies;
 use crate::level::tile::*;
+use crate::loot::level_depth;
 use crate::player_state::PlayerState;
 use crate::random::random_select;
 use crate::random::random_select_mut;
 use crate::spell::SPELL_TYPES;
 use crate::states::GameState;
 use bevy::asset::*;
-use bevy::core::FrameCount;
 use bevy::prelude::*;
 use bevy_aseprite_ultra::prelude::*;
 use map::image_to_spawn_tiles;
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use wall::spawn_wall_collisions;
 use wall::WallCollider;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum GameLevel {
     Level(i32),
     MultiPlayArena,
@@ -132,13 +136,7 @@ pub fn setup_level(
         player.equipments,
         Player {
             name: player.name,
-            last_idle_frame_count: FrameCount(0),
-            last_ilde_x: player_x,
-            last_ilde_y: player_y,
-            last_idle_vx: 0.0,
-            last_idle_vy: 0.0,
-            last_idle_life: player.life,
-            last_idle_max_life: player.max_life,
+            debt: player.debt,
         },
         ActorGroup::Player,
     );
@@ -241,39 +239,22 @@ fn spawn_level(
 
     spawn_wall_collisions(&mut commands, &chunk);
 
-    spawn_entities(&mut commands, &assets, &life_bar_res, &chunk);
+    spawn_entities(&mut commands, &assets, &life_bar_res, &chunk, level);
 
-    if 30 < empties.len() {
-        for _ in 0..10 {
-            let (x, y) = random_select_mut(&mut empties);
-            spawn_slime(
-                &mut commands,
-                &assets,
-                Vec2::new(
-                    TILE_SIZE * x as f32 + TILE_HALF,
-                    TILE_SIZE * -y as f32 - TILE_HALF,
-                ),
-                &life_bar_res,
-                0,
-                2,
-                ActorGroup::Enemy,
-                None,
-            );
-        }
+    if level == GameLevel::Level(0) {
+        spawn_home_facilities(&mut commands, &assets, &chunk);
+    }
 
-        for _ in 0..10 {
-            let (x, y) = random_select_mut(&mut empties);
-            spawn_eyeball(
-                &mut commands,
-                &assets,
-                Vec2::new(
-                    TILE_SIZE * x as f32 + TILE_HALF,
-                    TILE_SIZE * -y as f32 - TILE_HALF,
-                ),
-                &life_bar_res,
-            );
-        }
+    spawn_enemies(
+        &mut commands,
+        &assets,
+        &life_bar_res,
+        level,
+        &chunk,
+        &mut empties,
+    );
 
+    if 30 < empties.len() {
         let mut spells = Vec::from(SPELL_TYPES);
         for _ in 0..3 {
             let (x, y) = random_select_mut(&mut empties);
@@ -287,6 +268,7 @@ fn spawn_level(
                 InventoryItem {
                     item_type: InventoryItemType::Spell(*random_select(&mut spells)),
                     price: 0,
+                    count: 1,
                 },
             );
         }
@@ -356,42 +338,100 @@ fn spawn_world_tilemap(commands: &mut Commands, assets: &Res<GameAssets>, chunk:
     }
 }
 
+/// ホームに保管箱と作業台を配置します
+/// 両方の画面が同時に開かないよう、作業台は保管箱から離れた位置に置きます
+fn spawn_home_facilities(commands: &mut Commands, assets: &Res<GameAssets>, chunk: &LevelChunk) {
+    let stash = find_home_spot(chunk, None);
+    if let Some((x, y)) = stash {
+        spawn_stash_chest(commands, assets, tile_center(x, y));
+    }
+    if let Some((x, y)) = find_home_spot(chunk, stash) {
+        spawn_workbench(commands, assets, tile_center(x, y));
+    }
+}
+
+fn tile_center(x: i32, y: i32) -> Vec2 {
+    Vec2::new(
+        TILE_SIZE * x as f32 + TILE_HALF,
+        -TILE_SIZE * y as f32 - TILE_HALF,
+    )
+}
+
+/// 入り口の近くで、他のエンティティと重ならない空きタイルを探します
+/// avoid を指定した場合は、その位置から5タイル以上離れたタイルを選びます
+fn find_home_spot(chunk: &LevelChunk, avoid: Option<(i32, i32)>) -> Option<(i32, i32)> {
+    if let Some(entry_point) = chunk.entry_points.first() {
+        let (ex, ey) = (entry_point.x as i32, entry_point.y as i32);
+        for radius in 2..10 {
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if dx.abs().max(dy.abs()) != radius {
+                        continue;
+                    }
+                    let (x, y) = (ex + dx, ey + dy);
+                    let occupied = chunk
+                        .entities
+                        .iter()
+                        .any(|(_, ox, oy)| (ox - x).abs() <= 1 && (oy - y).abs() <= 1);
+                    let near = avoid.is_some_and(|(ax, ay)| (ax - x).abs().max((ay - y).abs()) < 5);
+                    if chunk.is_empty(x, y) && chunk.is_empty(x + 1, y) && !occupied && !near {
+                        return Some((x, y));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 fn spawn_entities(
     mut commands: &mut Commands,
     assets: &Res<GameAssets>,
     life_bar_resource: &Res<LifeBarResource>,
     chunk: &LevelChunk,
+    level: GameLevel,
 ) {
+    // ショップの棚の数だけ商品を用意します
+    let shelves = chunk
+        .entities
+        .iter()
+        .filter(|(entity, _, _)| *entity == GameEntity::Spell)
+        .count();
+    let mut stock = ShopInventory::generate(shelves, level_depth(level));
+
     // エンティティの生成
     for (entity, x, y) in &chunk.entities {
         let tx = TILE_SIZE * *x as f32;
         let ty = TILE_SIZE * -*y as f32;
         match entity {
             GameEntity::BookShelf => {
-                spawn_book_shelf(
+                let entity = spawn_book_shelf(
                     &mut commands,
                     assets.atlas.clone(),
                     tx + TILE_SIZE,
                     ty - TILE_HALF,
                 );
+                commands.entity(entity).insert(SpawnTile(*x, *y));
             }
             GameEntity::Chest => {
-                spawn_chest(
+                let entity = spawn_chest(
                     &mut commands,
                     assets.atlas.clone(),
                     tx + TILE_HALF,
                     ty - TILE_HALF,
                     ChestType::Chest,
                 );
+                commands.entity(entity).insert(SpawnTile(*x, *y));
             }
             GameEntity::Crate => {
-                spawn_chest(
+                let entity = spawn_chest(
                     &mut commands,
                     assets.atlas.clone(),
                     tx + TILE_HALF,
                     ty - TILE_HALF,
                     ChestType::Crate,
                 );
+                commands.entity(entity).insert(SpawnTile(*x, *y));
             }
             GameEntity::MagicCircle => {
                 spawn_magic_circle(
@@ -460,29 +500,12 @@ fn spawn_entities(
                 ));
             }
             GameEntity::Spell => {
-                if 0.5 < rand::random::<f32>() {
-                    let spell = SPELL_TYPES[rand::random::<usize>() % SPELL_TYPES.len()];
-                    let props = spell.to_props();
+                if let Some(item) = stock.pop() {
                     spawn_dropped_item(
                         &mut commands,
                         &assets,
                         Vec2::new(tx + TILE_HALF, ty - TILE_HALF),
-                        InventoryItem {
-                            item_type: InventoryItemType::Spell(spell),
-                            price: props.price,
-                        },
-                    );
-                } else {
-                    let equipment = EQUIPMENTS[rand::random::<usize>() % EQUIPMENTS.len()];
-                    let props = equipment.to_props();
-                    spawn_dropped_item(
-                        &mut commands,
-                        &assets,
-                        Vec2::new(tx + TILE_HALF, ty - TILE_HALF),
-                        InventoryItem {
-                            item_type: InventoryItemType::Equipment(equipment),
-                            price: props.price,
-                        },
+                        item,
                     );
                 }
             }
@@ -493,6 +516,13 @@ fn spawn_entities(
                     Vec2::new(tx + TILE_HALF, ty - TILE_HALF),
                 );
             }
+            GameEntity::Buer => {
+                spawn_buer(
+                    &mut commands,
+                    &assets,
+                    Vec2::new(tx + TILE_HALF, ty - TILE_HALF),
+                );
+            }
             GameEntity::Rabbit => {
                 spawn_rabbit(
                     &mut commands,
@@ -203,6 +203,13 @@ pub fn image_to_tilemap(
                     });
                     entities.push((GameEntity::HugeSlime, x, y));
                 }
+                (180, 0, 255, 255) => {
+                    tiles.push(LevelTileMapile {
+                        tile: Tile::StoneTile,
+                        biome: Biome::SafeZone,
+                    });
+                    entities.push((GameEntity::Buer, x, y));
+                }
                 (255, 243, 0, 255) => {
                     tiles.push(LevelTileMapile {
                         tile: Tile::StoneTile,
@@ -0,0 +1,126 @@
+use crate::asset::GameAssets;
+use crate::constant::*;
+use crate::controller::replication::SpawnTile;
+use crate::enemy::basic::spawn_enemy;
+use crate::enemy::elite::{random_affixes, Elite};
+use crate::enemy_props::EnemyType;
+use crate::hud::life_bar::LifeBarResource;
+use crate::level::map::LevelChunk;
+use crate::level::GameLevel;
+use crate::random::random_select_mut;
+use bevy::prelude::*;
+
+/// プレイヤーの出現地点からこの距離以内には敵を配置しません
+const SAFE_DISTANCE: f32 = 8.0;
+
+/// レベルごとに出現する敵の種類と重み、難易度の予算です
+pub struct SpawnTable {
+    pub enemies: &'static [(EnemyType, u32)],
+
+    /// 敵が出現するタイル100マスあたりの難易度の予算
+    /// マップが広いほど多くの敵が出現します
+    pub density: f32,
+}
+
+pub fn level_to_spawn_table(level: GameLevel) -> SpawnTable {
+    match level {
+        GameLevel::Level(0) => SpawnTable {
+            enemies: &[],
+            density: 0.0,
+        },
+        GameLevel::Level(1) => SpawnTable {
+            enemies: &[(EnemyType::Slime, 3), (EnemyType::Eyeball, 1)],
+            density: 1.5,
+        },
+        GameLevel::Level(2) => SpawnTable {
+            enemies: &[(EnemyType::Slime, 1), (EnemyType::Eyeball, 1)],
+            density: 2.0,
+        },
+        GameLevel::Level(3) => SpawnTable {
+            enemies: &[(EnemyType::Slime, 4), (EnemyType::Eyeball, 1)],
+            density: 2.5,
+        },
+        GameLevel::MultiPlayArena => SpawnTable {
+            enemies: &[(EnemyType::Slime, 1), (EnemyType::Eyeball, 1)],
+            density: 1.0,
+        },
+        _ => SpawnTable {
+            enemies: &[(EnemyType::Slime, 1), (EnemyType::Eyeball, 1)],
+            density: 2.0,
+        },
+    }
+}
+
+/// 予算の範囲内で、重みに従って敵の種類を選びます
+fn select_enemy(table: &SpawnTable, budget: u32) -> Option<EnemyType> {
+    let candidates: Vec<(EnemyType, u32)> = table
+        .enemies
+        .iter()
+        .filter(|(enemy_type, _)| enemy_type.to_props().cost <= budget)
+        .cloned()
+        .collect();
+    let total: u32 = candidates.iter().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut value = rand::random::<u32>() % total;
+    for (enemy_type, weight) in candidates.iter() {
+        if value < *weight {
+            return Some(*enemy_type);
+        }
+        value -= weight;
+    }
+    None
+}
+
+/// スポーンテーブルに従って、敵が出現するタイルに敵を配置します
+/// 敵を配置したタイルは empties から取り除きます
+pub fn spawn_enemies(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    life_bar_res: &Res<LifeBarResource>,
+    level: GameLevel,
+    chunk: &LevelChunk,
+    empties: &mut Vec<(i32, i32)>,
+) {
+    let table = level_to_spawn_table(level);
+
+    let mut tiles: Vec<(i32, i32)> = empties
+        .iter()
+        .filter(|(x, y)| {
+            let tile = Vec2::new(*x as f32, *y as f32);
+            chunk
+                .entry_points
+                .iter()
+                .all(|entry_point| SAFE_DISTANCE < entry_point.distance(tile))
+        })
+        .cloned()
+        .collect();
+
+    let mut budget = (empties.len() as f32 * table.density / 100.0) as u32;
+
+    while !tiles.is_empty() {
+        if let Some(enemy_type) = select_enemy(&table, budget) {
+            let (x, y) = random_select_mut(&mut tiles);
+            let entity = spawn_enemy(
+                commands,
+                assets,
+                enemy_type,
+                Vec2::new(
+                    TILE_SIZE * x as f32 + TILE_HALF,
+                    TILE_SIZE * -y as f32 - TILE_HALF,
+                ),
+                life_bar_res,
+            );
+            commands.entity(entity).insert(SpawnTile(x, y));
+            empties.retain(|tile| *tile != (x, y));
+            let affixes = random_affixes(level);
+            if !affixes.is_empty() {
+                commands.entity(entity).insert(Elite::new(affixes));
+            }
+            budget -= enemy_type.to_props().cost;
+        } else {
+            break;
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::asset::GameAssets;
+use crate::consumable::CONSUMABLES;
+use crate::entity::dropped_item::spawn_dropped_item;
+use crate::entity::gold::spawn_gold;
+use crate::equipment::{EquipmentItem, EQUIPMENTS};
+use crate::inventory::InventoryItem;
+use crate::inventory_item::InventoryItemType;
+use crate::level::GameLevel;
+use crate::spell::SPELL_TYPES;
+use crate::wand::WandItem;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use std::f32::consts::PI;
+
+/// ドロップしたアイテムが飛び出す速さ
+const POP_SPEED: f32 = 40.0;
+
+/// アイテムを落とすものの種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LootSource {
+    Chest,
+    Crate,
+    BookShelf,
+    Enemy,
+}
+
+/// ひとつの抽選で出現するもの
+#[derive(Clone, Copy, Debug)]
+pub enum Loot {
+    Nothing,
+    Gold { min: u32, max: u32 },
+    Spell,
+    Wand,
+    Equipment,
+    Consumable,
+}
+
+/// 抽選の候補です
+/// 重みは weight + depth_weight * レベルの深さ で、深い階層ほど depth_weight の大きいものが出やすくなります
+pub struct LootEntry {
+    pub loot: Loot,
+    pub weight: u32,
+    pub depth_weight: u32,
+}
+
+pub struct LootTable {
+    /// 抽選する回数
+    pub rolls: u32,
+    pub entries: &'static [LootEntry],
+}
+
+const CHEST: LootTable = LootTable {
+    rolls: 2,
+    entries: &[
+        LootEntry {
+            loot: Loot::Gold { min: 3, max: 12 },
+            weight: 60,
+            depth_weight: 0,
+        },
+        LootEntry {
+            loot: Loot::Spell,
+            weight: 20,
+            depth_weight: 5,
+        },
+        LootEntry {
+            loot: Loot::Equipment,
+            weight: 10,
+            depth_weight: 5,
+        },
+        LootEntry {
+            loot: Loot::Wand,
+            weight: 3,
+            depth_weight: 2,
+        },
+        LootEntry {
+            loot: Loot::Consumable,
+            weight: 15,
+            depth_weight: 0,
+        },
+    ],
+};
+
+const CRATE: LootTable = LootTable {
+    rolls: 1,
+    entries: &[
+        LootEntry {
+            loot: Loot::Nothing,
+            weight: 70,
+            depth_weight: 0,
+        },
+        LootEntry {
+            loot: Loot::Gold { min: 1, max: 3 },
+            weight: 25,
+            depth_weight: 0,
+        },
+        LootEntry {
+            loot: Loot::Spell,
+            weight: 5,
+            depth_weight: 2,
+        },
+        LootEntry {
+            loot: Loot::Consumable,
+            weight: 5,
+            depth_weight: 0,
+        },
+    ],
+};
+
+const BOOK_SHELF: LootTable = LootTable {
+    rolls: 1,
+    entries: &[
+        LootEntry {
+            loot: Loot::Nothing,
+            weight: 80,
+            depth_weight: 0,
+        },
+        LootEntry {
+            loot: Loot::Spell,
+            weight: 20,
+            depth_weight: 3,
+        },
+    ],
+};
+
+/// 敵の落とすゴールドは DespawnWithGold で決まるので、ここではゴールド以外を抽選します
+const ENEMY: LootTable = LootTable {
+    rolls: 1,
+    entries: &[
+        LootEntry {
+            loot: Loot::Nothing,
+            weight: 95,
+            depth_weight: 0,
+        },
+        LootEntry {
+            loot: Loot::Spell,
+            weight: 3,
+            depth_weight: 1,
+        },
+        LootEntry {
+            loot: Loot::Equipment,
+            weight: 2,
+            depth_weight: 1,
+        },
+        LootEntry {
+            loot: Loot::Consumable,
+            weight: 3,
+            depth_weight: 0,
+        },
+    ],
+};
+
+impl LootSource {
+    pub fn to_table(&self) -> LootTable {
+        match self {
+            LootSource::Chest => CHEST,
+            LootSource::Crate => CRATE,
+            LootSource::BookShelf => BOOK_SHELF,
+            LootSource::Enemy => ENEMY,
+        }
+    }
+}
+
+/// レベルの深さ
+/// マルチプレイヤーアリーナはレベル1相当として扱います
+pub fn level_depth(level: GameLevel) -> i32 {
+    match level {
+        GameLevel::Level(level) => level.max(0),
+        GameLevel::MultiPlayArena => 1,
+    }
+}
+
+fn select_loot(table: &LootTable, depth: i32) -> Loot {
+    let weight = |entry: &LootEntry| entry.weight + entry.depth_weight * depth.max(0) as u32;
+    let total: u32 = table.entries.iter().map(weight).sum();
+    if total == 0 {
+        return Loot::Nothing;
+    }
+    let mut value = rand::random::<u32>() % total;
+    for entry in table.entries.iter() {
+        if value < weight(entry) {
+            return entry.loot;
+        }
+        value -= weight(entry);
+    }
+    Loot::Nothing
+}
+
+/// 落とし物の表に従って抽選し、ゴールドやアイテムをその場に落とします
+/// アイテムは少し飛び出すように初速を与えます
+pub fn spawn_loot(
+    commands: &mut Commands,
+    assets: &Res<GameAssets>,
+    source: LootSource,
+    depth: i32,
+    position: Vec2,
+) {
+    let table = source.to_table();
+    for _ in 0..table.rolls {
+        let item_type = match select_loot(&table, depth) {
+            Loot::Nothing => None,
+            Loot::Gold { min, max } => {
+                let amount = min + rand::random::<u32>() % (max - min + 1);
+                for _ in 0..amount {
+                    spawn_gold(commands, assets, position.x, position.y);
+                }
+                None
+            }
+            Loot::Spell => Some(InventoryItemType::Spell(
+                SPELL_TYPES[rand::random::<usize>() % SPELL_TYPES.len()],
+            )),
+            Loot::Wand => Some(InventoryItemType::Wand(WandItem::random(depth))),
+            Loot::Consumable => Some(InventoryItemType::Consumable(
+                CONSUMABLES[rand::random::<usize>() % CONSUMABLES.len()],
+            )),
+            Loot::Equipment => Some(InventoryItemType::Equipment(EquipmentItem::random(
+                EQUIPMENTS[rand::random::<usize>() % EQUIPMENTS.len()],
+                depth,
+            ))),
+        };
+
+        if let Some(item_type) = item_type {
+            let entity = spawn_dropped_item(
+                commands,
+                assets,
+                position,
+                InventoryItem {
+                    item_type,
+                    price: 0,
+                    count: 1,
+                },
+            );
+            commands.entity(entity).insert(Velocity::linear(
+                Vec2::from_angle(2.0 * PI * rand::random::<f32>()) * POP_SPEED,
+            ));
+        }
+    }
+}
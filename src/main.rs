@@ -8,10 +8,12 @@ mod camera;
 mod cast;
 mod config;
 mod constant;
+mod consumable;
 mod controller;
 mod curve;
 mod debug;
 mod enemy;
+mod enemy_props;
 mod entity;
 mod equipment;
 mod footsteps;
@@ -22,6 +24,7 @@ mod inventory;
 mod inventory_item;
 mod language;
 mod level;
+mod loot;
 mod page;
 mod physics;
 mod player_state;
@@ -31,6 +34,7 @@ mod set;
 mod speech_bubble;
 mod spell;
 mod spell_props;
+mod stash;
 mod states;
 mod ui;
 mod wand;
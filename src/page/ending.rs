@@ -1,9 +1,5 @@
 use crate::{
-    asset::GameAssets,
-    audio::NextBGM,
-    enemy::huge_slime::HugeSlime,
-    hud::overlay::OverlayEvent,
-    level::{CurrentLevel, GameLevel},
+    asset::GameAssets, audio::NextBGM, enemy::boss::BossDefeated, hud::overlay::OverlayEvent,
     states::GameState,
 };
 use bevy::prelude::*;
@@ -47,16 +43,23 @@ fn interaction(
     }
 }
 
+/// エンディングに進むボスが倒されてから、少し待ってエンディングに移ります
 fn start_ending(
-    mut local: Local<u32>,
-    boss_query: Query<&HugeSlime>,
+    mut local: Local<Option<u32>>,
+    mut reader: EventReader<BossDefeated>,
     mut writer: EventWriter<OverlayEvent>,
-    current: Res<CurrentLevel>,
 ) {
-    if current.level == Some(GameLevel::Level(3)) && boss_query.is_empty() {
-        *local += 1;
-        if *local == 120 {
+    for event in reader.read() {
+        if event.ending {
+            *local = Some(0);
+        }
+    }
+    if let Some(count) = *local {
+        if count == 120 {
             writer.send(OverlayEvent::Close(GameState::Ending));
+            *local = None;
+        } else {
+            *local = Some(count + 1);
         }
     }
 }
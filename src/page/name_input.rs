@@ -11,13 +11,22 @@ use crate::{
 use bevy::ecs::system::SystemId;
 use bevy::prelude::*;
 use bevy_simple_text_input::{
-    TextInput, TextInputSettings, TextInputTextColor, TextInputTextFont, TextInputValue,
+    TextInput, TextInputInactive, TextInputSettings, TextInputTextColor, TextInputTextFont,
+    TextInputValue,
 };
 
 const BORDER_COLOR_ACTIVE: Color = Color::srgb(0.75, 0.52, 0.99);
+const BORDER_COLOR_INACTIVE: Color = Color::srgb(0.25, 0.25, 0.25);
 const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
 const BACKGROUND_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
 
+#[derive(Component)]
+struct NameInput;
+
+/// 協力プレイの合言葉の入力欄
+#[derive(Component)]
+struct RoomInput;
+
 #[derive(Resource)]
 struct ButtonShots {
     start: SystemId,
@@ -34,7 +43,8 @@ impl FromWorld for ButtonShots {
 fn start_game(
     mut menu_next_state: ResMut<NextState<MainMenuPhase>>,
     mut config: ResMut<GameConfig>,
-    query: Query<&TextInputValue>,
+    name_query: Query<&TextInputValue, With<NameInput>>,
+    room_query: Query<&TextInputValue, With<RoomInput>>,
     mut writer: EventWriter<SEEvent>,
     mut next_bgm: ResMut<NextBGM>,
     mut overlay_event_writer: EventWriter<OverlayEvent>,
@@ -43,8 +53,8 @@ fn start_game(
     overlay_event_writer.send(OverlayEvent::Close(GameState::InGame));
     *next_bgm = NextBGM(None);
 
-    let q = query.single();
-    config.player_name = q.0.clone();
+    config.player_name = name_query.single().0.clone();
+    config.room = room_query.single().0.trim().to_string();
 
     writer.send(SEEvent::new(SE::Click));
 }
@@ -123,6 +133,8 @@ fn setup(
                                     ..default()
                                 },
                                 TextInputValue(config.player_name.clone()),
+                                Interaction::None,
+                                NameInput,
                             ));
 
                             menu_button(
@@ -137,15 +149,86 @@ fn setup(
                                 },
                             );
                         });
+
+                    parent
+                        .spawn(Node {
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new(
+                                    Dict {
+                                        ja: "協力プレイの合言葉",
+                                        en: "Co-op Room",
+                                    }
+                                    .get(config.language),
+                                ),
+                                TextColor(TEXT_COLOR),
+                                TextFont {
+                                    font_size: 24.0,
+                                    font: assets.dotgothic.clone(),
+                                    ..default()
+                                },
+                            ));
+
+                            parent.spawn((
+                                Node {
+                                    width: Val::Px(200.0),
+                                    border: UiRect::all(Val::Px(5.0)),
+                                    padding: UiRect::all(Val::Px(5.0)),
+                                    ..default()
+                                },
+                                BorderColor::from(BORDER_COLOR_INACTIVE),
+                                BackgroundColor::from(BACKGROUND_COLOR),
+                                TextInput,
+                                TextInputInactive(true),
+                                TextInputTextFont(TextFont {
+                                    font_size: 24.,
+                                    ..default()
+                                }),
+                                TextInputTextColor(TEXT_COLOR.into()),
+                                TextInputSettings {
+                                    retain_on_submit: true,
+                                    ..default()
+                                },
+                                TextInputValue(config.room.clone()),
+                                Interaction::None,
+                                RoomInput,
+                            ));
+                        });
                 });
         });
 }
 
+/// クリックした入力欄にだけ文字を入力できるようにします
+fn focus(
+    query: Query<(Entity, &Interaction), Changed<Interaction>>,
+    mut text_input_query: Query<(Entity, &mut TextInputInactive, &mut BorderColor)>,
+) {
+    for (interaction_entity, interaction) in &query {
+        if *interaction == Interaction::Pressed {
+            for (entity, mut inactive, mut border_color) in &mut text_input_query {
+                if entity == interaction_entity {
+                    inactive.0 = false;
+                    *border_color = BORDER_COLOR_ACTIVE.into();
+                } else {
+                    inactive.0 = true;
+                    *border_color = BORDER_COLOR_INACTIVE.into();
+                }
+            }
+        }
+    }
+}
+
 pub struct NameInputPagePlugin;
 
 impl Plugin for NameInputPagePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::NameInput), setup);
+        app.add_systems(Update, focus.run_if(in_state(GameState::NameInput)));
         app.init_resource::<ButtonShots>();
     }
 }
@@ -3,18 +3,20 @@ use bevy::ecs::query::QuerySingleError;
 use crate::{
     config::GameConfig,
     constant::{MAX_ITEMS_IN_EQUIPMENT, MAX_WANDS},
+    consumable::ConsumableType,
     controller::player::{Equipment, Player},
     entity::{actor::Actor, life::Life},
-    equipment::EquipmentType,
+    equipment::{EquipmentItem, EquipmentType},
     inventory::Inventory,
     inventory_item::InventoryItemType,
     spell::SpellType,
-    wand::{Wand, WandSpell, WandType},
+    wand::{Wand, WandItem, WandSpell, WandType},
 };
 
 #[derive(Debug, Clone)]
 pub struct PlayerState {
     pub name: String,
+    pub debt: u32,
     pub life: i32,
     pub max_life: i32,
     pub inventory: Inventory,
@@ -23,11 +25,14 @@ pub struct PlayerState {
 }
 
 impl PlayerState {
+    /// 最大ライフは装備による増加分を除いた値で保存します
+    /// 次のレベルで装備から改めて集計されます
     pub fn new(player: &Player, actor: &Actor, life: &Life) -> Self {
         PlayerState {
             name: player.name.clone(),
+            debt: player.debt,
             life: life.life,
-            max_life: life.max_life,
+            max_life: life.max_life - actor.aggregate_stats().max_life,
             inventory: actor.inventory.clone(),
             equipments: actor.equipments.clone(),
             wands: actor.wands.clone(),
@@ -60,36 +65,57 @@ impl PlayerState {
         inventory.insert_free(InventoryItemType::Spell(SpellType::PurpleBolt));
         inventory.insert_free(InventoryItemType::Spell(SpellType::DualCast));
         inventory.insert_free(InventoryItemType::Spell(SpellType::TripleCast));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::Lantern));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::SpikeBoots));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::SpikeBoots));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::SpikeBoots));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::Telescope));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::Telescope));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::Magnifier));
-        inventory.insert_free(InventoryItemType::Equipment(EquipmentType::Magnifier));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::Lantern,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::SpikeBoots,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::SpikeBoots,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::SpikeBoots,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::Telescope,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::Telescope,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::Magnifier,
+        )));
+        inventory.insert_free(InventoryItemType::Equipment(EquipmentItem::new(
+            EquipmentType::Magnifier,
+        )));
         inventory.insert_free(InventoryItemType::Spell(SpellType::Homing));
         inventory.insert_free(InventoryItemType::Spell(SpellType::Homing));
         inventory.insert_free(InventoryItemType::Spell(SpellType::Homing));
         inventory.insert_free(InventoryItemType::Spell(SpellType::Homing));
-        inventory.insert_free(InventoryItemType::Wand(WandType::KeyWand));
+        inventory.insert_free(InventoryItemType::Wand(WandItem::new(WandType::KeyWand)));
         inventory.insert_free(InventoryItemType::Spell(SpellType::HeavyShot));
         inventory.insert_free(InventoryItemType::Spell(SpellType::HeavyShot));
         inventory.insert_free(InventoryItemType::Spell(SpellType::HeavyShot));
         inventory.insert_free(InventoryItemType::Spell(SpellType::SummonFriendSlime));
         inventory.insert_free(InventoryItemType::Spell(SpellType::SummonEnemySlime));
         inventory.insert_free(InventoryItemType::Spell(SpellType::Dash));
+        inventory.insert_free(InventoryItemType::Consumable(ConsumableType::HealingPotion));
+        inventory.insert_free(InventoryItemType::Consumable(ConsumableType::HealingPotion));
+        inventory.insert_free(InventoryItemType::Consumable(ConsumableType::Bomb));
+        inventory.insert_free(InventoryItemType::Consumable(ConsumableType::Bomb));
+        inventory.insert_free(InventoryItemType::Consumable(ConsumableType::Bomb));
         inventory.sort();
 
         let mut equipments = [None; MAX_ITEMS_IN_EQUIPMENT];
         equipments[0] = Some(Equipment {
-            equipment_type: EquipmentType::Lantern,
+            item: EquipmentItem::new(EquipmentType::Lantern),
             price: 0,
         });
 
         let wands = [
             Some(Wand {
-                wand_type: WandType::CypressWand,
+                item: WandItem::new(WandType::CypressWand),
                 price: 0,
                 slots: [
                     Some(WandSpell {
@@ -107,7 +133,7 @@ impl PlayerState {
                 index: 0,
             }),
             Some(Wand {
-                wand_type: WandType::CypressWand,
+                item: WandItem::new(WandType::CypressWand),
                 price: 0,
                 slots: [
                     Some(WandSpell {
@@ -125,7 +151,7 @@ impl PlayerState {
                 index: 0,
             }),
             Some(Wand {
-                wand_type: WandType::KeyWand,
+                item: WandItem::new(WandType::KeyWand),
                 price: 0,
                 slots: [
                     Some(WandSpell {
@@ -143,7 +169,7 @@ impl PlayerState {
                 index: 0,
             }),
             Some(Wand {
-                wand_type: WandType::CypressWand,
+                item: WandItem::new(WandType::CypressWand),
                 price: 0,
                 slots: [
                     Some(WandSpell {
@@ -164,6 +190,7 @@ impl PlayerState {
 
         PlayerState {
             name: config.player_name.clone(),
+            debt: 0,
             life: 60,
             max_life: 60,
             inventory,
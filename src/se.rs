@@ -1,4 +1,4 @@
-use crate::{asset::GameAssets, audio::play_se, config::GameConfig};
+use crate::{asset::GameAssets, audio::play_se, config::GameConfig, entity::actor::ActorGroup};
 use bevy::{prelude::*, utils::hashbrown::HashSet};
 use bevy_rapier2d::plugin::PhysicsSet;
 use std::cmp::Ordering;
@@ -7,6 +7,10 @@ use std::cmp::Ordering;
 pub struct SEEvent {
     se: SE,
     position: Option<Vec2>,
+
+    /// 音を立てたアクターの所属するグループ
+    /// 敵は自分と同じグループが立てた物音には反応しません
+    source: Option<ActorGroup>,
 }
 
 impl SEEvent {
@@ -14,11 +18,33 @@ impl SEEvent {
         Self {
             se,
             position: Some(position),
+            source: None,
         }
     }
 
     pub fn new(se: SE) -> Self {
-        Self { se, position: None }
+        Self {
+            se,
+            position: None,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: ActorGroup) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn source(&self) -> Option<ActorGroup> {
+        self.source
+    }
+
+    pub fn se(&self) -> SE {
+        self.se
+    }
+
+    pub fn position(&self) -> Option<Vec2> {
+        self.position
     }
 }
 
@@ -13,6 +13,9 @@ const SPEECH_BUBBLE_WIDTH: f32 = 160.0;
 
 const SPEECH_BUBBLE_HEIGHT: f32 = 64.0;
 
+/// 1文字を表示するのにかかるフレーム数
+pub const TYPING_DELAY: usize = 4;
+
 #[derive(Component)]
 pub struct SpeechBubble {
     count: usize,
@@ -109,6 +112,13 @@ fn read_speech_events(
     }
 }
 
+/// 文字を1文字ずつ表示する効果で、count フレーム目に表示する文字列を返します
+/// 2番目の値は、まだ表示されていない文字が残っているかどうかです
+pub fn typing_text(text: &str, count: usize) -> (String, bool) {
+    let pos = count / TYPING_DELAY;
+    (text.chars().take(pos).collect(), pos < text.chars().count())
+}
+
 fn countup(
     mut speech_query: Query<(&Visibility, &mut SpeechBubble)>,
     mut text_query: Query<&mut Text, With<SpeechBubbleText>>,
@@ -117,23 +127,12 @@ fn countup(
     let (visibility, mut speech) = speech_query.single_mut();
 
     if *visibility == Visibility::Inherited {
-        const DELAY: usize = 4;
-
-        let pos = speech.count / DELAY;
         let mut text = text_query.single_mut();
-        let chars = speech.text.char_indices();
-        let mut str = "".to_string();
-        let mut s = 0;
-        for (i, val) in chars.enumerate() {
-            if i < pos {
-                str.push(val.1);
-            }
-            s += 1;
-        }
+        let (str, typing) = typing_text(&speech.text, speech.count);
         text.0 = str;
 
-        if pos < s {
-            if speech.count % DELAY == 0 {
+        if typing {
+            if speech.count % TYPING_DELAY == 0 {
                 se.send(SEEvent::new(SE::Kawaii));
             }
 
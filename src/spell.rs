@@ -1,6 +1,7 @@
 use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
 
-#[derive(Reflect, Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Reflect, Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum SpellType {
     MagicBolt,
     PurpleBolt,
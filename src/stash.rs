@@ -0,0 +1,69 @@
+use crate::inventory::{Inventory, InventoryItem};
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+
+/// ホームのレベルに置かれた保管箱の中身です
+/// プレイヤーのインベントリとは異なり、死亡してもリセットされず、セーブデータに保存されます
+#[derive(Resource, Clone, Debug)]
+pub struct Stash {
+    pub inventory: Inventory,
+
+    /// セーブデータを読み込めなかった場合は true になります
+    /// 読み込めなかったデータを空の保管箱で上書きしないよう、保存を行いません
+    load_failed: bool,
+}
+
+impl Default for Stash {
+    fn default() -> Self {
+        Self {
+            inventory: Inventory::new(),
+            load_failed: false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn startup(pkv: Res<PkvStore>, mut stash: ResMut<Stash>) {
+    if let Ok(v) = pkv.get::<String>("stash") {
+        match serde_json::from_str::<Vec<Option<InventoryItem>>>(v.as_str()) {
+            Ok(items) => {
+                for (i, item) in items.into_iter().enumerate() {
+                    if i < stash.inventory.0.len() {
+                        stash.inventory.set(i, item);
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("Failed to load stash: {}", err);
+                stash.load_failed = true;
+            }
+        }
+    };
+}
+
+#[allow(dead_code)]
+fn on_change(mut pkv: ResMut<PkvStore>, stash: Res<Stash>) {
+    if stash.is_changed() && !stash.load_failed {
+        // serde は要素数の多い配列を扱えないため、Vec に変換して保存します
+        let items: Vec<Option<InventoryItem>> = stash.inventory.0.to_vec();
+        if let Ok(serialized) = serde_json::to_string(&items) {
+            if let Err(err) = pkv.set::<String>("stash", &serialized) {
+                warn!("Failed to save stash: {}", err);
+            }
+        } else {
+            warn!("Failed to serialize stash");
+        }
+    }
+}
+
+pub struct StashPlugin;
+
+impl Plugin for StashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stash>();
+        #[cfg(any(not(debug_assertions), target_arch = "wasm32", feature = "save"))]
+        app.add_systems(Startup, startup);
+        #[cfg(any(not(debug_assertions), target_arch = "wasm32", feature = "save"))]
+        app.add_systems(Update, on_change);
+    }
+}
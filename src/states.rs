@@ -56,4 +56,7 @@ pub enum GameMenuState {
     PauseMenuClosing,
 
     WandEditOpen,
+
+    /// チャットの入力欄を開いている状態
+    ChatOpen,
 }
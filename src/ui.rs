@@ -13,8 +13,11 @@ pub mod menu_left;
 pub mod on_press;
 pub mod pause_menu;
 pub mod player_list;
+pub mod quick_use;
 pub mod range;
 pub mod spell_in_wand;
+pub mod stash;
 pub mod wand_editor;
 pub mod wand_list;
 pub mod wand_sprite;
+pub mod workbench;
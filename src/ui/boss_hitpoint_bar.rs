@@ -1,4 +1,4 @@
-use crate::{enemy::huge_slime::Boss, entity::life::Life, states::GameState};
+use crate::{enemy::boss::Boss, entity::life::Life, states::GameState};
 use bevy::prelude::*;
 
 const BAR_WIDTH: f32 = 800.0;
@@ -118,7 +118,7 @@ pub fn spawn_boss_hitpoint_bar(parent: &mut ChildBuilder) {
 
 fn update_bar_visibility(
     mut bar_query: Query<&mut Visibility, With<BossHitpointBar>>,
-    boss_query: Query<(&Name, &Life), With<Boss>>,
+    boss_query: Query<(&Name, &Life, &Boss)>,
     mut rect_query: Query<&mut Node, (With<StatusBarRect>, Without<StatusBarBackground>)>,
     mut text_query: Query<
         &mut Text,
@@ -139,7 +139,9 @@ fn update_bar_visibility(
     >,
 ) {
     for mut visibility in bar_query.iter_mut() {
-        if let Ok((name, life)) = boss_query.get_single() {
+        // 登場演出が終わって戦闘中のボスのライフを表示します
+        let fighting = boss_query.iter().find(|(_, _, boss)| boss.is_fighting());
+        if let Some((name, life, _)) = fighting {
             *visibility = Visibility::Inherited;
 
             for mut rect in rect_query.iter_mut() {
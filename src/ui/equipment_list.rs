@@ -59,8 +59,9 @@ fn update_equipment_sprite(
             panel.0 = match float.content {
                 Some(FloatingContent::Equipment(index)) if index == sprite.index => None,
                 _ => actor.equipments[sprite.index].map(|e| InventoryItem {
-                    item_type: InventoryItemType::Equipment(e.equipment_type),
+                    item_type: InventoryItemType::Equipment(e.item),
                     price: e.price,
+                    count: 1,
                 }),
             };
         }
@@ -1,14 +1,20 @@
 use super::item_panel::{spawn_item_panel, ItemPanel};
 use crate::{
     asset::GameAssets,
-    constant::{MAX_SPELLS_IN_WAND, WAND_EDITOR_FLOATING_Z_INDEX},
+    constant::{
+        MAX_ITEMS_IN_EQUIPMENT, MAX_SPELLS_IN_WAND, MAX_WANDS, WAND_EDITOR_FLOATING_Z_INDEX,
+    },
     controller::player::{Equipment, Player},
-    entity::{actor::Actor, dropped_item::spawn_dropped_item},
+    entity::{
+        actor::Actor,
+        dropped_item::{spawn_dropped_item, DroppedByPlayer},
+    },
     hud::DropArea,
-    inventory::InventoryItem,
+    inventory::{Inventory, InventoryItem},
     inventory_item::InventoryItemType,
     level::{tile::Tile, CurrentLevel},
     se::{SEEvent, SE},
+    stash::Stash,
     states::{GameMenuState, GameState},
     wand::{Wand, WandSpell},
 };
@@ -20,33 +26,40 @@ pub enum FloatingContent {
     WandSpell(usize, usize),
     Wand(usize),
     Equipment(usize),
+
+    /// 保管箱のスロット
+    Stash(usize),
 }
 
 impl FloatingContent {
-    pub fn get_item(&self, actor: &Actor) -> Option<InventoryItem> {
+    pub fn get_item(&self, actor: &Actor, stash: &Stash) -> Option<InventoryItem> {
         match self {
             FloatingContent::Inventory(index) => actor.inventory.get(*index),
+            FloatingContent::Stash(index) => stash.inventory.get(*index),
             FloatingContent::WandSpell(wand_index, spell_index) => actor.wands[*wand_index]
                 .clone()
                 .and_then(|ref wand| match wand.slots[*spell_index] {
                     Some(spell) => Some(InventoryItem {
                         item_type: InventoryItemType::Spell(spell.spell_type),
                         price: spell.price,
+                        count: 1,
                     }),
                     None => None,
                 }),
             FloatingContent::Wand(wand_index) => {
                 actor.wands[*wand_index].clone().and_then(|ref wand| {
                     Some(InventoryItem {
-                        item_type: InventoryItemType::Wand(wand.wand_type),
+                        item_type: InventoryItemType::Wand(wand.item),
                         price: wand.price,
+                        count: 1,
                     })
                 })
             }
             FloatingContent::Equipment(index) => {
                 actor.equipments[*index].clone().map(|ref e| InventoryItem {
-                    item_type: InventoryItemType::Equipment(e.equipment_type),
+                    item_type: InventoryItemType::Equipment(e.item),
                     price: e.price,
+                    count: 1,
                 })
             }
         }
@@ -60,8 +73,8 @@ pub struct Floating {
 }
 
 impl Floating {
-    pub fn get_item(&self, actor: &Actor) -> Option<InventoryItem> {
-        self.content.and_then(|c| c.get_item(actor))
+    pub fn get_item(&self, actor: &Actor, stash: &Stash) -> Option<InventoryItem> {
+        self.content.and_then(|c| c.get_item(actor, stash))
     }
 }
 
@@ -83,10 +96,11 @@ pub fn spawn_inventory_floating(mut builder: &mut ChildBuilder, assets: &Res<Gam
 fn update_item_frame(
     query: Query<&Actor, With<Player>>,
     mut frame_query: Query<(&Floating, &mut ItemPanel)>,
+    stash: Res<Stash>,
 ) {
     if let Ok(actor) = query.get_single() {
         let (floating, mut panel) = frame_query.single_mut();
-        panel.0 = floating.content.and_then(|f| f.get_item(actor));
+        panel.0 = floating.content.and_then(|f| f.get_item(actor, &stash));
     }
 }
 
@@ -135,6 +149,7 @@ fn cancel_on_close(state: Res<State<GameMenuState>>, mut floating_query: Query<&
 
 fn drop(
     mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
     mut floating_query: Query<&mut Floating>,
     mut player_query: Query<&mut Actor, With<Player>>,
     drop_query: Query<&DropArea>,
@@ -144,6 +159,7 @@ fn drop(
     camera_query: Query<(&Camera, &GlobalTransform), (With<Camera2d>, Without<Player>)>,
     map: Res<CurrentLevel>,
     mut se: EventWriter<SEEvent>,
+    mut stash: ResMut<Stash>,
 ) {
     let mut floating = floating_query.single_mut();
     if mouse.just_released(MouseButton::Left) {
@@ -175,16 +191,21 @@ fn drop(
                                         if chunk.get_tile_by_coords(pointer_in_world)
                                             == Tile::StoneTile
                                         {
-                                            if let Some(item) = content.get_inventory_item(&actor) {
+                                            if let Some(item) =
+                                                content.get_inventory_item(&actor, &stash)
+                                            {
                                                 let spells = content.get_wand_spells(&actor);
-                                                content.set_item(None, &spells, &mut actor, false);
+                                                content.set_item(
+                                                    None, &spells, &mut actor, &mut stash, false,
+                                                );
 
-                                                spawn_dropped_item(
+                                                let entity = spawn_dropped_item(
                                                     &mut commands,
                                                     &assets,
                                                     pointer_in_world,
                                                     item,
                                                 );
+                                                commands.entity(entity).insert(DroppedByPlayer);
                                                 floating.content = None;
 
                                                 se.send(SEEvent::new(SE::PickUp));
@@ -196,27 +217,13 @@ fn drop(
                         }
                     }
                 } else if let Some(target) = target_optional {
-                    // 移動元のアイテムを取得
-                    let item_optional_from = content.get_inventory_item(&actor);
-                    // 移動先のアイテムを取得
-                    let item_optional_to = target.get_inventory_item(&actor);
-                    // 移動元が杖の場合は杖に含まれている魔法を取得
-                    let spells_from = content.get_wand_spells(&actor);
-
-                    let spells_to = target.get_wand_spells(&actor);
-
-                    // 移動先に書きこみ
-                    let ok_target =
-                        target.set_item(item_optional_from, &spells_from, &mut actor, true);
-                    // 移動元に書きこみ
-                    let ok_content =
-                        content.set_item(item_optional_to, &spells_to, &mut actor, true);
-
-                    if ok_target && ok_content {
-                        // 移動先に書きこみ
-                        target.set_item(item_optional_from, &spells_from, &mut actor, false);
-                        // 移動元に書きこみ
-                        content.set_item(item_optional_to, &spells_to, &mut actor, false);
+                    if content == target {
+                        // シフトを押しながらクリックした場合は、決まった場所へ素早く移動します
+                        if keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight) {
+                            quick_move(content, &mut actor, &mut stash);
+                        }
+                    } else {
+                        move_item(content, target, &mut actor, &mut stash);
                     }
                 }
             }
@@ -224,31 +231,238 @@ fn drop(
     }
 }
 
+/// content のアイテムを target へ移動します
+/// 重ねられるアイテムは重ね、重ねたアイテムをひとつしか入らない場所へ置く場合はひとつだけ取り出します
+fn move_item(
+    content: FloatingContent,
+    target: FloatingContent,
+    actor: &mut Actor,
+    stash: &mut Stash,
+) {
+    let no_spells = [None; MAX_SPELLS_IN_WAND];
+    let item_optional_from = content.get_inventory_item(actor, stash);
+    let item_optional_to = target.get_inventory_item(actor, stash);
+    match (item_optional_from, item_optional_to) {
+        // 同じ種類のアイテムの上に置いた場合は上限まで重ねます
+        (Some(from), Some(to)) if from.is_stackable_with(&to) && target.can_hold_stack() => {
+            let moved = from
+                .count
+                .min(to.item_type.get_max_stack().saturating_sub(to.count));
+            let merged = InventoryItem {
+                count: to.count + moved,
+                ..to
+            };
+            let rest = if moved < from.count {
+                Some(InventoryItem {
+                    count: from.count - moved,
+                    ..from
+                })
+            } else {
+                None
+            };
+            if 0 < moved
+                && target.set_item(Some(merged), &no_spells, actor, stash, true)
+                && content.set_item(rest, &no_spells, actor, stash, true)
+            {
+                target.set_item(Some(merged), &no_spells, actor, stash, false);
+                content.set_item(rest, &no_spells, actor, stash, false);
+            }
+        }
+        // 重ねたアイテムをひとつしか入らない場所へ置いた場合は、ひとつだけ取り出します
+        // 置き換えられたアイテムはインベントリに戻します
+        (Some(from), _) if 1 < from.count && !target.can_hold_stack() => {
+            let one = InventoryItem { count: 1, ..from };
+            let rest = InventoryItem {
+                count: from.count - 1,
+                ..from
+            };
+            if let Some(to) = item_optional_to {
+                let mut inventory = actor.inventory;
+                if !inventory.insert(to) {
+                    return;
+                }
+            }
+            if target.set_item(Some(one), &no_spells, actor, stash, true) {
+                content.set_item(Some(rest), &no_spells, actor, stash, false);
+                target.set_item(Some(one), &no_spells, actor, stash, false);
+                if let Some(to) = item_optional_to {
+                    actor.inventory.insert(to);
+                }
+            }
+        }
+        // ひとつしか入らない場所へ重ねたアイテムを入れ替えることはできません
+        (_, Some(to)) if 1 < to.count && !content.can_hold_stack() => {}
+        _ => swap_items(content, target, actor, stash),
+    }
+}
+
+/// content と target のアイテムを入れ替えます
+fn swap_items(
+    content: FloatingContent,
+    target: FloatingContent,
+    actor: &mut Actor,
+    stash: &mut Stash,
+) {
+    // 移動元のアイテムを取得
+    let item_optional_from = content.get_inventory_item(actor, stash);
+    // 移動先のアイテムを取得
+    let item_optional_to = target.get_inventory_item(actor, stash);
+    // 移動元が杖の場合は杖に含まれている魔法を取得
+    let spells_from = content.get_wand_spells(actor);
+
+    let spells_to = target.get_wand_spells(actor);
+
+    // 幅のあるアイテムがほかのアイテムと重なる場合は入れ替えられません
+    let inventory_index = |c: FloatingContent| match c {
+        FloatingContent::Inventory(i) => Some(i),
+        _ => None,
+    };
+    let stash_index = |c: FloatingContent| match c {
+        FloatingContent::Stash(i) => Some(i),
+        _ => None,
+    };
+    if !fits_after_swap(
+        &actor.inventory,
+        inventory_index(content),
+        inventory_index(target),
+        item_optional_from,
+        item_optional_to,
+    ) || !fits_after_swap(
+        &stash.inventory,
+        stash_index(content),
+        stash_index(target),
+        item_optional_from,
+        item_optional_to,
+    ) {
+        return;
+    }
+
+    // 移動先に書きこみ
+    let ok_target = target.set_item(item_optional_from, &spells_from, actor, stash, true);
+    // 移動元に書きこみ
+    let ok_content = content.set_item(item_optional_to, &spells_to, actor, stash, true);
+
+    if ok_target && ok_content {
+        // 移動先に書きこみ
+        target.set_item(item_optional_from, &spells_from, actor, stash, false);
+        // 移動元に書きこみ
+        content.set_item(item_optional_to, &spells_to, actor, stash, false);
+    }
+}
+
+/// インベントリの content と target のアイテムを入れ替えたときに、
+/// 幅のあるアイテムがほかのアイテムと重ならずに収まるかどうかを返します
+fn fits_after_swap(
+    inventory: &Inventory,
+    content: Option<usize>,
+    target: Option<usize>,
+    from: Option<InventoryItem>,
+    to: Option<InventoryItem>,
+) -> bool {
+    let mut inventory = *inventory;
+    for index in [content, target].iter().flatten() {
+        inventory.set(*index, None);
+    }
+    for (index, item) in [(target, from), (content, to)] {
+        if let (Some(index), Some(item)) = (index, item) {
+            if !inventory.is_settable(index, item) {
+                return false;
+            }
+            inventory.set(index, Some(item));
+        }
+    }
+    true
+}
+
+/// シフトを押しながらクリックしたアイテムを移動します
+/// インベントリの杖、装備、呪文はそれぞれ杖の欄、装備の欄、選択中の杖の空いているスロットへ移動し、
+/// それ以外の場所のアイテムはインベントリへ戻します
+fn quick_move(content: FloatingContent, actor: &mut Actor, stash: &mut Stash) {
+    let item = match content.get_inventory_item(actor, stash) {
+        Some(item) => item,
+        None => return,
+    };
+    match content {
+        FloatingContent::Inventory(_) => {
+            let target = match item.item_type {
+                InventoryItemType::Wand(..) => (0..MAX_WANDS)
+                    .find(|w| actor.wands[*w].is_none())
+                    .map(FloatingContent::Wand),
+                InventoryItemType::Equipment(..) => (0..MAX_ITEMS_IN_EQUIPMENT)
+                    .find(|e| actor.equipments[*e].is_none())
+                    .map(FloatingContent::Equipment),
+                InventoryItemType::Spell(..) => (0..MAX_WANDS)
+                    .map(|w| (actor.current_wand + w) % MAX_WANDS)
+                    .find_map(|w| {
+                        actor.wands[w].as_ref().and_then(|wand| {
+                            (0..wand.item.capacity)
+                                .find(|s| wand.slots[*s].is_none())
+                                .map(|s| FloatingContent::WandSpell(w, s))
+                        })
+                    }),
+                InventoryItemType::Consumable(..) => None,
+            };
+            if let Some(target) = target {
+                move_item(content, target, actor, stash);
+            }
+        }
+        _ => {
+            // 杖に入っていた呪文も含めて、すべてインベントリに収まる場合だけ移動します
+            let mut inventory = actor.inventory;
+            let spells = content.get_wand_spells(actor);
+            let fits = inventory.insert(item)
+                && spells.iter().flatten().all(|spell| {
+                    inventory.insert(InventoryItem {
+                        item_type: InventoryItemType::Spell(spell.spell_type),
+                        price: spell.price,
+                        count: 1,
+                    })
+                });
+            if fits {
+                content.set_item(None, &[None; MAX_SPELLS_IN_WAND], actor, stash, false);
+                actor.inventory = inventory;
+            }
+        }
+    }
+}
+
 impl FloatingContent {
-    pub fn get_inventory_item(&self, actor: &Actor) -> Option<InventoryItem> {
+    pub fn get_inventory_item(&self, actor: &Actor, stash: &Stash) -> Option<InventoryItem> {
         match self {
             FloatingContent::Inventory(i) => actor.inventory.get(*i),
+            FloatingContent::Stash(i) => stash.inventory.get(*i),
             FloatingContent::WandSpell(w, i) => actor.get_spell(*w, *i).map(|w| InventoryItem {
                 item_type: InventoryItemType::Spell(w.spell_type),
                 price: w.price,
+                count: 1,
             }),
             FloatingContent::Wand(w) => {
                 if let Some(ref wand) = actor.wands[*w] {
                     Some(InventoryItem {
-                        item_type: InventoryItemType::Wand(wand.wand_type),
+                        item_type: InventoryItemType::Wand(wand.item),
                         price: wand.price,
+                        count: 1,
                     })
                 } else {
                     None
                 }
             }
             FloatingContent::Equipment(e) => actor.equipments[*e].map(|e| InventoryItem {
-                item_type: InventoryItemType::Equipment(e.equipment_type),
+                item_type: InventoryItemType::Equipment(e.item),
                 price: e.price,
+                count: 1,
             }),
         }
     }
 
+    /// 重ねたアイテムをそのまま置ける場所かどうかを返します
+    pub fn can_hold_stack(&self) -> bool {
+        match self {
+            FloatingContent::Inventory(..) | FloatingContent::Stash(..) => true,
+            _ => false,
+        }
+    }
+
     pub fn get_wand_spells(&self, actor: &Actor) -> Box<[Option<WandSpell>; MAX_SPELLS_IN_WAND]> {
         Box::new(match self {
             FloatingContent::Wand(w) => {
@@ -267,6 +481,7 @@ impl FloatingContent {
         item: Option<InventoryItem>,
         slots: &[Option<WandSpell>; MAX_SPELLS_IN_WAND],
         actor: &mut Actor,
+        stash: &mut Stash,
         dry_run: bool,
     ) -> bool {
         match (self, item) {
@@ -278,6 +493,25 @@ impl FloatingContent {
                             actor.inventory.insert(InventoryItem {
                                 item_type: InventoryItemType::Spell(spell.spell_type),
                                 price: spell.price,
+                                count: 1,
+                            });
+                        }
+                    }
+                }
+                true
+            }
+            // 未清算の商品は保管箱に入れられません
+            (FloatingContent::Stash(_), Some(InventoryItem { price, .. })) if 0 < price => false,
+            // 保管箱に杖を入れた場合、杖に入っていた呪文はプレイヤーのインベントリに戻ります
+            (FloatingContent::Stash(i), _) => {
+                if !dry_run {
+                    stash.inventory.set(*i, item);
+                    for spell in slots.iter() {
+                        if let Some(spell) = spell {
+                            actor.inventory.insert(InventoryItem {
+                                item_type: InventoryItemType::Spell(spell.spell_type),
+                                price: spell.price,
+                                count: 1,
                             });
                         }
                     }
@@ -287,13 +521,14 @@ impl FloatingContent {
             (
                 FloatingContent::Wand(w),
                 Some(InventoryItem {
-                    item_type: InventoryItemType::Wand(wand_type),
+                    item_type: InventoryItemType::Wand(wand_item),
                     price,
+                    ..
                 }),
             ) => {
                 if !dry_run {
                     actor.wands[*w] = Some(Wand {
-                        wand_type,
+                        item: wand_item,
                         price,
                         slots: *slots,
                         index: 0,
@@ -312,6 +547,7 @@ impl FloatingContent {
                 Some(InventoryItem {
                     item_type: InventoryItemType::Spell(spell_type),
                     price,
+                    ..
                 }),
             ) => {
                 if !dry_run {
@@ -334,11 +570,12 @@ impl FloatingContent {
                 Some(InventoryItem {
                     item_type: InventoryItemType::Equipment(equipment),
                     price,
+                    ..
                 }),
             ) => {
                 if !dry_run {
                     actor.equipments[*e] = Some(Equipment {
-                        equipment_type: equipment,
+                        item: equipment,
                         price,
                     })
                 }
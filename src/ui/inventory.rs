@@ -1,8 +1,11 @@
 use crate::asset::GameAssets;
 use crate::constant::MAX_ITEMS_IN_INVENTORY;
+use crate::consumable::UseConsumableEvent;
 use crate::controller::player::Player;
 use crate::entity::actor::Actor;
-use crate::states::GameState;
+use crate::inventory_item::InventoryItemType;
+use crate::stash::Stash;
+use crate::states::{GameMenuState, GameState};
 use crate::ui::floating::{Floating, FloatingContent};
 use crate::ui::item_information::{SpellInformation, SpellInformationItem};
 use crate::ui::item_panel::{spawn_item_panel, ItemPanel};
@@ -99,6 +102,7 @@ fn interaction(
     mut floating_query: Query<&mut Floating>,
     player_query: Query<(&Actor, &Transform), With<Player>>,
     mut spell_info_query: Query<&mut SpellInformation>,
+    stash: Res<Stash>,
 ) {
     for (slot, interaction) in &mut interaction_query {
         match *interaction {
@@ -117,7 +121,7 @@ fn interaction(
 
                 let mut spell_info = spell_info_query.single_mut();
                 if let Ok((actor, _)) = player_query.get_single() {
-                    let floating_item = floating.get_item(&actor);
+                    let floating_item = floating.get_item(&actor, &stash);
                     if actor.inventory.is_settable_optional(slot.0, floating_item) {
                         *spell_info = match actor.inventory.get(slot.0) {
                             Some(slot_item) => SpellInformation(Some(
@@ -137,6 +141,34 @@ fn interaction(
     }
 }
 
+/// インベントリの消費アイテムを右クリックで使います
+fn use_item_by_right_click(
+    buttons: Res<ButtonInput<MouseButton>>,
+    menu: Res<State<GameMenuState>>,
+    player_query: Query<&Actor, With<Player>>,
+    floating_query: Query<&Floating>,
+    grid_query: Query<&InventoryGrid>,
+    mut writer: EventWriter<UseConsumableEvent>,
+) {
+    if *menu.get() != GameMenuState::WandEditOpen || !buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let floating = floating_query.single();
+    let grid = grid_query.single();
+    if floating.content.is_some() || !grid.hover {
+        return;
+    }
+    if let Some(FloatingContent::Inventory(index)) = floating.target {
+        if let Ok(actor) = player_query.get_single() {
+            if let Some(item) = actor.inventory.get(index) {
+                if let InventoryItemType::Consumable(..) = item.item_type {
+                    writer.send(UseConsumableEvent { index });
+                }
+            }
+        }
+    }
+}
+
 fn root_interaction(
     mut interaction_query: Query<(&Interaction, &mut InventoryGrid), Changed<Interaction>>,
 ) {
@@ -159,7 +191,12 @@ impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (update_inventory_slot, interaction, root_interaction)
+            (
+                update_inventory_slot,
+                interaction,
+                root_interaction,
+                use_item_by_right_click,
+            )
                 .run_if(in_state(GameState::InGame)),
         );
     }
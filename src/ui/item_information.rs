@@ -3,20 +3,24 @@ use crate::ui::floating::Floating;
 use crate::{
     asset::GameAssets,
     config::GameConfig,
-    inventory_item::{get_inventory_item_description, inventory_item_to_props},
+    inventory_item::{
+        get_inventory_item_description, inventory_item_to_props, wand_description,
+        InventoryItemType,
+    },
     states::GameState,
-    wand::WandType,
+    wand::WandItem,
 };
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy_aseprite_ultra::prelude::*;
 
 use super::inventory::InventoryGrid;
+use super::stash::StashGrid;
 
 #[derive(PartialEq, Eq)]
 pub enum SpellInformationItem {
     InventoryItem(InventoryItem),
-    Wand(WandType),
+    Wand(WandItem),
 }
 
 #[derive(Component)]
@@ -123,7 +127,7 @@ fn update_spell_icon(
             slice.name = props.icon.into();
         }
         SpellInformation(Some(SpellInformationItem::Wand(wand))) => {
-            slice.name = wand.to_props().slice.into();
+            slice.name = wand.wand_type.to_props().slice.into();
         }
         _ => {
             slice.name = "empty".into();
@@ -132,7 +136,7 @@ fn update_spell_icon(
 }
 
 fn update_spell_name(
-    mut query: Query<&mut Text, With<SpellName>>,
+    mut query: Query<(&mut Text, &mut TextColor), With<SpellName>>,
     spell_info: Query<&SpellInformation>,
     config: Res<GameConfig>,
     floating_query: Query<&Floating>,
@@ -142,15 +146,28 @@ fn update_spell_name(
         return;
     }
 
-    let mut text = query.single_mut();
+    let (mut text, mut color) = query.single_mut();
     let spell_info = spell_info.single();
+    color.0 = Color::WHITE;
     match spell_info {
+        SpellInformation(Some(SpellInformationItem::InventoryItem(InventoryItem {
+            item_type: InventoryItemType::Equipment(equipment),
+            ..
+        }))) => {
+            text.0 = equipment.name(config.language);
+            color.0 = equipment.rarity.color();
+        }
         SpellInformation(Some(SpellInformationItem::InventoryItem(item))) => {
             let props = inventory_item_to_props(item.item_type);
             text.0 = props.name.get(config.language).to_string();
         }
         SpellInformation(Some(SpellInformationItem::Wand(wand))) => {
-            text.0 = wand.to_props().name.get(config.language).to_string();
+            text.0 = wand
+                .wand_type
+                .to_props()
+                .name
+                .get(config.language)
+                .to_string();
         }
         _ => {
             text.0 = "".to_string();
@@ -179,7 +196,7 @@ fn update_spell_description(
             }
         }
         SpellInformation(Some(SpellInformationItem::Wand(wand))) => {
-            text.0 = wand.to_props().description.get(config.language).to_string();
+            text.0 = wand_description(wand, config.language);
         }
         _ => {
             text.0 = "".to_string();
@@ -191,13 +208,15 @@ fn update_visible(
     mut root_query: Query<&mut Node, With<SpellInformationRoot>>,
     text_query: Query<&SpellInformation>,
     inventory_grid_query: Query<&InventoryGrid>,
+    stash_grid_query: Query<&StashGrid>,
     floating_query: Query<&Floating>,
 ) {
     let mut root = root_query.single_mut();
     let text = text_query.single();
     let grid = inventory_grid_query.single();
+    let hover = grid.hover || stash_grid_query.iter().any(|grid| grid.hover);
     let floating = floating_query.single();
-    root.display = if grid.hover && text.0 != None && floating.content == None {
+    root.display = if hover && text.0 != None && floating.content == None {
         Display::Flex
     } else {
         Display::None
@@ -1,3 +1,4 @@
+use crate::equipment::Rarity;
 use crate::inventory::InventoryItem;
 use crate::inventory_item::InventoryItemType;
 use crate::{asset::GameAssets, states::GameState};
@@ -13,6 +14,14 @@ struct ItemFrame;
 #[derive(Component)]
 struct ChargeAlert;
 
+/// レア度の高い装備を囲む枠
+#[derive(Component)]
+struct RarityFrame;
+
+/// 重ねて持っているアイテムの個数
+#[derive(Component)]
+struct ItemCount;
+
 pub fn spawn_item_panel<T: Component>(
     builder: &mut ChildBuilder,
     assets: &Res<GameAssets>,
@@ -68,6 +77,38 @@ pub fn spawn_item_panel<T: Component>(
                 ..default()
             },
         ));
+        builder.spawn((
+            RarityFrame,
+            ZIndex(2),
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                top: Val::Px(0.0),
+                width: Val::Px(32.0),
+                height: Val::Px(32.0),
+                border: UiRect::all(Val::Px(1.0)),
+                display: Display::None,
+                ..default()
+            },
+            BorderColor(Color::NONE),
+        ));
+        builder.spawn((
+            ItemCount,
+            Text::new(""),
+            TextColor(Color::WHITE),
+            TextFont {
+                font: assets.dotgothic.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            ZIndex(3),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(2.0),
+                bottom: Val::Px(0.0),
+                ..default()
+            },
+        ));
         builder.spawn((
             ChargeAlert,
             AseUiSlice {
@@ -139,6 +180,12 @@ fn update_item_frame(
             }) => {
                 aseprite.name = "wand_frame".into();
             }
+            Some(InventoryItem {
+                item_type: InventoryItemType::Consumable(..),
+                ..
+            }) => {
+                aseprite.name = "equipment_frame".into();
+            }
             _ => {
                 aseprite.name = "empty".into();
             }
@@ -163,6 +210,43 @@ fn update_charge_alert(
     }
 }
 
+fn update_rarity_frame(
+    slot_query: Query<&ItemPanel>,
+    mut children_query: Query<(&Parent, &mut Node, &mut BorderColor), With<RarityFrame>>,
+) {
+    for (parent, mut node, mut border) in children_query.iter_mut() {
+        let slot = slot_query.get(parent.get()).unwrap();
+        match slot.0 {
+            Some(InventoryItem {
+                item_type: InventoryItemType::Equipment(equipment),
+                ..
+            }) if equipment.rarity != Rarity::Common => {
+                node.display = Display::default();
+                border.0 = equipment.rarity.color();
+            }
+            _ => {
+                node.display = Display::None;
+            }
+        }
+    }
+}
+
+fn update_item_count(
+    slot_query: Query<&ItemPanel>,
+    mut children_query: Query<(&Parent, &mut Text), With<ItemCount>>,
+) {
+    for (parent, mut text) in children_query.iter_mut() {
+        let slot = slot_query.get(parent.get()).unwrap();
+        let count = match slot.0 {
+            Some(item) if 1 < item.count => format!("{}", item.count),
+            _ => "".to_string(),
+        };
+        if text.0 != count {
+            text.0 = count;
+        }
+    }
+}
+
 pub struct ItemPanelPlugin;
 
 impl Plugin for ItemPanelPlugin {
@@ -172,6 +256,8 @@ impl Plugin for ItemPanelPlugin {
             (
                 update_inventory_slot,
                 update_charge_alert,
+                update_rarity_frame,
+                update_item_count,
                 update_item_frame,
                 update_panel_width,
             )
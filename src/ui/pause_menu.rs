@@ -1,6 +1,8 @@
 use super::label::spawn_label;
 use crate::config::GameConfig;
-use crate::constant::GAME_MENU_Z_INDEX;
+use crate::constant::{GAME_MENU_Z_INDEX, WEBSOCKET_URL};
+use crate::controller::connection::{Connection, ConnectionStatus};
+use crate::controller::remote::is_online_level;
 use crate::hud::overlay::OverlayEvent;
 use crate::language::{Dict, Languages};
 use crate::level::{CurrentLevel, GameLevel};
@@ -10,6 +12,7 @@ use crate::states::GameMenuState;
 use crate::ui::menu_button::menu_button;
 use crate::ui::range::spawn_range;
 use crate::{asset::GameAssets, states::GameState};
+use bevy::core::FrameCount;
 use bevy::ecs::system::SystemId;
 use bevy::prelude::*;
 use bevy::window::WindowMode;
@@ -28,6 +31,8 @@ struct ButtonShots {
     en: SystemId,
     fullscreen_on: SystemId,
     fullscreen_off: SystemId,
+    coop_on: SystemId,
+    coop_off: SystemId,
     wait: i32,
 }
 
@@ -53,6 +58,8 @@ impl FromWorld for ButtonShots {
             en: world.register_system(en),
             fullscreen_on: world.register_system(fullscreen_on),
             fullscreen_off: world.register_system(fullscreen_off),
+            coop_on: world.register_system(coop_on),
+            coop_off: world.register_system(coop_off),
             wait: 0,
         }
     }
@@ -127,6 +134,49 @@ fn fullscreen_off(
     config.fullscreen = false;
 }
 
+/// 協力プレイをオンにします
+/// 拠点以外の通常のレベルにいて合言葉が入力されている場合は、すぐにサーバーへ接続します
+fn coop_on(
+    mut config: ResMut<GameConfig>,
+    mut writer: EventWriter<SEEvent>,
+    mut websocket: EventWriter<ClientMessage>,
+    mut connection: ResMut<Connection>,
+    current: Res<CurrentLevel>,
+    frame_count: Res<FrameCount>,
+) {
+    writer.send(SEEvent::new(SE::Click));
+    if config.coop {
+        return;
+    }
+    let online = is_online_level(current.level, &config);
+    config.coop = true;
+    if !online && is_online_level(current.level, &config) {
+        websocket.send(ClientMessage::Open(WEBSOCKET_URL.to_string()));
+        connection.begin(&frame_count);
+    }
+}
+
+/// 協力プレイをオフにします
+/// 協力プレイで接続している場合は、すぐにサーバーから切断します
+fn coop_off(
+    mut config: ResMut<GameConfig>,
+    mut writer: EventWriter<SEEvent>,
+    mut websocket: EventWriter<ClientMessage>,
+    mut connection: ResMut<Connection>,
+    current: Res<CurrentLevel>,
+) {
+    writer.send(SEEvent::new(SE::Click));
+    if !config.coop {
+        return;
+    }
+    let online = is_online_level(current.level, &config);
+    config.coop = false;
+    if online && !is_online_level(current.level, &config) {
+        websocket.send(ClientMessage::Close);
+        connection.status = ConnectionStatus::Offline;
+    }
+}
+
 fn setup_game_menu(
     mut commands: Commands,
     assets: Res<GameAssets>,
@@ -253,6 +303,46 @@ fn setup_game_menu(
                             );
                         });
 
+                    parent
+                        .spawn(Node {
+                            column_gap: Val::Px(4.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            spawn_label(
+                                parent,
+                                &assets,
+                                Dict {
+                                    ja: "協力プレイ",
+                                    en: "Co-op",
+                                },
+                            );
+
+                            menu_button(
+                                parent,
+                                &assets,
+                                shots.coop_on,
+                                80.0,
+                                40.0,
+                                Dict {
+                                    ja: "オン",
+                                    en: "On",
+                                },
+                            );
+                            menu_button(
+                                parent,
+                                &assets,
+                                shots.coop_off,
+                                80.0,
+                                40.0,
+                                Dict {
+                                    ja: "オフ",
+                                    en: "Off",
+                                },
+                            );
+                        });
+
                     spawn_range(
                         parent,
                         &assets,
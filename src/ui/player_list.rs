@@ -1,8 +1,12 @@
 use crate::{
     asset::GameAssets,
-    controller::{player::Player, remote::RemotePlayer},
+    config::GameConfig,
+    controller::{
+        player::Player,
+        remote::{is_online_level, RemotePlayer},
+    },
     entity::actor::Actor,
-    level::{CurrentLevel, GameLevel},
+    level::CurrentLevel,
     states::GameState,
 };
 use bevy::prelude::*;
@@ -76,15 +80,15 @@ fn spawn_player_list(mut commands: Commands, assets: Res<GameAssets>) {
 fn update_player_list_visibility(
     mut player_list_query: Query<&mut Node, With<PlayerListRoot>>,
     current: Res<CurrentLevel>,
+    config: Res<GameConfig>,
 ) {
     let mut player_list_root = player_list_query.single_mut();
 
-    player_list_root.display =
-        if current.level != None && current.level == Some(GameLevel::MultiPlayArena) {
-            Display::Flex
-        } else {
-            Display::None
-        };
+    player_list_root.display = if is_online_level(current.level, &config) {
+        Display::Flex
+    } else {
+        Display::None
+    };
 }
 
 /// プレイヤーリストを更新
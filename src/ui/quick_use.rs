@@ -0,0 +1,61 @@
+use super::item_panel::{spawn_item_panel, ItemPanel};
+use crate::asset::GameAssets;
+use crate::consumable::find_quick_use_item;
+use crate::controller::player::Player;
+use crate::entity::actor::Actor;
+use crate::states::GameState;
+use bevy::prelude::*;
+
+/// キーを押したときに使われる消費アイテムを表示するスロット
+#[derive(Component)]
+struct QuickUseSlot;
+
+pub fn spawn_quick_use_slot(parent: &mut ChildBuilder, assets: &Res<GameAssets>) {
+    parent
+        .spawn(Node {
+            width: Val::Px(64.0),
+            height: Val::Px(32.0),
+            ..default()
+        })
+        .with_children(|mut parent| {
+            parent.spawn((
+                Text::new("F"),
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 12.0,
+                    ..default()
+                },
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(8.0),
+                    top: Val::Px(8.0),
+                    ..default()
+                },
+            ));
+
+            spawn_item_panel(&mut parent, &assets, QuickUseSlot, 32.0, 0.0, None, None);
+        });
+}
+
+fn update_quick_use_slot(
+    player_query: Query<&Actor, With<Player>>,
+    mut slot_query: Query<&mut ItemPanel, With<QuickUseSlot>>,
+) {
+    if let Ok(actor) = player_query.get_single() {
+        for mut panel in slot_query.iter_mut() {
+            panel.0 = find_quick_use_item(actor).and_then(|index| actor.inventory.get(index));
+        }
+    }
+}
+
+pub struct QuickUsePlugin;
+
+impl Plugin for QuickUsePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_quick_use_slot.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
@@ -57,6 +57,7 @@ fn update_panel_item(
                     .map(|e| InventoryItem {
                         item_type: InventoryItemType::Spell(e.spell_type),
                         price: e.price,
+                        count: 1,
                     }),
             };
         }
@@ -70,7 +71,7 @@ fn update_spell_sprite_visibility(
     if let Ok(actor) = player_query.get_single() {
         for (sprite, mut visibility) in sprite_query.iter_mut() {
             match actor.get_wand(sprite.wand_index) {
-                Some(wand) if sprite.spell_index < wand.wand_type.to_props().capacity => {
+                Some(wand) if sprite.spell_index < wand.item.capacity => {
                     *visibility = Visibility::default();
                 }
                 _ => *visibility = Visibility::Hidden,
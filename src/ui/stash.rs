@@ -0,0 +1,207 @@
+use crate::asset::GameAssets;
+use crate::constant::{MAX_ITEMS_IN_INVENTORY, WAND_EDITOR_Z_INDEX};
+use crate::controller::player::Player;
+use crate::entity::actor::Actor;
+use crate::entity::stash::{StashChest, STASH_RANGE};
+use crate::stash::Stash;
+use crate::states::{GameMenuState, GameState};
+use crate::ui::floating::{Floating, FloatingContent};
+use crate::ui::inventory::INVENTORY_IMAGE_HEIGHT;
+use crate::ui::item_information::{SpellInformation, SpellInformationItem};
+use crate::ui::item_panel::{spawn_item_panel, ItemPanel};
+use bevy::prelude::*;
+use bevy_aseprite_ultra::prelude::*;
+
+#[derive(Component)]
+struct StashRoot;
+
+#[derive(Component)]
+pub struct StashGrid {
+    pub hover: bool,
+}
+
+#[derive(Component)]
+struct StashItemSlot(usize);
+
+/// 保管箱の中身を表示するグリッドです
+/// プレイヤーが保管箱のそばでインベントリを開いたときだけ、画面の右側に表示されます
+pub fn spawn_stash(builder: &mut ChildBuilder, assets: &Res<GameAssets>) {
+    builder
+        .spawn((
+            StashRoot,
+            GlobalZIndex(WAND_EDITOR_Z_INDEX),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(100.0),
+                width: Val::Px(151.0 * 2.0),
+                height: Val::Px(INVENTORY_IMAGE_HEIGHT * 2.0),
+                display: Display::None,
+                ..default()
+            },
+        ))
+        .with_children(|builder| {
+            // 背景画像
+            builder.spawn((
+                ZIndex(0),
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(151.0 * 2.0),
+                    height: Val::Px(INVENTORY_IMAGE_HEIGHT * 2.0),
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    ..default()
+                },
+                AseUiSlice {
+                    aseprite: assets.atlas.clone(),
+                    name: "inventory".into(),
+                },
+            ));
+
+            builder
+                .spawn((
+                    StashGrid { hover: false },
+                    Interaction::default(),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(16.0 * 8.0 * 2.0),
+                        height: Val::Px(16.0 * 8.0 * 2.0),
+                        left: Val::Px(16.0),
+                        top: Val::Px(16.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|mut builder| {
+                    for i in 0..MAX_ITEMS_IN_INVENTORY {
+                        spawn_item_panel(
+                            &mut builder,
+                            &assets,
+                            StashItemSlot(i),
+                            (i % 8) as f32 * 32.0,
+                            (i / 8) as f32 * 32.0,
+                            None,
+                            None,
+                        );
+                    }
+                });
+        });
+}
+
+fn update_stash_visibility(
+    mut root_query: Query<&mut Node, With<StashRoot>>,
+    player_query: Query<&Transform, With<Player>>,
+    chest_query: Query<&Transform, With<StashChest>>,
+    state: Res<State<GameMenuState>>,
+) {
+    if let Ok(mut root) = root_query.get_single_mut() {
+        let near = player_query.get_single().is_ok_and(|player| {
+            chest_query.iter().any(|chest| {
+                player
+                    .translation
+                    .truncate()
+                    .distance(chest.translation.truncate())
+                    < STASH_RANGE
+            })
+        });
+        let display = if near && *state.get() == GameMenuState::WandEditOpen {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if root.display != display {
+            root.display = display;
+        }
+    }
+}
+
+fn update_stash_slot(
+    stash: Res<Stash>,
+    mut slot_query: Query<(&StashItemSlot, &mut ItemPanel)>,
+    floating_query: Query<&Floating>,
+) {
+    let floating = floating_query.single();
+    for (slot, mut panel) in slot_query.iter_mut() {
+        match floating.content {
+            Some(FloatingContent::Stash(i)) if i == slot.0 => {
+                panel.0 = None;
+            }
+            _ => {
+                panel.0 = stash.inventory.get(slot.0);
+            }
+        }
+    }
+}
+
+fn interaction(
+    mut interaction_query: Query<(&StashItemSlot, &Interaction), Changed<Interaction>>,
+    mut floating_query: Query<&mut Floating>,
+    player_query: Query<&Actor, With<Player>>,
+    mut spell_info_query: Query<&mut SpellInformation>,
+    stash: Res<Stash>,
+) {
+    for (slot, interaction) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let mut floating = floating_query.single_mut();
+                if floating.content.is_none() {
+                    floating.content = Some(FloatingContent::Stash(slot.0));
+                }
+            }
+            Interaction::Hovered => {
+                let mut floating = floating_query.single_mut();
+                floating.target = Some(FloatingContent::Stash(slot.0));
+
+                let mut spell_info = spell_info_query.single_mut();
+                if let Ok(actor) = player_query.get_single() {
+                    let floating_item = floating.get_item(&actor, &stash);
+                    if stash.inventory.is_settable_optional(slot.0, floating_item) {
+                        *spell_info = match stash.inventory.get(slot.0) {
+                            Some(slot_item) => SpellInformation(Some(
+                                SpellInformationItem::InventoryItem(slot_item),
+                            )),
+                            None => SpellInformation(None),
+                        };
+                    } else {
+                        *spell_info = SpellInformation(None);
+                    }
+                } else {
+                    *spell_info = SpellInformation(None);
+                }
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+fn root_interaction(
+    mut interaction_query: Query<(&Interaction, &mut StashGrid), Changed<Interaction>>,
+) {
+    for (interaction, mut grid) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {}
+            Interaction::Hovered => {
+                grid.hover = true;
+            }
+            Interaction::None => {
+                grid.hover = false;
+            }
+        }
+    }
+}
+
+pub struct StashGridPlugin;
+
+impl Plugin for StashGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                update_stash_visibility,
+                update_stash_slot,
+                interaction,
+                root_interaction,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
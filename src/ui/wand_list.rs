@@ -1,7 +1,9 @@
 use crate::asset::GameAssets;
+use crate::config::GameConfig;
 use crate::constant::{MAX_SPELLS_IN_WAND, MAX_WANDS};
 use crate::controller::player::Player;
 use crate::entity::actor::Actor;
+use crate::inventory_item::wand_stats;
 use crate::states::GameState;
 use crate::ui::floating::Floating;
 use crate::ui::floating::FloatingContent;
@@ -24,6 +26,12 @@ pub struct WandSlot {
 #[derive(Component)]
 struct TriggerMarker;
 
+/// 杖の個体ごとの性能を表示するラベル
+#[derive(Component)]
+struct WandStatsLabel {
+    wand_index: usize,
+}
+
 pub fn spawn_wand_list(parent: &mut ChildBuilder, assets: &Res<GameAssets>) {
     parent
         .spawn((
@@ -63,6 +71,22 @@ fn spawn_wand_and_spell_slot(
                 spawn_wand_spell_slot(&mut parent, &assets, wand_index, spell_index);
             }
 
+            parent.spawn((
+                WandStatsLabel { wand_index },
+                Text::new(""),
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 10.0,
+                    ..default()
+                },
+                Node {
+                    margin: UiRect::left(Val::Px(4.0)),
+                    align_self: AlignSelf::Center,
+                    ..default()
+                },
+            ));
+
             parent.spawn((
                 TriggerMarker,
                 AseUiSlice {
@@ -120,13 +144,48 @@ fn update_wand_slot_visibility(
     }
 }
 
+fn update_wand_stats_label(
+    mut label_query: Query<(&WandStatsLabel, &mut Text)>,
+    actor_query: Query<&Actor, With<Player>>,
+    config: Res<GameConfig>,
+) {
+    if let Ok(actor) = actor_query.get_single() {
+        for (label, mut text) in label_query.iter_mut() {
+            let value = match actor.get_wand(label.wand_index) {
+                Some(wand) => {
+                    let stats = wand_stats(&wand.item, config.language);
+                    match wand.item.always_cast {
+                        Some(spell) => format!(
+                            "{}\n{}",
+                            stats,
+                            config.language.m17n(
+                                format!("常時詠唱:{}", spell.to_props().name.ja),
+                                format!("Always Cast:{}", spell.to_props().name.en),
+                            )
+                        ),
+                        None => stats,
+                    }
+                }
+                None => "".to_string(),
+            };
+            if text.0 != value {
+                text.0 = value;
+            }
+        }
+    }
+}
+
 pub struct WandListPlugin;
 
 impl Plugin for WandListPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (update_wand_slot_visibility, update_trigger_marker)
+            (
+                update_wand_slot_visibility,
+                update_trigger_marker,
+                update_wand_stats_label,
+            )
                 .run_if(in_state(GameState::InGame)),
         );
     }
@@ -30,6 +30,7 @@ pub fn spawn_wand_sprite_in_list(
             height: Val::Px(32.),
             ..default()
         },
+        ImageNode::default(),
         AseUiSlice {
             aseprite: assets.atlas.clone(),
             name: "empty".into(),
@@ -39,19 +40,23 @@ pub fn spawn_wand_sprite_in_list(
 
 fn update_wand_sprite(
     player_query: Query<&Actor, With<Player>>,
-    mut sprite_query: Query<(&WandSprite, &mut AseUiSlice)>,
+    mut sprite_query: Query<(&WandSprite, &mut AseUiSlice, &mut ImageNode)>,
     floating_query: Query<&Floating>,
 ) {
     let floating = floating_query.single();
 
     if let Ok(actor) = player_query.get_single() {
-        for (wand_sprite, mut aseprite) in sprite_query.iter_mut() {
+        for (wand_sprite, mut aseprite, mut image) in sprite_query.iter_mut() {
+            image.color = match &actor.wands[wand_sprite.wand_index] {
+                Some(wand) => wand.item.color(),
+                None => Color::WHITE,
+            };
             aseprite.name = match floating.content {
                 Some(FloatingContent::Wand(wand_index)) if wand_index == wand_sprite.wand_index => {
                     "empty".into()
                 }
                 _ => match &actor.wands[wand_sprite.wand_index] {
-                    Some(wand) => wand.wand_type.to_props().icon.into(),
+                    Some(wand) => wand.item.wand_type.to_props().icon.into(),
                     None => "empty".into(),
                 },
             }
@@ -98,7 +103,7 @@ fn update_wand_information(
                 if let Ok(actor) = player_query.get_single_mut() {
                     if let Some(ref wand) = actor.wands[slot.wand_index] {
                         let mut info = spell_information_query.single_mut();
-                        *info = SpellInformation(Some(SpellInformationItem::Wand(wand.wand_type)));
+                        *info = SpellInformation(Some(SpellInformationItem::Wand(wand.item)));
                     }
                 }
             }
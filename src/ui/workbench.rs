@@ -0,0 +1,418 @@
+use crate::asset::GameAssets;
+use crate::config::GameConfig;
+use crate::constant::{MAX_WANDS, WAND_EDITOR_Z_INDEX};
+use crate::controller::player::Player;
+use crate::entity::actor::Actor;
+use crate::entity::workbench::{Workbench, WORKBENCH_RANGE};
+use crate::inventory::{Inventory, InventoryItem};
+use crate::inventory_item::{wand_stats, InventoryItemType};
+use crate::language::Dict;
+use crate::se::{SEEvent, SE};
+use crate::states::{GameMenuState, GameState};
+use crate::ui::command_button::{command_button, CommandButton};
+use crate::ui::floating::Floating;
+use crate::wand::Wand;
+use bevy::prelude::*;
+
+const WORKBENCH_THEME_COLOR: Color = Color::hsla(30.0, 0.2, 0.3, 0.95);
+
+/// 作業台の画面です
+/// base は合成やスロット追加の対象となる杖、material は合成で消費される杖の番号です
+#[derive(Component)]
+struct WorkbenchRoot {
+    base: usize,
+    material: usize,
+}
+
+#[derive(Component)]
+struct WorkbenchPreview;
+
+#[derive(Component)]
+struct BaseButton;
+
+#[derive(Component)]
+struct MaterialButton;
+
+#[derive(Component)]
+struct MergeButton;
+
+#[derive(Component)]
+struct AddSlotButton;
+
+/// 作業台の画面を生成します
+/// プレイヤーが作業台のそばでインベントリを開いたときだけ、画面の右側に表示されます
+pub fn spawn_workbench_panel(builder: &mut ChildBuilder, assets: &Res<GameAssets>) {
+    builder
+        .spawn((
+            WorkbenchRoot {
+                base: 0,
+                material: 1,
+            },
+            GlobalZIndex(WAND_EDITOR_Z_INDEX),
+            BackgroundColor(WORKBENCH_THEME_COLOR),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(100.0),
+                width: Val::Px(151.0 * 2.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                WorkbenchPreview,
+                Text::new(""),
+                TextColor(Color::WHITE),
+                TextFont {
+                    font: assets.dotgothic.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                Node {
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+            ));
+
+            command_button(
+                parent,
+                assets,
+                BaseButton,
+                280.0,
+                32.0,
+                false,
+                Dict {
+                    ja: "土台を切り替え",
+                    en: "Switch Base",
+                },
+            );
+
+            command_button(
+                parent,
+                assets,
+                MaterialButton,
+                280.0,
+                32.0,
+                false,
+                Dict {
+                    ja: "素材を切り替え",
+                    en: "Switch Material",
+                },
+            );
+
+            command_button(
+                parent,
+                assets,
+                MergeButton,
+                280.0,
+                32.0,
+                false,
+                Dict {
+                    ja: "合成",
+                    en: "Merge",
+                },
+            );
+
+            command_button(
+                parent,
+                assets,
+                AddSlotButton,
+                280.0,
+                32.0,
+                false,
+                Dict {
+                    ja: "スロット追加",
+                    en: "Add Slot",
+                },
+            );
+        });
+}
+
+/// 未清算の杖や呪文は作業台で扱えません
+fn is_paid(wand: &Wand) -> bool {
+    wand.price == 0 && wand.dept() == 0
+}
+
+/// 次に選択する杖の番号を返します
+fn next_wand(actor: &Actor, current: usize) -> usize {
+    (1..=MAX_WANDS)
+        .map(|i| (current + i) % MAX_WANDS)
+        .find(|i| actor.wands[*i].is_some())
+        .unwrap_or(current)
+}
+
+/// 土台の杖に素材の杖を合成した結果と、入りきらなかった呪文を戻したあとのインベントリを返します
+/// 素材の杖の呪文は土台の空きスロットへ移り、残りはインベントリに戻ります
+/// インベントリに空きがない場合など、合成できないときは None を返します
+fn merge_wands(actor: &Actor, base: usize, material: usize) -> Option<(Wand, Inventory)> {
+    if base == material {
+        return None;
+    }
+    match (&actor.wands[base], &actor.wands[material]) {
+        (Some(base), Some(material)) if is_paid(base) && is_paid(material) => {
+            let mut wand = base.clone();
+            wand.item = base.item.merge(&material.item);
+            wand.index = 0;
+            let mut inventory = actor.inventory.clone();
+            for spell in material.slots.iter().flatten() {
+                match (0..wand.item.capacity).find(|i| wand.slots[*i].is_none()) {
+                    Some(i) => {
+                        wand.slots[i] = Some(*spell);
+                    }
+                    None => {
+                        let item = InventoryItem {
+                            item_type: InventoryItemType::Spell(spell.spell_type),
+                            price: spell.price,
+                            count: 1,
+                        };
+                        if !inventory.insert(item) {
+                            return None;
+                        }
+                    }
+                }
+            }
+            Some((wand, inventory))
+        }
+        _ => None,
+    }
+}
+
+/// 土台の杖にスロットを追加するのに必要なゴールドを返します
+/// ゴールドが足りない場合やスロットが最大数の場合は None を返します
+fn add_slot_price(actor: &Actor, base: usize) -> Option<u32> {
+    match &actor.wands[base] {
+        Some(wand) if is_paid(wand) => wand
+            .item
+            .add_slot_price()
+            .filter(|price| *price as i32 <= actor.golds),
+        _ => None,
+    }
+}
+
+fn update_workbench_visibility(
+    mut root_query: Query<&mut Node, With<WorkbenchRoot>>,
+    player_query: Query<&Transform, With<Player>>,
+    workbench_query: Query<&Transform, With<Workbench>>,
+    state: Res<State<GameMenuState>>,
+) {
+    if let Ok(mut root) = root_query.get_single_mut() {
+        let near = player_query.get_single().is_ok_and(|player| {
+            workbench_query.iter().any(|workbench| {
+                player
+                    .translation
+                    .truncate()
+                    .distance(workbench.translation.truncate())
+                    < WORKBENCH_RANGE
+            })
+        });
+        let display = if near && *state.get() == GameMenuState::WandEditOpen {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if root.display != display {
+            root.display = display;
+        }
+    }
+}
+
+fn update_preview(
+    root_query: Query<&WorkbenchRoot>,
+    mut preview_query: Query<&mut Text, With<WorkbenchPreview>>,
+    player_query: Query<&Actor, With<Player>>,
+    config: Res<GameConfig>,
+) {
+    if let Ok(root) = root_query.get_single() {
+        if let Ok(actor) = player_query.get_single() {
+            let mut preview = preview_query.single_mut();
+            let lang = config.language;
+            let describe = |index: usize| match &actor.wands[index] {
+                Some(wand) => format!(
+                    "{} ({})",
+                    wand.item.wand_type.to_props().name.get(lang),
+                    wand.item.capacity
+                ),
+                None => "-".to_string(),
+            };
+
+            let base = format!(
+                "{}: [{}] {}",
+                Dict {
+                    ja: "土台",
+                    en: "Base"
+                }
+                .get(lang),
+                root.base + 1,
+                describe(root.base)
+            );
+            let material = format!(
+                "{}: [{}] {}",
+                Dict {
+                    ja: "素材",
+                    en: "Material"
+                }
+                .get(lang),
+                root.material + 1,
+                describe(root.material)
+            );
+            let merged = match merge_wands(actor, root.base, root.material) {
+                Some((wand, _)) => format!(
+                    "{}\n{}",
+                    Dict {
+                        ja: "合成後:",
+                        en: "After merge:"
+                    }
+                    .get(lang),
+                    wand_stats(&wand.item, lang)
+                ),
+                None => lang.m17n("合成できません".to_string(), "Cannot merge".to_string()),
+            };
+            let slot = match (&actor.wands[root.base], add_slot_price(actor, root.base)) {
+                (Some(wand), Some(price)) => lang.m17n(
+                    format!(
+                        "スロット追加: {} → {} ({}ゴールド)",
+                        wand.item.capacity,
+                        wand.item.capacity + 1,
+                        price
+                    ),
+                    format!(
+                        "Add slot: {} → {} ({} Golds)",
+                        wand.item.capacity,
+                        wand.item.capacity + 1,
+                        price
+                    ),
+                ),
+                _ => lang.m17n(
+                    "スロットを追加できません".to_string(),
+                    "Cannot add a slot".to_string(),
+                ),
+            };
+            preview.0 = format!("{}\n{}\n{}\n{}", base, material, merged, slot);
+        }
+    }
+}
+
+fn switch_buttons_disabled(
+    root_query: Query<&WorkbenchRoot>,
+    floating_query: Query<&Floating>,
+    player_query: Query<&Actor, With<Player>>,
+    mut merge_query: Query<&mut CommandButton, (With<MergeButton>, Without<AddSlotButton>)>,
+    mut slot_query: Query<&mut CommandButton, (With<AddSlotButton>, Without<MergeButton>)>,
+) {
+    if let Ok(root) = root_query.get_single() {
+        let floating = floating_query.single();
+        let (merge, slot) = match player_query.get_single() {
+            Ok(actor) if floating.content.is_none() => (
+                merge_wands(actor, root.base, root.material).is_some(),
+                add_slot_price(actor, root.base).is_some(),
+            ),
+            _ => (false, false),
+        };
+        if let Ok(mut button) = merge_query.get_single_mut() {
+            button.disabled = !merge;
+        }
+        if let Ok(mut button) = slot_query.get_single_mut() {
+            button.disabled = !slot;
+        }
+    }
+}
+
+fn select_button_pressed(
+    mut root_query: Query<&mut WorkbenchRoot>,
+    base_query: Query<&Interaction, (With<BaseButton>, Changed<Interaction>)>,
+    material_query: Query<&Interaction, (With<MaterialButton>, Changed<Interaction>)>,
+    player_query: Query<&Actor, With<Player>>,
+    mut se: EventWriter<SEEvent>,
+) {
+    if let Ok(mut root) = root_query.get_single_mut() {
+        if let Ok(actor) = player_query.get_single() {
+            for interaction in base_query.iter() {
+                if *interaction == Interaction::Pressed {
+                    root.base = next_wand(actor, root.base);
+                    se.send(SEEvent::new(SE::Click));
+                }
+            }
+            for interaction in material_query.iter() {
+                if *interaction == Interaction::Pressed {
+                    root.material = next_wand(actor, root.material);
+                    se.send(SEEvent::new(SE::Click));
+                }
+            }
+        }
+    }
+}
+
+fn merge_button_pressed(
+    root_query: Query<&WorkbenchRoot>,
+    interaction_query: Query<
+        (&Interaction, &CommandButton),
+        (With<MergeButton>, Changed<Interaction>),
+    >,
+    mut player_query: Query<&mut Actor, With<Player>>,
+    mut se: EventWriter<SEEvent>,
+) {
+    if let Ok(root) = root_query.get_single() {
+        if let Ok(mut actor) = player_query.get_single_mut() {
+            for (interaction, button) in interaction_query.iter() {
+                if *interaction == Interaction::Pressed && !button.disabled {
+                    if let Some((wand, inventory)) = merge_wands(&actor, root.base, root.material) {
+                        actor.wands[root.base] = Some(wand);
+                        actor.wands[root.material] = None;
+                        actor.inventory = inventory;
+                        se.send(SEEvent::new(SE::Register));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn add_slot_button_pressed(
+    root_query: Query<&WorkbenchRoot>,
+    interaction_query: Query<
+        (&Interaction, &CommandButton),
+        (With<AddSlotButton>, Changed<Interaction>),
+    >,
+    mut player_query: Query<&mut Actor, With<Player>>,
+    mut se: EventWriter<SEEvent>,
+) {
+    if let Ok(root) = root_query.get_single() {
+        if let Ok(mut actor) = player_query.get_single_mut() {
+            for (interaction, button) in interaction_query.iter() {
+                if *interaction == Interaction::Pressed && !button.disabled {
+                    if let Some(price) = add_slot_price(&actor, root.base) {
+                        actor.golds -= price as i32;
+                        if let Some(ref mut wand) = actor.wands[root.base] {
+                            wand.item.capacity += 1;
+                        }
+                        se.send(SEEvent::new(SE::Register));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct WorkbenchPanelPlugin;
+
+impl Plugin for WorkbenchPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                update_workbench_visibility,
+                update_preview,
+                switch_buttons_disabled,
+                select_button_pressed,
+                merge_button_pressed,
+                add_slot_button_pressed,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
@@ -1,12 +1,164 @@
 use crate::{constant::MAX_SPELLS_IN_WAND, spell::SpellType};
+use bevy::color::Color;
 use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
 
-#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum WandType {
     CypressWand,
     KeyWand,
 }
 
+pub const WAND_TYPES: [WandType; 2] = [WandType::CypressWand, WandType::KeyWand];
+
+/// 作業台でスロットをひとつ追加するときの、現在のスロット数あたりの価格
+pub const SLOT_PRICE_PER_CAPACITY: u32 = 20;
+
+/// 生成される杖に常時詠唱の呪文が付く、レベル1での確率
+/// 深い階層ほど確率が上がります
+const ALWAYS_CAST_CHANCE: f32 = 0.1;
+
+const ALWAYS_CAST_CHANCE_PER_LEVEL: f32 = 0.03;
+
+/// 生成される杖の色合いの数
+/// 0 は元の色のままで、それ以外は色相をずらして着色します
+const WAND_TINTS: u8 = 7;
+
+/// 常時詠唱の呪文の候補
+const ALWAYS_CAST_SPELLS: [SpellType; 4] = [
+    SpellType::MagicBolt,
+    SpellType::HeavyShot,
+    SpellType::Homing,
+    SpellType::BulletSpeedUp,
+];
+
+/// 杖の個体ごとの性能です
+/// 作業台で強化した性能やランダムに生成された性能がインベントリや保管箱に移しても失われないよう、
+/// 杖の種類とあわせて保持します
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct WandItem {
+    /// 杖の種類
+    /// スプライトや名前はこの種類で決まります
+    pub wand_type: WandType,
+
+    /// 呪文を入れられるスロットの数
+    pub capacity: usize,
+
+    /// 呪文の詠唱遅延に加算されるフレーム数
+    #[serde(default)]
+    pub cast_delay: i32,
+
+    /// 呪文を一巡したときに追加される詠唱遅延のフレーム数
+    #[serde(default)]
+    pub reload: u32,
+
+    /// 弾丸の発射方向のばらつきに加算される角度(度)
+    #[serde(default)]
+    pub spread: u32,
+
+    /// 杖を振るたびに、スロットの呪文とは別に唱えられる呪文
+    #[serde(default)]
+    pub always_cast: Option<SpellType>,
+
+    /// 杖のスプライトの色合い
+    /// 同じ種類の杖でも個体ごとに見分けられるようにします
+    #[serde(default)]
+    pub tint: u8,
+}
+
+impl WandItem {
+    pub fn new(wand_type: WandType) -> Self {
+        Self {
+            wand_type,
+            capacity: wand_type.to_props().capacity,
+            cast_delay: 0,
+            reload: 0,
+            spread: 0,
+            always_cast: None,
+            tint: 0,
+        }
+    }
+
+    /// ランダムな性能の杖を生成します
+    /// 深い階層ほどスロットが多く詠唱遅延の短い杖が出やすくなります
+    pub fn random(depth: i32) -> Self {
+        let depth = depth.max(0);
+        let wand_type = WAND_TYPES[rand::random::<usize>() % WAND_TYPES.len()];
+        let capacity = (2 + rand::random::<usize>() % (3 + depth as usize)).min(MAX_SPELLS_IN_WAND);
+        let cast_delay = (rand::random::<u32>() % 21) as i32 - 5 - depth.min(5);
+        let reload = rand::random::<u32>() % 40;
+        let spread = rand::random::<u32>() % 15;
+        let chance = ALWAYS_CAST_CHANCE + ALWAYS_CAST_CHANCE_PER_LEVEL * depth as f32;
+        let always_cast = if rand::random::<f32>() < chance {
+            Some(ALWAYS_CAST_SPELLS[rand::random::<usize>() % ALWAYS_CAST_SPELLS.len()])
+        } else {
+            None
+        };
+        let tint = rand::random::<u8>() % WAND_TINTS;
+        Self {
+            wand_type,
+            capacity,
+            cast_delay,
+            reload,
+            spread,
+            always_cast,
+            tint,
+        }
+    }
+
+    /// スプライトに乗算する色
+    pub fn color(&self) -> Color {
+        if self.tint == 0 {
+            Color::WHITE
+        } else {
+            let hue = 360.0 * (self.tint - 1) as f32 / (WAND_TINTS - 1) as f32;
+            Color::hsl(hue, 0.7, 0.75)
+        }
+    }
+
+    /// ショップでの基本価格
+    /// 種類ごとの標準より多いスロットは、作業台で順に追加した場合と同じ価格で上乗せされます
+    /// 常時詠唱の呪文があればその分も高くなります
+    pub fn price(&self) -> u32 {
+        let props = self.wand_type.to_props();
+        let extra: u32 = (props.capacity..self.capacity).map(slot_price).sum();
+        let always_cast = self.always_cast.map(|s| s.to_props().price).unwrap_or(0);
+        props.price + extra + always_cast
+    }
+
+    /// スロットを追加するのに必要なゴールド
+    /// すでに最大数のスロットがある場合は None を返します
+    pub fn add_slot_price(&self) -> Option<u32> {
+        if self.capacity < MAX_SPELLS_IN_WAND {
+            Some(slot_price(self.capacity))
+        } else {
+            None
+        }
+    }
+
+    /// ふたつの杖を合成したときの性能を返します
+    /// 杖の種類は土台となる杖のものを引き継ぎ、スロット数は大きいほうに小さいほうの半分を加えた数になります
+    /// 詠唱遅延、リロード時間、ばらつきはそれぞれ良いほうを選び、常時詠唱の呪文は土台の杖のものを優先します
+    pub fn merge(&self, material: &WandItem) -> WandItem {
+        let large = self.capacity.max(material.capacity);
+        let small = self.capacity.min(material.capacity);
+        WandItem {
+            wand_type: self.wand_type,
+            capacity: (large + small / 2).min(MAX_SPELLS_IN_WAND),
+            cast_delay: self.cast_delay.min(material.cast_delay),
+            reload: self.reload.min(material.reload),
+            spread: self.spread.min(material.spread),
+            always_cast: self.always_cast.or(material.always_cast),
+            tint: self.tint,
+        }
+    }
+}
+
+/// スロットが capacity 個ある杖に、スロットをひとつ追加する価格
+fn slot_price(capacity: usize) -> u32 {
+    SLOT_PRICE_PER_CAPACITY * capacity as u32
+}
+
 #[derive(Reflect, Clone, Copy, Debug)]
 pub struct WandSpell {
     pub spell_type: SpellType,
@@ -15,7 +167,7 @@ pub struct WandSpell {
 
 #[derive(Reflect, Clone, Debug)]
 pub struct Wand {
-    pub wand_type: WandType,
+    pub item: WandItem,
     pub price: u32,
     pub slots: [Option<WandSpell>; MAX_SPELLS_IN_WAND],
     pub index: usize,
@@ -31,11 +183,11 @@ impl Wand {
     }
 
     pub fn shift(&mut self) {
-        let props = self.wand_type.to_props();
-        self.index = (self.index + 1) % props.capacity;
+        let capacity = self.item.capacity;
+        self.index = (self.index + 1) % capacity;
         for _ in 0..MAX_SPELLS_IN_WAND {
             if self.slots[self.index].is_none() {
-                self.index = (self.index + 1) % props.capacity;
+                self.index = (self.index + 1) % capacity;
                 continue;
             } else {
                 break;
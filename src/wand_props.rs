@@ -6,6 +6,9 @@ pub struct WandProps {
     pub slice: &'static str,
     pub icon: &'static str,
     pub capacity: usize,
+
+    /// ショップでの基本価格
+    pub price: u32,
 }
 
 const CYPRESS_WAND: WandProps = WandProps {
@@ -20,6 +23,7 @@ const CYPRESS_WAND: WandProps = WandProps {
     slice: "wand_cypress",
     icon: "wand_icon_cypress",
     capacity: 8,
+    price: 50,
 };
 
 const KEY_WAND: WandProps = WandProps {
@@ -34,6 +38,7 @@ const KEY_WAND: WandProps = WandProps {
     slice: "wand_key",
     icon: "wand_icon_key",
     capacity: 4,
+    price: 100,
 };
 
 impl WandType {